@@ -1,9 +1,18 @@
 pub mod add;
 pub mod branch;
+pub mod cat_file;
 pub mod checkout;
 pub mod commit;
+pub mod config;
 pub mod diff;
+pub mod fsck;
+pub mod gc;
 pub mod init;
 pub mod log;
 pub mod merge;
+pub mod migrate;
+pub mod reflog;
+pub mod repack;
 pub mod status;
+pub mod tag;
+pub mod unlock;
@@ -1,29 +1,35 @@
 use rayon::prelude::*;
 use std::sync::{Arc, Mutex};
 
-use std::{
-    fs,
-    path::{Path, PathBuf},
-};
+use std::{fs, path::Path};
 
+use crate::commands::gc::{GcReport, auto_gc, print_auto_gc_report};
+use crate::repository::Repository;
 use crate::utils::path::RepoLocationError::*;
 use crate::{
     error_data,
     utils::{
         error::ErrorData,
-        hash::calculate_hash,
         index::{IndexData, get_svn_ignore, ignore_file},
+        object::write_object_from_file,
         path::{
-            FileInfo, copy_to_repo_objects, find_repo_root, format_path,
-            get_working_directory_optimized, is_path_within,
+            FileInfo, RepoLocation, file_mode, find_repo_root, format_path,
+            get_working_directory_optimized, is_path_within, same_mode,
         },
     },
 };
 
+// Un fisier este re-adaugat cand data modificarii sau modul s-au schimbat
+fn needs_staging(staged: Option<&FileInfo>, file: &FileInfo) -> bool {
+    match staged {
+        Some(staged) => staged.timestamp != file.timestamp || !same_mode(staged.mode, file.mode),
+        None => true,
+    }
+}
+
 fn add_files_parallel(
     files_to_add: &[(String, FileInfo)],
     root: &str,
-    objects: &Path,
     index_data: &mut IndexData,
 ) -> Result<(), ErrorData> {
     let index_data = Arc::new(Mutex::new(index_data));
@@ -39,11 +45,11 @@ fn add_files_parallel(
     files_to_add.par_chunks(chunk_size).try_for_each(|chunk| {
         for (path, info) in chunk {
             let absolute_file_path = format_path(&vec![&root, &path]);
-            let file_hash = calculate_hash(&absolute_file_path).map_err(|e| {
+            let file_hash = write_object_from_file(root, &absolute_file_path).map_err(|e| {
                 error_data!(
                     "add_files_parallel",
                     e.to_string(),
-                    "Failed to calculate hash for file"
+                    "Failed to copy file to objects directory"
                 )
             })?;
 
@@ -59,26 +65,21 @@ fn add_files_parallel(
                     }
                 };
 
+                // Modul deja inregistrat ramane daca sistemul nu il poate detecta
+                let mode = match index_data.entries.get(path) {
+                    Some(staged) if same_mode(staged.mode, info.mode) => staged.mode,
+                    _ => info.mode,
+                };
+
                 index_data.entries.insert(
                     path.clone(),
                     FileInfo {
-                        hash: file_hash.clone(),
+                        hash: file_hash,
                         timestamp: info.timestamp,
+                        mode,
                     },
                 );
             }
-
-            if !objects.join(file_hash.clone()).exists() {
-                copy_to_repo_objects(&absolute_file_path, &objects.join(&file_hash)).map_err(
-                    |e| {
-                        error_data!(
-                            "add_files_parallel",
-                            e.to_string(),
-                            "Failed to copy file to objects directory"
-                        )
-                    },
-                )?;
-            }
         }
         Ok::<(), ErrorData>(())
     })?;
@@ -86,7 +87,131 @@ fn add_files_parallel(
     Ok(())
 }
 
-pub fn add(files: &Vec<String>) -> Result<(), ErrorData> {
+// Folosit de `commit -a`: actualizeaza doar intrarile deja urmarite din index
+// (fisierele modificate sunt re-hash-uite, cele sterse sunt scoase), fara
+// sa adauge fisiere noi
+pub fn stage_tracked_changes(root: &str) -> Result<(), ErrorData> {
+    let mut index_data = IndexData::lock(root).map_err(|e| {
+        error_data!(
+            "stage_tracked_changes",
+            e.to_string(),
+            "Failed to load index data"
+        )
+    })?;
+
+    let folder_files = get_working_directory_optimized(root).map_err(|e| {
+        error_data!(
+            "stage_tracked_changes",
+            e.to_string(),
+            "Failed to get working directory files"
+        )
+    })?;
+
+    let mut files_to_add: Vec<(String, FileInfo)> = Vec::new();
+    let mut deleted: Vec<String> = Vec::new();
+
+    for (path, info) in index_data.entries.iter() {
+        match folder_files.entries.get(path) {
+            Some(file) if needs_staging(Some(info), file) => {
+                files_to_add.push((path.clone(), file.clone()));
+            }
+            Some(_) => {}
+            None => deleted.push(path.clone()),
+        }
+    }
+
+    for path in deleted {
+        index_data.entries.remove(&path);
+    }
+
+    add_files_parallel(&files_to_add, root, &mut index_data).map_err(|e| {
+        error_data!(
+            "stage_tracked_changes",
+            e.to_string(),
+            "Failed to add files in parallel"
+        )
+    })?;
+
+    index_data.save_index().map_err(|e| {
+        error_data!(
+            "stage_tracked_changes",
+            e.to_string(),
+            "Failed to save index data"
+        )
+    })
+}
+
+// Folosit de `commit <pathspec>`: aduce in index continutul din directorul de
+// lucru al fisierelor date; cele care nu mai exista sunt scoase din index, iar
+// cele deja adaugate si neatinse de atunci raman asa cum sunt
+pub(crate) fn stage_selected(
+    root: &str,
+    index_data: &mut IndexData,
+    paths: &[String],
+) -> Result<(), ErrorData> {
+    let mut files_to_add: Vec<(String, FileInfo)> = Vec::new();
+
+    for path in paths {
+        let metadata = match fs::symlink_metadata(format_path(&vec![root, path])) {
+            Ok(m) if !m.is_dir() => m,
+            _ => {
+                index_data.entries.remove(path);
+                continue;
+            }
+        };
+
+        let timestamp = metadata
+            .modified()
+            .map_err(|e| {
+                error_data!(
+                    "stage_selected",
+                    e.to_string(),
+                    "Failed to get file modified time"
+                )
+            })?
+            .duration_since(std::time::UNIX_EPOCH)
+            .map_err(|e| {
+                error_data!(
+                    "stage_selected",
+                    e.to_string(),
+                    "Failed to convert file modified time to UNIX timestamp"
+                )
+            })?
+            .as_secs();
+
+        let file = FileInfo {
+            hash: String::new(),
+            timestamp,
+            mode: file_mode(&metadata),
+        };
+
+        if needs_staging(index_data.entries.get(path), &file) {
+            files_to_add.push((path.clone(), file));
+        }
+    }
+
+    add_files_parallel(&files_to_add, root, index_data).map_err(|e| {
+        error_data!(
+            "stage_selected",
+            e.to_string(),
+            "Failed to add files in parallel"
+        )
+    })
+}
+
+// Caile din `Staged` sunt relative la radacina depozitului
+pub enum AddOutcome {
+    // `gc` este colectarea pornita automat dupa adaugare, daca a fost cazul
+    Staged {
+        updated: Vec<String>,
+        removed: Vec<String>,
+        gc: Option<GcReport>,
+    },
+    PathspecNotMatched(String),
+    OutsideRepository(String),
+}
+
+pub fn add(files: &[String]) -> Result<(), ErrorData> {
     let root = match find_repo_root(&"./".to_string()) {
         Ok(rep_loc) => rep_loc,
         Err(e) => match e {
@@ -104,33 +229,61 @@ pub fn add(files: &Vec<String>) -> Result<(), ErrorData> {
         },
     };
 
-    let objects = PathBuf::from(format_path(&vec![&root.root, &".my_svn", &"objects"]));
+    let repo = Repository::from_location(root);
+    let outcome = repo
+        .add(files)
+        .map_err(|e| error_data!("add", e.to_string(), "Failed to add files"))?;
 
-    let mut index_data = match IndexData::new() {
+    match outcome {
+        AddOutcome::PathspecNotMatched(path) => {
+            println!("pathspec '{}' did not match any files", path)
+        }
+        AddOutcome::OutsideRepository(path) => {
+            println!(
+                "Error: '{}' is outside repository at '{}'",
+                path,
+                repo.root()
+            )
+        }
+        AddOutcome::Staged { gc, .. } => {
+            if let Some(report) = gc {
+                print_auto_gc_report(&report);
+            }
+        }
+    }
+
+    Ok(())
+}
+
+pub fn add_paths(location: &RepoLocation, files: &[String]) -> Result<AddOutcome, ErrorData> {
+    let mut index_data = match IndexData::lock(&location.root) {
         Ok(data) => data,
         Err(e) => {
             return Err(error_data!(
-                "add",
+                "add_paths",
                 e.to_string(),
                 "Failed to load index data"
             ));
         }
     };
 
-    let start_path = if root.relative.is_empty() {
+    let start_path = if location.relative.is_empty() {
         String::new()
     } else {
-        format_path(&vec![&root.relative, &""])
+        format_path(&vec![&location.relative, &""])
     };
 
-    let ignore_rules = get_svn_ignore(&format_path(&vec![&root.root, ".svnignore"]));
+    let ignore_rules = get_svn_ignore(&format_path(&vec![&location.root, ".svnignore"]));
+
+    let mut updated: Vec<String> = Vec::new();
+    let mut removed: Vec<String> = Vec::new();
 
     if files[0] == "." {
-        let folder_files = match get_working_directory_optimized(&root.root) {
+        let folder_files = match get_working_directory_optimized(&location.root) {
             Ok(f) => f,
             Err(e) => {
                 return Err(error_data!(
-                    "add",
+                    "add_paths",
                     e.to_string(),
                     "Failed to get working directory files"
                 ));
@@ -143,14 +296,19 @@ pub fn add(files: &Vec<String>) -> Result<(), ErrorData> {
             .filter(|(path, info)| {
                 !ignore_file(path, &ignore_rules)
                     && path.starts_with(&start_path)
-                    && (!index_data.entries.contains_key(*path)
-                        || index_data.entries[*path].timestamp != info.timestamp)
+                    && needs_staging(index_data.entries.get(*path), info)
             })
             .map(|(path, info)| (path.clone(), info.clone()))
             .collect::<Vec<(String, FileInfo)>>();
 
-        add_files_parallel(&files_to_add, &root.root, &objects, &mut index_data)
-            .map_err(|e| error_data!("add", e.to_string(), "Failed to add files in parallel"))?;
+        add_files_parallel(&files_to_add, &location.root, &mut index_data).map_err(|e| {
+            error_data!(
+                "add_paths",
+                e.to_string(),
+                "Failed to add files in parallel"
+            )
+        })?;
+        updated.extend(files_to_add.into_iter().map(|(path, _)| path));
 
         let keys_to_remove: Vec<String> = index_data
             .entries
@@ -161,14 +319,24 @@ pub fn add(files: &Vec<String>) -> Result<(), ErrorData> {
 
         for path in keys_to_remove {
             index_data.entries.remove(&path);
+            removed.push(path);
         }
     } else {
         let mut files_to_add: Vec<(String, FileInfo)> = Vec::new();
 
         for p in files {
-            if !Path::new(p).exists() {
+            // Caile sunt interpretate relativ la directorul din care s-a pornit
+            let full_path = format_path(&vec![&location.root, &location.relative, p]);
+
+            // Un symlink catre o tinta inexistenta este tot un fisier valid
+            let is_symlink = fs::symlink_metadata(&full_path)
+                .map(|m| m.file_type().is_symlink())
+                .unwrap_or(false);
+
+            if !is_symlink && !Path::new(&full_path).exists() {
                 if index_data.entries.contains_key(p) {
                     index_data.entries.remove(p);
+                    removed.push(p.clone());
                     continue;
                 } else {
                     let mut is_valid_folder = false;
@@ -183,6 +351,7 @@ pub fn add(files: &Vec<String>) -> Result<(), ErrorData> {
                     for (key, _) in index_data.entries.clone() {
                         if key.starts_with(&relative_folder_path) {
                             index_data.entries.remove(&key);
+                            removed.push(key);
                             is_valid_folder = true;
                         }
                     }
@@ -192,34 +361,42 @@ pub fn add(files: &Vec<String>) -> Result<(), ErrorData> {
                     }
                 }
 
-                println!("pathspec '{}' did not match any files", p);
-                return Ok(());
+                return Ok(AddOutcome::PathspecNotMatched(p.clone()));
             }
 
-            let absolute_path = match dunce::canonicalize(p) {
+            // Legatura in sine este adaugata, nu fisierul spre care trimite
+            let canonical = if is_symlink {
+                let link = Path::new(&full_path);
+                let parent = link.parent().unwrap_or(Path::new(&location.root));
+
+                dunce::canonicalize(parent).map(|pa| pa.join(link.file_name().unwrap_or_default()))
+            } else {
+                dunce::canonicalize(&full_path)
+            };
+
+            let absolute_path = match canonical {
                 Ok(pa) => pa.to_string_lossy().to_string(),
                 Err(e) => {
                     return Err(error_data!(
-                        "add",
+                        "add_paths",
                         e.to_string(),
                         "Failed to canonicalize path"
                     ));
                 }
             };
 
-            if !is_path_within(&root.root, &absolute_path) {
-                println!("Error: '{}' is outside repository at '{}'", p, root.root);
-                return Ok(());
+            if !is_path_within(&location.root, &absolute_path) {
+                return Ok(AddOutcome::OutsideRepository(p.clone()));
             }
 
-            let relative_path = if root.root == absolute_path {
+            let relative_path = if location.root == absolute_path {
                 String::from("")
             } else {
-                match absolute_path.strip_prefix(&format_path(&vec![&root.root, ""])) {
+                match absolute_path.strip_prefix(&format_path(&vec![&location.root, ""])) {
                     Some(rel_path) => rel_path.to_string(),
                     None => {
                         return Err(error_data!(
-                            "add",
+                            "add_paths",
                             "".to_string(),
                             "Failed to get relative path"
                         ));
@@ -227,44 +404,49 @@ pub fn add(files: &Vec<String>) -> Result<(), ErrorData> {
                 }
             };
 
-            if Path::new(&absolute_path).is_file() {
+            if is_symlink || Path::new(&absolute_path).is_file() {
                 if ignore_file(&relative_path, &ignore_rules) {
                     continue;
                 }
 
-                let timestamp = fs::metadata(&absolute_path)
-                    .map_err(|e| error_data!("add", e.to_string(), "Failed to get file metadata"))?
+                let metadata = fs::symlink_metadata(&absolute_path).map_err(|e| {
+                    error_data!("add_paths", e.to_string(), "Failed to get file metadata")
+                })?;
+
+                let timestamp = metadata
                     .modified()
                     .map_err(|e| {
-                        error_data!("add", e.to_string(), "Failed to get file modified time")
+                        error_data!(
+                            "add_paths",
+                            e.to_string(),
+                            "Failed to get file modified time"
+                        )
                     })?
                     .duration_since(std::time::UNIX_EPOCH)
                     .map_err(|e| {
                         error_data!(
-                            "add",
+                            "add_paths",
                             e.to_string(),
                             "Failed to convert file modified time to UNIX timestamp"
                         )
                     })?
                     .as_secs();
 
-                if !index_data.entries.contains_key(&relative_path)
-                    || index_data.entries[&relative_path].timestamp != timestamp
-                {
-                    files_to_add.push((
-                        relative_path.clone(),
-                        FileInfo {
-                            hash: String::new(),
-                            timestamp,
-                        },
-                    ));
+                let info = FileInfo {
+                    hash: String::new(),
+                    timestamp,
+                    mode: file_mode(&metadata),
+                };
+
+                if needs_staging(index_data.entries.get(&relative_path), &info) {
+                    files_to_add.push((relative_path.clone(), info));
                 }
             } else if Path::new(&absolute_path).is_dir() {
                 let mut folder_files = match get_working_directory_optimized(&absolute_path) {
                     Ok(f) => f,
                     Err(e) => {
                         return Err(error_data!(
-                            "add",
+                            "add_paths",
                             e.to_string(),
                             "Failed to get working directory files"
                         ));
@@ -272,14 +454,15 @@ pub fn add(files: &Vec<String>) -> Result<(), ErrorData> {
                 };
 
                 for (path, info) in &mut folder_files.entries {
-                    let absolute_file_path = format_path(&vec![&root.root, &absolute_path, &path]);
+                    let absolute_file_path =
+                        format_path(&vec![&location.root, &absolute_path, &path]);
                     let relative_file_path = match absolute_file_path
-                        .strip_prefix(&format_path(&vec![&root.root, ""]))
+                        .strip_prefix(&format_path(&vec![&location.root, ""]))
                     {
                         Some(rel_path) => rel_path.to_string(),
                         None => {
                             return Err(error_data!(
-                                "add",
+                                "add_paths",
                                 "".to_string(),
                                 "Failed to canonicalize path"
                             ));
@@ -290,22 +473,36 @@ pub fn add(files: &Vec<String>) -> Result<(), ErrorData> {
                         continue;
                     }
 
-                    if !index_data.entries.contains_key(&relative_file_path)
-                        || index_data.entries[&relative_file_path].timestamp != info.timestamp
-                    {
+                    if needs_staging(index_data.entries.get(&relative_file_path), info) {
                         files_to_add.push((relative_file_path.clone(), info.clone()));
                     }
                 }
             }
         }
 
-        add_files_parallel(&files_to_add, &root.root, &objects, &mut index_data)
-            .map_err(|e| error_data!("add", e.to_string(), "Failed to add files in parallel"))?;
+        add_files_parallel(&files_to_add, &location.root, &mut index_data).map_err(|e| {
+            error_data!(
+                "add_paths",
+                e.to_string(),
+                "Failed to add files in parallel"
+            )
+        })?;
+        updated.extend(files_to_add.into_iter().map(|(path, _)| path));
     }
 
     index_data
         .save_index()
-        .map_err(|e| error_data!("add", e.to_string(), "Failed to save index data"))?;
+        .map_err(|e| error_data!("add_paths", e.to_string(), "Failed to save index data"))?;
 
-    Ok(())
+    let gc = auto_gc(&location.root)
+        .map_err(|e| error_data!("add_paths", e.to_string(), "Failed to run automatic gc"))?;
+
+    updated.sort();
+    removed.sort();
+
+    Ok(AddOutcome::Staged {
+        updated,
+        removed,
+        gc,
+    })
 }
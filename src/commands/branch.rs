@@ -1,13 +1,121 @@
+use colored::Colorize;
 use std::{fs, path::Path};
 
 use crate::{
+    commands::{
+        commit::{CommitObject, find_base_commit},
+        tag::{get_tag_hash, peel_to_commit},
+    },
     error_data,
     utils::{
         error::ErrorData,
+        hash::check_object_name,
+        json::load_object_json,
+        lock::{LockFile, write_locked},
         path::{RepoLocationError::*, find_repo_root, format_path},
+        reflog::{append_head_reflog, append_reflog, delete_reflog, rename_reflog},
+        revision::valid_ref_name,
     },
 };
 
+#[derive(PartialEq)]
+pub enum BranchAction {
+    // `-v` afiseaza si ultimul commit al fiecarei ramuri
+    List(bool),
+    Create(String),
+    // `-D` sterge si o ramura care nu a fost unita in cea curenta
+    Delete(String, bool),
+    // Fara numele vechi este redenumita ramura curenta
+    Rename(Option<String>, String),
+}
+
+pub enum DeleteBranchOutcome {
+    // Commit-ul la care indica ramura stearsa
+    Deleted(String),
+    NotFound,
+    CurrentBranch,
+    NotMerged,
+}
+
+pub enum RenameBranchOutcome {
+    Renamed,
+    NotFound,
+    AlreadyExists,
+    InvalidName,
+}
+
+// `hash` este gol pentru o ramura fara niciun commit
+pub struct Branch {
+    pub name: String,
+    pub hash: String,
+    pub current: bool,
+}
+
+// HEAD contine numele ramurii curente sau, cand este detasat (dupa
+// `checkout <tag>` sau `checkout <commit>`), direct hash-ul commit-ului
+#[derive(Debug, Clone, PartialEq)]
+pub enum Head {
+    Branch(String),
+    Detached(String),
+}
+
+pub fn read_head(root: &str) -> Result<Head, ErrorData> {
+    let content = get_current_branch(root)
+        .map_err(|e| error_data!("read_head", e.to_string(), "Failed to read HEAD"))?;
+
+    let is_branch = Path::new(&format_path(&vec![
+        root, ".my_svn", "refs", "heads", &content,
+    ]))
+    .is_file();
+
+    if !is_branch
+        && !content.is_empty()
+        && content.chars().all(|c| c.is_ascii_hexdigit())
+        && check_object_name(root, &content).is_ok()
+    {
+        return Ok(Head::Detached(content));
+    }
+
+    Ok(Head::Branch(content))
+}
+
+// Commit-ul la care indica HEAD; gol daca ramura curenta nu are commit-uri
+pub fn head_commit(root: &str) -> Result<String, ErrorData> {
+    match read_head(root)
+        .map_err(|e| error_data!("head_commit", e.to_string(), "Failed to read HEAD"))?
+    {
+        Head::Branch(branch) => get_branch_hash(root, &branch).map_err(|e| {
+            error_data!(
+                "head_commit",
+                e.to_string(),
+                "Failed to get current branch hash"
+            )
+        }),
+        Head::Detached(hash) => Ok(hash),
+    }
+}
+
+// Un nume de ramura sau de tag (ramurile au prioritate); tag-urile adnotate
+// sunt urmate pana la commit. `None` daca numele nu exista
+pub fn resolve_ref(root: &str, name: &str) -> Result<Option<String>, ErrorData> {
+    if Path::new(&format_path(&vec![root, ".my_svn", "refs", "heads", name])).is_file() {
+        return get_branch_hash(root, name)
+            .map(Some)
+            .map_err(|e| error_data!("resolve_ref", e.to_string(), "Failed to get branch hash"));
+    }
+
+    match get_tag_hash(root, name)
+        .map_err(|e| error_data!("resolve_ref", e.to_string(), "Failed to get tag hash"))?
+    {
+        Some(hash) => peel_to_commit(root, &hash)
+            .map(Some)
+            .map_err(|e| error_data!("resolve_ref", e.to_string(), "Failed to peel tag")),
+        None => Ok(None),
+    }
+}
+
+// Continutul lui HEAD: numele ramurii curente sau hash-ul commit-ului cand
+// HEAD este detasat (vezi `read_head`)
 pub fn get_current_branch(root: &str) -> Result<String, ErrorData> {
     let head_path = format_path(&vec![root, ".my_svn", "HEAD"]);
     let content = match fs::read_to_string(&head_path) {
@@ -39,9 +147,79 @@ pub fn get_branch_hash(root: &str, branch: &str) -> Result<String, ErrorData> {
     };
 
     let branch_hash = content.trim().to_string();
+
+    if !branch_hash.is_empty() {
+        check_object_name(root, &branch_hash).map_err(|e| {
+            error_data!(
+                "get_branch_hash",
+                e.to_string(),
+                "Branch points to an object of another format"
+            )
+        })?;
+    }
+
     Ok(branch_hash)
 }
 
+pub fn list_branches(root: &str) -> Result<Vec<String>, ErrorData> {
+    let heads_path = format_path(&vec![root, ".my_svn", "refs", "heads"]);
+    let entries = fs::read_dir(&heads_path).map_err(|e| {
+        error_data!(
+            "list_branches",
+            e.to_string(),
+            "Failed to read refs/heads directory"
+        )
+    })?;
+
+    let mut branches: Vec<String> = Vec::new();
+    for entry in entries {
+        let entry = entry.map_err(|e| {
+            error_data!(
+                "list_branches",
+                e.to_string(),
+                "Failed to read refs/heads entry"
+            )
+        })?;
+
+        let name = entry.file_name().to_string_lossy().to_string();
+        if entry.path().is_file() && !name.ends_with(".lock") {
+            branches.push(name);
+        }
+    }
+
+    branches.sort();
+    Ok(branches)
+}
+
+pub fn list_branch_details(root: &str) -> Result<Vec<Branch>, ErrorData> {
+    let current_branch = get_current_branch(root).map_err(|e| {
+        error_data!(
+            "list_branch_details",
+            e.to_string(),
+            "Failed to get current branch"
+        )
+    })?;
+
+    let mut branches: Vec<Branch> = Vec::new();
+    for name in list_branches(root)? {
+        let hash = get_branch_hash(root, &name).map_err(|e| {
+            error_data!(
+                "list_branch_details",
+                e.to_string(),
+                "Failed to get branch hash"
+            )
+        })?;
+
+        branches.push(Branch {
+            current: name == current_branch,
+            name,
+            hash,
+        });
+    }
+
+    Ok(branches)
+}
+
 pub fn create_branch(branch_name: &str) -> Result<(), ErrorData> {
     let root = match find_repo_root(&"./".to_string()) {
         Ok(rep_loc) => rep_loc,
@@ -60,45 +238,30 @@ pub fn create_branch(branch_name: &str) -> Result<(), ErrorData> {
         },
     };
 
-    let current_branch = match get_current_branch(&root.root) {
-        Ok(b) => b,
-        Err(e) => {
-            return Err(error_data!(
-                "create_branch",
-                e.to_string(),
-                "Failed to get current branch"
-            ));
-        }
-    };
-    let commit_hash = match get_branch_hash(&root.root, &current_branch) {
-        Ok(h) => h,
-        Err(e) => {
-            return Err(error_data!(
-                "create_branch",
-                e.to_string(),
-                "Failed to get branch hash"
-            ));
-        }
-    };
+    if !valid_ref_name(branch_name) {
+        println!("fatal: '{}' is not a valid branch name.", branch_name);
+        return Ok(());
+    }
 
-    if Path::new(&format_path(&vec![
-        &root.root,
-        ".my_svn",
-        "refs",
-        "heads",
-        &branch_name,
-    ]))
-    .exists()
-    {
+    let commit_hash = head_commit(&root.root)
+        .map_err(|e| error_data!("create_branch", e.to_string(), "Failed to get HEAD commit"))?;
+
+    // Existenta se verifica sub blocare, ca doua comenzi sa nu creeze aceeasi ramura
+    let path = branch_path(&root.root, branch_name);
+    let lock = LockFile::acquire(&path).map_err(|e| {
+        error_data!(
+            "create_branch",
+            e.to_string(),
+            "Failed to lock branch reference"
+        )
+    })?;
+
+    if Path::new(&path).exists() {
         println!("fatal: A branch named '{}' already exists.", branch_name);
         return Ok(());
     }
 
-    fs::write(
-        format_path(&vec![&root.root, ".my_svn", "refs", "heads", &branch_name]),
-        commit_hash,
-    )
-    .map_err(|e| {
+    lock.commit(commit_hash.as_bytes()).map_err(|e| {
         error_data!(
             "create_branch",
             e.to_string(),
@@ -106,5 +269,385 @@ pub fn create_branch(branch_name: &str) -> Result<(), ErrorData> {
         )
     })?;
 
+    append_reflog(
+        &root.root,
+        &format!("refs/heads/{}", branch_name),
+        "",
+        &commit_hash,
+        "branch: Created from HEAD",
+    )
+    .map_err(|e| error_data!("create_branch", e.to_string(), "Failed to update reflog"))?;
+
+    Ok(())
+}
+
+fn branch_path(root: &str, name: &str) -> String {
+    format_path(&vec![root, ".my_svn", "refs", "heads", name])
+}
+
+// O ramura este unita daca varful ei este stramos al ramurii curente
+pub fn delete_branch_ref(
+    root: &str,
+    name: &str,
+    force: bool,
+) -> Result<DeleteBranchOutcome, ErrorData> {
+    let path = branch_path(root, name);
+
+    if !valid_ref_name(name) || !Path::new(&path).is_file() {
+        return Ok(DeleteBranchOutcome::NotFound);
+    }
+
+    let current_branch = get_current_branch(root).map_err(|e| {
+        error_data!(
+            "delete_branch_ref",
+            e.to_string(),
+            "Failed to get current branch"
+        )
+    })?;
+
+    if current_branch == name {
+        return Ok(DeleteBranchOutcome::CurrentBranch);
+    }
+
+    let lock = LockFile::acquire(&path).map_err(|e| {
+        error_data!(
+            "delete_branch_ref",
+            e.to_string(),
+            "Failed to lock branch reference"
+        )
+    })?;
+
+    let hash = get_branch_hash(root, name).map_err(|e| {
+        error_data!(
+            "delete_branch_ref",
+            e.to_string(),
+            "Failed to get branch hash"
+        )
+    })?;
+
+    if !force && !hash.is_empty() {
+        let current_hash = head_commit(root).map_err(|e| {
+            error_data!(
+                "delete_branch_ref",
+                e.to_string(),
+                "Failed to get current branch hash"
+            )
+        })?;
+
+        let base = find_base_commit(&current_hash, &hash, root).map_err(|e| {
+            error_data!(
+                "delete_branch_ref",
+                e.to_string(),
+                "Failed to check if the branch is merged"
+            )
+        })?;
+
+        if base.as_deref() != Some(hash.as_str()) {
+            return Ok(DeleteBranchOutcome::NotMerged);
+        }
+    }
+
+    fs::remove_file(&path).map_err(|e| {
+        error_data!(
+            "delete_branch_ref",
+            e.to_string(),
+            "Failed to remove branch file"
+        )
+    })?;
+    drop(lock);
+
+    delete_reflog(root, &format!("refs/heads/{}", name)).map_err(|e| {
+        error_data!(
+            "delete_branch_ref",
+            e.to_string(),
+            "Failed to remove branch reflog"
+        )
+    })?;
+
+    Ok(DeleteBranchOutcome::Deleted(hash))
+}
+
+pub fn rename_branch_ref(
+    root: &str,
+    old_name: &str,
+    new_name: &str,
+) -> Result<RenameBranchOutcome, ErrorData> {
+    if !valid_ref_name(new_name) {
+        return Ok(RenameBranchOutcome::InvalidName);
+    }
+
+    let old_path = branch_path(root, old_name);
+    let new_path = branch_path(root, new_name);
+
+    if !valid_ref_name(old_name) || !Path::new(&old_path).is_file() {
+        return Ok(RenameBranchOutcome::NotFound);
+    }
+
+    // Ramura ar trebui blocata de doua ori; redenumirea in acelasi nume nu schimba nimic
+    if old_name == new_name {
+        return Ok(RenameBranchOutcome::Renamed);
+    }
+
+    let old_lock = LockFile::acquire(&old_path).map_err(|e| {
+        error_data!(
+            "rename_branch_ref",
+            e.to_string(),
+            "Failed to lock branch reference"
+        )
+    })?;
+
+    // Existenta se verifica sub blocare, ca numele sa nu fie ocupat intre timp
+    let new_lock = LockFile::acquire(&new_path).map_err(|e| {
+        error_data!(
+            "rename_branch_ref",
+            e.to_string(),
+            "Failed to lock new branch reference"
+        )
+    })?;
+
+    if Path::new(&new_path).exists() {
+        return Ok(RenameBranchOutcome::AlreadyExists);
+    }
+
+    let hash = get_branch_hash(root, old_name).map_err(|e| {
+        error_data!(
+            "rename_branch_ref",
+            e.to_string(),
+            "Failed to get branch hash"
+        )
+    })?;
+
+    new_lock.commit(hash.as_bytes()).map_err(|e| {
+        error_data!(
+            "rename_branch_ref",
+            e.to_string(),
+            "Failed to create renamed branch file"
+        )
+    })?;
+
+    // HEAD trebuie mutat inainte ca ramura veche sa dispara
+    let current_branch = get_current_branch(root).map_err(|e| {
+        error_data!(
+            "rename_branch_ref",
+            e.to_string(),
+            "Failed to get current branch"
+        )
+    })?;
+
+    if current_branch == old_name {
+        write_locked(
+            &format_path(&vec![root, ".my_svn", "HEAD"]),
+            new_name.as_bytes(),
+        )
+        .map_err(|e| error_data!("rename_branch_ref", e.to_string(), "Failed to update HEAD"))?;
+    }
+
+    fs::remove_file(&old_path).map_err(|e| {
+        error_data!(
+            "rename_branch_ref",
+            e.to_string(),
+            "Failed to remove old branch file"
+        )
+    })?;
+    drop(old_lock);
+
+    let old_ref = format!("refs/heads/{}", old_name);
+    let new_ref = format!("refs/heads/{}", new_name);
+
+    rename_reflog(root, &old_ref, &new_ref).map_err(|e| {
+        error_data!(
+            "rename_branch_ref",
+            e.to_string(),
+            "Failed to move branch reflog"
+        )
+    })?;
+
+    // Redenumirea ramurii curente muta si HEAD, deci apare si in jurnalul lui
+    let message = format!("branch: renamed {} to {}", old_ref, new_ref);
+    let logged = if current_branch == old_name {
+        append_head_reflog(
+            root,
+            &Head::Branch(new_name.to_string()),
+            &hash,
+            &hash,
+            &message,
+        )
+    } else {
+        append_reflog(root, &new_ref, &hash, &hash, &message)
+    };
+
+    logged.map_err(|e| {
+        error_data!(
+            "rename_branch_ref",
+            e.to_string(),
+            "Failed to update reflog"
+        )
+    })?;
+
+    Ok(RenameBranchOutcome::Renamed)
+}
+
+fn print_branches(root: &str, verbose: bool) -> Result<(), ErrorData> {
+    let branches = list_branch_details(root)
+        .map_err(|e| error_data!("print_branches", e.to_string(), "Failed to list branches"))?;
+
+    // Cu HEAD detasat prima linie arata commit-ul curent in locul unei ramuri
+    let detached = match read_head(root)
+        .map_err(|e| error_data!("print_branches", e.to_string(), "Failed to read HEAD"))?
+    {
+        Head::Detached(hash) => Some((
+            format!("(HEAD detached at {})", &hash[..7.min(hash.len())]),
+            hash,
+        )),
+        Head::Branch(_) => None,
+    };
+
+    let width = branches
+        .iter()
+        .map(|b| b.name.len())
+        .chain(detached.iter().map(|(label, _)| label.len()))
+        .max()
+        .unwrap_or(0);
+
+    if let Some((label, hash)) = detached {
+        if verbose {
+            let commit: CommitObject = load_object_json(root, &hash).map_err(|e| {
+                error_data!(
+                    "print_branches",
+                    e.to_string(),
+                    "Failed to load HEAD commit"
+                )
+            })?;
+
+            println!(
+                "* {} {} {}",
+                format!("{:width$}", label, width = width).green(),
+                hash[..7.min(hash.len())].yellow(),
+                commit.message().lines().next().unwrap_or_default()
+            );
+        } else {
+            println!("* {}", label.green());
+        }
+    }
+
+    for branch in branches {
+        let marker = if branch.current { "*" } else { " " };
+        let name = format!("{:width$}", branch.name, width = width);
+        let name = if branch.current {
+            name.green().to_string()
+        } else {
+            name
+        };
+
+        if !verbose {
+            println!("{} {}", marker, name.trim_end());
+            continue;
+        }
+
+        if branch.hash.is_empty() {
+            println!("{} {} (no commits yet)", marker, name);
+            continue;
+        }
+
+        let commit: CommitObject = load_object_json(root, &branch.hash).map_err(|e| {
+            error_data!(
+                "print_branches",
+                e.to_string(),
+                "Failed to load branch commit"
+            )
+        })?;
+
+        println!(
+            "{} {} {} {}",
+            marker,
+            name,
+            branch.hash[..7.min(branch.hash.len())].yellow(),
+            commit.message().lines().next().unwrap_or_default()
+        );
+    }
+
     Ok(())
 }
+
+pub fn branch(action: BranchAction) -> Result<(), ErrorData> {
+    let root = match find_repo_root(&"./".to_string()) {
+        Ok(rep_loc) => rep_loc,
+        Err(e) => match e {
+            ErrorData(ed) => {
+                return Err(error_data!(
+                    "branch",
+                    ed.to_string(),
+                    "Failed to find repository root"
+                ));
+            }
+            RepositoryNotFoundError => {
+                println!("fatal: not a svn repository (or any of the parent directories): .my_svn");
+                return Ok(());
+            }
+        },
+    };
+
+    match action {
+        BranchAction::Create(name) => create_branch(&name),
+        BranchAction::List(verbose) => print_branches(&root.root, verbose),
+        BranchAction::Delete(name, force) => {
+            match delete_branch_ref(&root.root, &name, force)
+                .map_err(|e| error_data!("branch", e.to_string(), "Failed to delete branch"))?
+            {
+                DeleteBranchOutcome::Deleted(hash) if hash.is_empty() => {
+                    println!("Deleted branch {} (had no commits).", name)
+                }
+                DeleteBranchOutcome::Deleted(hash) => {
+                    println!(
+                        "Deleted branch {} (was {}).",
+                        name,
+                        &hash[..7.min(hash.len())]
+                    )
+                }
+                DeleteBranchOutcome::NotFound => println!("error: branch '{}' not found.", name),
+                DeleteBranchOutcome::CurrentBranch => println!(
+                    "error: Cannot delete branch '{}' which you are currently on.",
+                    name
+                ),
+                DeleteBranchOutcome::NotMerged => {
+                    println!("error: The branch '{}' is not fully merged.", name);
+                    println!(
+                        "If you are sure you want to delete it, run 'my_svn branch -D {}'.",
+                        name
+                    );
+                }
+            }
+            Ok(())
+        }
+        BranchAction::Rename(old_name, new_name) => {
+            let old_name = match old_name {
+                Some(n) => n,
+                None => match read_head(&root.root)
+                    .map_err(|e| error_data!("branch", e.to_string(), "Failed to read HEAD"))?
+                {
+                    Head::Branch(current) => current,
+                    Head::Detached(_) => {
+                        println!("fatal: cannot rename the current branch while not on any.");
+                        return Ok(());
+                    }
+                },
+            };
+
+            match rename_branch_ref(&root.root, &old_name, &new_name)
+                .map_err(|e| error_data!("branch", e.to_string(), "Failed to rename branch"))?
+            {
+                RenameBranchOutcome::Renamed => {}
+                RenameBranchOutcome::NotFound => {
+                    println!("error: branch '{}' not found.", old_name)
+                }
+                RenameBranchOutcome::AlreadyExists => {
+                    println!("fatal: A branch named '{}' already exists.", new_name)
+                }
+                RenameBranchOutcome::InvalidName => {
+                    println!("fatal: '{}' is not a valid branch name.", new_name)
+                }
+            }
+            Ok(())
+        }
+    }
+}
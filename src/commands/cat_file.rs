@@ -0,0 +1,172 @@
+use chrono::{DateTime, Local};
+use std::io::Write;
+
+use crate::{
+    commands::{
+        commit::{CommitObject, TreeData, TreeDataType},
+        tag::TagObject,
+    },
+    error_data,
+    utils::{
+        error::ErrorData,
+        identity::Signature,
+        object::{ObjectType, object_exists, read_typed_object},
+        path::{FileMode, RepoLocationError::*, find_repo_root},
+        revision::{RevisionError, resolve_object},
+    },
+};
+
+#[derive(PartialEq)]
+pub enum CatFileMode {
+    Type,
+    Size,
+    Pretty,
+}
+
+fn print_tree(data: &[u8]) -> Result<(), ErrorData> {
+    let entries: Vec<TreeData> = serde_json::from_slice(data)
+        .map_err(|e| error_data!("print_tree", e.to_string(), "Failed to parse tree object"))?;
+
+    for entry in entries {
+        let (mode, entry_type) = if entry.data_type == TreeDataType::Folder {
+            ("040000", ObjectType::Tree)
+        } else {
+            let mode = match entry.mode {
+                FileMode::Regular => "100644",
+                FileMode::Executable => "100755",
+                FileMode::Symlink => "120000",
+            };
+            (mode, ObjectType::Blob)
+        };
+
+        println!(
+            "{} {} {}\t{}",
+            mode,
+            entry_type.as_str(),
+            entry.hash,
+            entry.name
+        );
+    }
+
+    Ok(())
+}
+
+fn format_signature(signature: &Signature) -> String {
+    let date = match DateTime::from_timestamp(signature.timestamp as i64, 0) {
+        Some(t) => t
+            .with_timezone(&Local)
+            .format("%Y-%m-%d %H:%M:%S")
+            .to_string(),
+        None => signature.timestamp.to_string(),
+    };
+
+    format!("{} <{}> {}", signature.name, signature.email, date)
+}
+
+fn print_commit(data: &[u8]) -> Result<(), ErrorData> {
+    let commit: CommitObject = serde_json::from_slice(data).map_err(|e| {
+        error_data!(
+            "print_commit",
+            e.to_string(),
+            "Failed to parse commit object"
+        )
+    })?;
+
+    println!("tree {}", commit.tree);
+
+    for parent in commit.parents() {
+        println!("parent {}", parent);
+    }
+
+    if let Some(author) = &commit.author {
+        println!("author {}", format_signature(author));
+    }
+
+    if let Some(committer) = &commit.committer {
+        println!("committer {}", format_signature(committer));
+    }
+
+    match DateTime::from_timestamp(commit.timestamp as i64, 0) {
+        Some(t) => {
+            let datetime: DateTime<Local> = t.with_timezone(&Local);
+            println!("date {}", datetime.format("%Y-%m-%d %H:%M:%S"));
+        }
+        None => println!("date {}", commit.timestamp),
+    }
+
+    println!();
+    println!("{}", commit.message);
+
+    Ok(())
+}
+
+fn print_tag(data: &[u8]) -> Result<(), ErrorData> {
+    let tag: TagObject = serde_json::from_slice(data)
+        .map_err(|e| error_data!("print_tag", e.to_string(), "Failed to parse tag object"))?;
+
+    println!("object {}", tag.object);
+    println!("type {}", tag.object_type);
+    println!("tag {}", tag.tag);
+    println!("tagger {}", format_signature(&tag.tagger));
+    println!();
+    println!("{}", tag.message);
+
+    Ok(())
+}
+
+pub fn cat_file(mode: CatFileMode, object: &str) -> Result<(), ErrorData> {
+    let root = match find_repo_root(&"./".to_string()) {
+        Ok(rep_loc) => rep_loc,
+        Err(e) => match e {
+            ErrorData(ed) => {
+                return Err(error_data!(
+                    "cat_file",
+                    ed.to_string(),
+                    "Failed to find repository root"
+                ));
+            }
+            RepositoryNotFoundError => {
+                println!("fatal: not a svn repository (or any of the parent directories): .my_svn");
+                return Ok(());
+            }
+        },
+    };
+
+    // Accepta si prefixe de hash, nume de ramuri si tag-uri sau `HEAD~2`
+    let hash = match resolve_object(&root.root, object)
+        .map_err(|e| error_data!("cat_file", e.to_string(), "Failed to resolve object name"))?
+    {
+        Ok(hash) if object_exists(&root.root, &hash) => hash,
+        Ok(_) | Err(RevisionError::Unknown(_)) => {
+            println!("fatal: Not a valid object name {}", object);
+            return Ok(());
+        }
+        Err(e) => {
+            println!("fatal: {}", e);
+            return Ok(());
+        }
+    };
+
+    let (object_type, data) = read_typed_object(&root.root, &hash)
+        .map_err(|e| error_data!("cat_file", e.to_string(), "Failed to read object"))?;
+
+    match mode {
+        CatFileMode::Type => println!("{}", object_type.as_str()),
+        CatFileMode::Size => println!("{}", data.len()),
+        CatFileMode::Pretty => match object_type {
+            ObjectType::Tree => print_tree(&data)
+                .map_err(|e| error_data!("cat_file", e.to_string(), "Failed to print tree"))?,
+            ObjectType::Commit => print_commit(&data)
+                .map_err(|e| error_data!("cat_file", e.to_string(), "Failed to print commit"))?,
+            ObjectType::Tag => print_tag(&data)
+                .map_err(|e| error_data!("cat_file", e.to_string(), "Failed to print tag"))?,
+            ObjectType::Blob => {
+                std::io::stdout().write_all(&data).map_err(|e| {
+                    error_data!("cat_file", e.to_string(), "Failed to write object content")
+                })?;
+            }
+        },
+    }
+
+    Ok(())
+}
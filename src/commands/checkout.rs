@@ -1,15 +1,22 @@
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 use std::path::Path;
 use std::{fs, vec};
 
-use crate::commands::branch::get_current_branch;
-use crate::commands::commit::read_commit;
+use crate::commands::branch::{
+    Head, get_branch_hash, get_current_branch, head_commit, list_branches, read_head, resolve_ref,
+};
+use crate::commands::commit::{CommitObject, read_commit_from_hash};
+use crate::commands::tag::list_tags;
 use crate::error_data;
-use crate::utils::hash::calculate_hash;
 use crate::utils::index::IndexData;
+use crate::utils::json::load_object_json;
+use crate::utils::lock::{LockFile, write_locked};
+use crate::utils::object::{apply_mode, restore_file};
 use crate::utils::path::{
-    FileInfo, RepoLocationError::*, format_path, get_working_directory_optimized,
+    FileMode, RepoLocationError::*, format_path, get_working_directory_optimized,
 };
+use crate::utils::reflog::append_reflog;
+use crate::utils::revision::{resolve_commit, valid_ref_name};
 use crate::utils::{error::ErrorData, path::find_repo_root};
 
 pub fn checkout(branch_name: &str) -> Result<(), ErrorData> {
@@ -30,36 +37,48 @@ pub fn checkout(branch_name: &str) -> Result<(), ErrorData> {
         },
     };
 
-    if !Path::new(&format_path(&vec![
+    // Un tag sau un commit nu pot fi avansate de commit-uri: HEAD este detasat
+    let is_branch = Path::new(&format_path(&vec![
         &root.root,
         ".my_svn",
         "refs",
         "heads",
         &branch_name,
     ]))
-    .exists()
-    {
-        println!("fatal: A branch named '{}' does not exist.", branch_name);
-        return Ok(());
-    }
+    .is_file();
 
-    let current_branch = match get_current_branch(&root.root) {
-        Ok(b) => b,
-        Err(e) => {
-            return Err(error_data!(
+    // O ramura fara commit-uri (dupa `checkout --orphan`) nu este o revizie valida
+    let target_hash = if is_branch {
+        get_branch_hash(&root.root, branch_name)
+            .map_err(|e| error_data!("checkout", e.to_string(), "Failed to get branch hash"))?
+    } else {
+        match resolve_commit(&root.root, branch_name).map_err(|e| {
+            error_data!(
                 "checkout",
                 e.to_string(),
-                "Failed to get current branch"
-            ));
+                "Failed to resolve checkout target"
+            )
+        })? {
+            Ok(hash) => hash,
+            Err(e) => {
+                println!("fatal: {}", e);
+                return Ok(());
+            }
         }
     };
 
-    if current_branch == branch_name {
+    let head = read_head(&root.root)
+        .map_err(|e| error_data!("checkout", e.to_string(), "Failed to read HEAD"))?;
+
+    if is_branch && head == Head::Branch(branch_name.to_string()) {
         println!("You are already on branch '{}'.", branch_name);
         return Ok(());
     }
 
-    let mut current_commit = match read_commit(&root.root, &current_branch) {
+    let current_hash = head_commit(&root.root)
+        .map_err(|e| error_data!("checkout", e.to_string(), "Failed to get HEAD commit"))?;
+
+    let mut current_commit = match read_commit_from_hash(&root.root, &current_hash) {
         Ok(c) => c,
         Err(e) => {
             return Err(error_data!(
@@ -69,7 +88,7 @@ pub fn checkout(branch_name: &str) -> Result<(), ErrorData> {
             ));
         }
     };
-    let checkout_commit = match read_commit(&root.root, branch_name) {
+    let checkout_commit = match read_commit_from_hash(&root.root, &target_hash) {
         Ok(c) => c,
         Err(e) => {
             return Err(error_data!(
@@ -80,7 +99,7 @@ pub fn checkout(branch_name: &str) -> Result<(), ErrorData> {
         }
     };
 
-    let mut working_dir_files = match get_working_directory_optimized(&root.root) {
+    let working_dir_files = match get_working_directory_optimized(&root.root) {
         Ok(wd) => wd,
         Err(e) => {
             return Err(error_data!(
@@ -91,49 +110,33 @@ pub fn checkout(branch_name: &str) -> Result<(), ErrorData> {
         }
     };
 
+    // Datele din index evita citirea fisierelor nemodificate
+    let staged = IndexData::new(&root.root)
+        .map_err(|e| error_data!("checkout", e.to_string(), "Failed to load index data"))?;
+
     let mut same_files: HashSet<String> = HashSet::new();
+    // Fisierele cu acelasi continut dar alt mod, cu modul din ramura curenta
+    let mut mode_changes: HashMap<String, FileMode> = HashMap::new();
 
     for (path, info) in checkout_commit.iter() {
         if current_commit.contains_key(path) {
             if current_commit[path].hash == info.hash {
                 same_files.insert(path.clone());
-            } else if working_dir_files.entries.contains_key(path) {
-                if working_dir_files.entries[path].timestamp == info.timestamp {
+
+                if current_commit[path].mode != info.mode {
+                    mode_changes.insert(path.clone(), current_commit[path].mode);
+                }
+            } else if let Some(file) = working_dir_files.entries.get(path) {
+                let file_hash = staged.working_hash(&root.root, path, file).map_err(|e| {
+                    error_data!("checkout", e.to_string(), "Failed to calculate file hash")
+                })?;
+
+                if file_hash == info.hash {
                     same_files.insert(path.clone());
-                } else {
-                    if current_commit[path].timestamp == working_dir_files.entries[path].timestamp {
-                        working_dir_files
-                            .entries
-                            .insert(path.clone(), current_commit[path].clone());
-                    } else {
-                        let file_hash = match calculate_hash(&format_path(&vec![&root.root, &path]))
-                        {
-                            Ok(h) => h,
-                            Err(e) => {
-                                return Err(error_data!(
-                                    "checkout",
-                                    e.to_string(),
-                                    "Failed to calculate file hash"
-                                ));
-                            }
-                        };
-
-                        working_dir_files.entries.insert(
-                            path.clone(),
-                            FileInfo {
-                                hash: file_hash,
-                                timestamp: working_dir_files.entries[path].timestamp,
-                            },
-                        );
-                    }
-
-                    if working_dir_files.entries[path].hash == info.hash {
-                        same_files.insert(path.clone());
-                    } else if working_dir_files.entries[path].hash != current_commit[path].hash {
-                        println!("File {} has uncommitted changes. Cannot checkout!", path);
-
-                        return Ok(());
-                    }
+                } else if file_hash != current_commit[path].hash {
+                    println!("File {} has uncommitted changes. Cannot checkout!", path);
+
+                    return Ok(());
                 }
             } else {
                 println!("File {} has uncommitted changes. Cannot checkout!", path);
@@ -149,46 +152,31 @@ pub fn checkout(branch_name: &str) -> Result<(), ErrorData> {
         }
     }
 
-    for path in current_commit.keys() {
-        if working_dir_files.entries.contains_key(path) {
-            if working_dir_files.entries[path].hash.is_empty() {
-                if working_dir_files.entries[path].timestamp == current_commit[path].timestamp {
-                    continue;
-                } else if checkout_commit.contains_key(path)
-                    && working_dir_files.entries[path].timestamp == checkout_commit[path].timestamp
-                {
-                    working_dir_files
-                        .entries
-                        .insert(path.clone(), checkout_commit[path].clone());
-                } else {
-                    let file_hash = match calculate_hash(&format_path(&vec![&root.root, &path])) {
-                        Ok(h) => h,
-                        Err(e) => {
-                            return Err(error_data!(
-                                "checkout",
-                                e.to_string(),
-                                "Failed to calculate file hash during checkout"
-                            ));
-                        }
-                    };
-
-                    working_dir_files.entries.insert(
-                        path.clone(),
-                        FileInfo {
-                            hash: file_hash,
-                            timestamp: working_dir_files.entries[path].timestamp,
-                        },
-                    );
-                }
-            }
+    for (path, info) in current_commit.iter() {
+        if let Some(file) = working_dir_files.entries.get(path) {
+            let file_hash = staged.working_hash(&root.root, path, file).map_err(|e| {
+                error_data!(
+                    "checkout",
+                    e.to_string(),
+                    "Failed to calculate file hash during checkout"
+                )
+            })?;
 
-            if working_dir_files.entries[path].hash != current_commit[path].hash {
+            if file_hash != info.hash {
                 println!("File {} has uncommitted changes. Cannot checkout!", path);
                 return Ok(());
             }
         }
     }
 
+    let mut index_files: IndexData = IndexData::lock(&root.root).map_err(|e| {
+        error_data!(
+            "checkout",
+            e.to_string(),
+            "Failed to lock index data during checkout"
+        )
+    })?;
+
     for path in current_commit.keys() {
         if working_dir_files.entries.contains_key(path) {
             fs::remove_file(format_path(&vec![&root.root, &path])).map_err(|e| {
@@ -202,32 +190,55 @@ pub fn checkout(branch_name: &str) -> Result<(), ErrorData> {
     }
 
     for (path, info) in checkout_commit.iter() {
-        if !same_files.contains(path) {
-            let object_path = format_path(&vec![&root.root, ".my_svn", "objects", &info.hash]);
-            let dest_path = format_path(&vec![&root.root, &path]);
-
-            if let Some(parent) = Path::new(&dest_path).parent() {
-                std::fs::create_dir_all(parent).map_err(|e| {
-                    error_data!(
-                        "checkout",
-                        e.to_string(),
-                        "Failed to create parent directories during checkout"
-                    )
-                })?;
+        let dest_path = format_path(&vec![&root.root, &path]);
+
+        if same_files.contains(path) {
+            match mode_changes.get(path) {
+                None => continue,
+                // Doar bitul de executie difera: continutul ramane neatins
+                Some(old_mode)
+                    if *old_mode != FileMode::Symlink && info.mode != FileMode::Symlink =>
+                {
+                    apply_mode(&dest_path, info.mode).map_err(|e| {
+                        error_data!(
+                            "checkout",
+                            e.to_string(),
+                            "Failed to update file mode during checkout"
+                        )
+                    })?;
+                    continue;
+                }
+                Some(_) => {}
             }
+        }
 
-            fs::copy(object_path, dest_path).map_err(|e| {
+        if let Some(parent) = Path::new(&dest_path).parent() {
+            std::fs::create_dir_all(parent).map_err(|e| {
                 error_data!(
                     "checkout",
                     e.to_string(),
-                    "Failed to copy file during checkout"
+                    "Failed to create parent directories during checkout"
                 )
             })?;
         }
+
+        restore_file(&root.root, &info.hash, &dest_path, info.mode).map_err(|e| {
+            error_data!(
+                "checkout",
+                e.to_string(),
+                "Failed to copy file during checkout"
+            )
+        })?;
     }
 
+    let new_head = if is_branch {
+        branch_name
+    } else {
+        target_hash.as_str()
+    };
+
     let head_path = format_path(&vec![&root.root, ".my_svn", "HEAD"]);
-    fs::write(head_path, branch_name).map_err(|e| {
+    write_locked(&head_path, new_head.as_bytes()).map_err(|e| {
         error_data!(
             "checkout",
             e.to_string(),
@@ -235,17 +246,32 @@ pub fn checkout(branch_name: &str) -> Result<(), ErrorData> {
         )
     })?;
 
-    let mut index_files: IndexData = IndexData::new().map_err(|e| {
-        error_data!(
-            "checkout",
-            e.to_string(),
-            "Failed to load index data during checkout"
-        )
-    })?;
+    let moved_from = match &head {
+        Head::Branch(branch) => branch.as_str(),
+        Head::Detached(hash) => hash.as_str(),
+    };
+    append_reflog(
+        &root.root,
+        "HEAD",
+        &current_hash,
+        &target_hash,
+        &format!("checkout: moving from {} to {}", moved_from, branch_name),
+    )
+    .map_err(|e| error_data!("checkout", e.to_string(), "Failed to update reflog"))?;
 
     index_files.entries.clear();
     index_files.entries = checkout_commit;
 
+    // Fisierele lasate neatinse isi pastreaza data in index
+    for path in same_files.iter().filter(|p| !mode_changes.contains_key(*p)) {
+        if let (Some(entry), Some(file)) = (
+            index_files.entries.get_mut(path),
+            working_dir_files.entries.get(path),
+        ) {
+            entry.timestamp = file.timestamp;
+        }
+    }
+
     index_files.save_index().map_err(|e| {
         error_data!(
             "checkout",
@@ -254,5 +280,234 @@ pub fn checkout(branch_name: &str) -> Result<(), ErrorData> {
         )
     })?;
 
+    if let Head::Detached(old_hash) = &head
+        && *old_hash != target_hash
+    {
+        warn_left_behind(&root.root, old_hash).map_err(|e| {
+            error_data!(
+                "checkout",
+                e.to_string(),
+                "Failed to look for commits left behind"
+            )
+        })?;
+    }
+
+    if !is_branch {
+        print_detached_note(&root.root, branch_name, &target_hash)
+            .map_err(|e| error_data!("checkout", e.to_string(), "Failed to load commit"))?;
+    }
+
+    Ok(())
+}
+
+// Commit-urile accesibile din ramuri si din tag-uri
+fn referenced_commits(root: &str) -> Result<HashSet<String>, ErrorData> {
+    let mut stack: Vec<String> = Vec::new();
+
+    for branch in list_branches(root).map_err(|e| {
+        error_data!(
+            "referenced_commits",
+            e.to_string(),
+            "Failed to list branches"
+        )
+    })? {
+        stack.extend(resolve_ref(root, &branch).map_err(|e| {
+            error_data!(
+                "referenced_commits",
+                e.to_string(),
+                "Failed to get branch hash"
+            )
+        })?);
+    }
+
+    for tag in list_tags(root)
+        .map_err(|e| error_data!("referenced_commits", e.to_string(), "Failed to list tags"))?
+    {
+        stack.extend(resolve_ref(root, &tag).map_err(|e| {
+            error_data!(
+                "referenced_commits",
+                e.to_string(),
+                "Failed to get tag hash"
+            )
+        })?);
+    }
+
+    let mut seen: HashSet<String> = HashSet::new();
+
+    while let Some(hash) = stack.pop() {
+        if hash.is_empty() || !seen.insert(hash.clone()) {
+            continue;
+        }
+
+        let commit: CommitObject = load_object_json(root, &hash).map_err(|e| {
+            error_data!("referenced_commits", e.to_string(), "Failed to load commit")
+        })?;
+        stack.extend(commit.parents().iter().cloned());
+    }
+
+    Ok(seen)
+}
+
+// Commit-urile facute cu HEAD detasat se pierd la plecarea de pe el daca
+// nicio ramura sau tag nu le mai contine
+fn warn_left_behind(root: &str, old_hash: &str) -> Result<(), ErrorData> {
+    let referenced = referenced_commits(root)?;
+
+    let mut left_behind: Vec<(String, CommitObject)> = Vec::new();
+    let mut seen: HashSet<String> = HashSet::new();
+    let mut stack: Vec<String> = vec![old_hash.to_string()];
+
+    while let Some(hash) = stack.pop() {
+        if referenced.contains(&hash) || !seen.insert(hash.clone()) {
+            continue;
+        }
+
+        let commit: CommitObject = load_object_json(root, &hash)
+            .map_err(|e| error_data!("warn_left_behind", e.to_string(), "Failed to load commit"))?;
+        stack.extend(commit.parents().iter().cloned());
+        left_behind.push((hash, commit));
+    }
+
+    if left_behind.is_empty() {
+        return Ok(());
+    }
+
+    println!(
+        "Warning: you are leaving {} commit(s) behind, not connected to",
+        left_behind.len()
+    );
+    println!("any of your branches:");
+    println!();
+    for (hash, commit) in &left_behind {
+        println!(
+            "  {} {}",
+            &hash[..7.min(hash.len())],
+            commit.message().lines().next().unwrap_or_default()
+        );
+    }
+    println!();
+    println!("If you want to keep them by creating a new branch, this may be a good time");
+    println!("to do so with:");
+    println!();
+    println!("  my_svn checkout {}", old_hash);
+    println!("  my_svn branch <new-branch-name>");
+    println!();
+
+    Ok(())
+}
+
+fn print_detached_note(root: &str, target: &str, hash: &str) -> Result<(), ErrorData> {
+    let commit: CommitObject = load_object_json(root, hash).map_err(|e| {
+        error_data!(
+            "print_detached_note",
+            e.to_string(),
+            "Failed to load commit"
+        )
+    })?;
+
+    println!("Note: switching to '{}'.", target);
+    println!();
+    println!("You are in 'detached HEAD' state. You can look around, make experimental");
+    println!("changes and commit them; these commits belong to no branch and are left");
+    println!("behind when you check out a branch again.");
+    println!();
+    println!("If you want to create a new branch to retain commits you create, you may");
+    println!("do so (now or later, while still detached) with:");
+    println!();
+    println!("  my_svn branch <new-branch-name>");
+    println!();
+    println!(
+        "HEAD is now at {} {}",
+        &hash[..7.min(hash.len())],
+        commit.message().lines().next().unwrap_or_default()
+    );
+
+    Ok(())
+}
+
+// Ramura noua nu are niciun commit (referinta goala, ca dupa `init`); indexul
+// si directorul de lucru raman neschimbate, iar urmatorul commit va fi radacina
+pub fn checkout_orphan(branch_name: &str) -> Result<(), ErrorData> {
+    let root = match find_repo_root(&"./".to_string()) {
+        Ok(rep_loc) => rep_loc,
+        Err(e) => match e {
+            ErrorData(ed) => {
+                return Err(error_data!(
+                    "checkout_orphan",
+                    ed.to_string(),
+                    "Failed to find repository root"
+                ));
+            }
+            RepositoryNotFoundError => {
+                println!("fatal: not a svn repository (or any of the parent directories): .my_svn");
+                return Ok(());
+            }
+        },
+    };
+
+    if !valid_ref_name(branch_name) {
+        println!("fatal: '{}' is not a valid branch name.", branch_name);
+        return Ok(());
+    }
+
+    // Ca la `branch`, existenta se verifica sub blocare
+    let branch_path = format_path(&vec![&root.root, ".my_svn", "refs", "heads", branch_name]);
+    let lock = LockFile::acquire(&branch_path).map_err(|e| {
+        error_data!(
+            "checkout_orphan",
+            e.to_string(),
+            "Failed to lock branch reference"
+        )
+    })?;
+
+    if Path::new(&branch_path).exists() {
+        println!("fatal: A branch named '{}' already exists.", branch_name);
+        return Ok(());
+    }
+
+    lock.commit(b"").map_err(|e| {
+        error_data!(
+            "checkout_orphan",
+            e.to_string(),
+            "Failed to create branch file"
+        )
+    })?;
+
+    let moved_from = get_current_branch(&root.root).map_err(|e| {
+        error_data!(
+            "checkout_orphan",
+            e.to_string(),
+            "Failed to get current branch"
+        )
+    })?;
+    let current_hash = head_commit(&root.root).map_err(|e| {
+        error_data!(
+            "checkout_orphan",
+            e.to_string(),
+            "Failed to get HEAD commit"
+        )
+    })?;
+
+    let head_path = format_path(&vec![&root.root, ".my_svn", "HEAD"]);
+    write_locked(&head_path, branch_name.as_bytes()).map_err(|e| {
+        error_data!(
+            "checkout_orphan",
+            e.to_string(),
+            "Failed to update HEAD during checkout"
+        )
+    })?;
+
+    // HEAD ajunge pe o ramura fara commit-uri
+    append_reflog(
+        &root.root,
+        "HEAD",
+        &current_hash,
+        "",
+        &format!("checkout: moving from {} to {}", moved_from, branch_name),
+    )
+    .map_err(|e| error_data!("checkout_orphan", e.to_string(), "Failed to update reflog"))?;
+
+    println!("Switched to a new branch '{}'", branch_name);
+
     Ok(())
 }
@@ -1,211 +1,976 @@
-use serde::{Deserialize, Serialize};
-use sha1::{Digest, Sha1};
+use serde::{Deserialize, Deserializer, Serialize};
 use std::{
     collections::{HashMap, HashSet, VecDeque},
-    fs,
+    env, fs,
+    io::{self, Read},
     path::Path,
+    process,
     time::{SystemTime, UNIX_EPOCH},
 };
 
 use crate::{
-    commands::branch::{get_branch_hash, get_current_branch},
+    commands::{
+        add::{stage_selected, stage_tracked_changes},
+        branch::{Head, get_branch_hash, head_commit, read_head},
+        gc::{GcReport, auto_gc, print_auto_gc_report},
+        status::collect_status,
+    },
     error_data,
+    repository::Repository,
     utils::{
+        config::load_config,
         error::ErrorData,
-        index::IndexData,
-        json::load_json,
-        path::{FileInfo, RepoLocationError::*, find_repo_root, format_path},
+        hash::hash_bytes,
+        hooks::{print_hook_outcome, run_hook},
+        identity::{IdentityRole, Signature, resolve_identity},
+        index::{IndexData, TreeHashes, parent_dirs},
+        json::load_object_json,
+        lock::LockFile,
+        object::{ObjectType, object_exists, write_object},
+        path::{FileInfo, FileMode, RepoLocationError::*, find_repo_root, format_path},
+        reflog::append_head_reflog,
     },
 };
 
-#[derive(Debug)]
-pub struct TreeNode {
-    name: String,
-    is_file: bool,
-    hash: String,
-    timestamp: Option<u64>,
-    children: HashMap<String, Box<TreeNode>>,
-}
-
 #[derive(Serialize, Deserialize, PartialEq)]
-enum TreeDataType {
+pub(crate) enum TreeDataType {
     File,
     Folder,
 }
 
-#[derive(Serialize, Deserialize)]
-struct TreeData {
-    data_type: TreeDataType,
-    name: String,
-    hash: String,
-    timestamp: Option<u64>,
+#[derive(Serialize, Deserialize, PartialEq)]
+pub(crate) struct TreeData {
+    pub(crate) data_type: TreeDataType,
+    pub(crate) name: String,
+    pub(crate) hash: String,
+    // Fisierele obisnuite nu isi scriu modul, ca arborii vechi sa aiba acelasi hash
+    #[serde(default, skip_serializing_if = "FileMode::is_regular")]
+    pub(crate) mode: FileMode,
 }
 
 #[derive(Serialize, Deserialize)]
 pub struct CommitObject {
-    tree: String,
+    pub(crate) tree: String,
+    // Commit-urile radacina nu au parinti (`null`)
+    #[serde(default, deserialize_with = "deserialize_parents")]
     pub(crate) parent: Option<Vec<String>>,
     pub(crate) message: String,
+    // Data commit-ului (a committer-ului)
     pub(crate) timestamp: u64,
+    // Commit-urile mai vechi nu au autor si committer
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) author: Option<Signature>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) committer: Option<Signature>,
 }
 
-fn travel_commit_tree(root: &String, node: &mut TreeNode) -> Result<(), ErrorData> {
-    if node.is_file {
-        return Ok(());
+// Versiunile vechi scriau primul commit cu `parent: [""]`; la citire este
+// tratat la fel ca un commit fara parinti
+fn deserialize_parents<'de, D>(deserializer: D) -> Result<Option<Vec<String>>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let parents: Option<Vec<String>> = Option::deserialize(deserializer)?;
+
+    Ok(parents
+        .map(|p| {
+            p.into_iter()
+                .filter(|h| !h.is_empty())
+                .collect::<Vec<String>>()
+        })
+        .filter(|p| !p.is_empty()))
+}
+
+impl CommitObject {
+    pub fn tree(&self) -> &str {
+        &self.tree
     }
 
-    for i in node.children.values_mut() {
-        travel_commit_tree(root, i)?;
+    pub fn parents(&self) -> &[String] {
+        self.parent.as_deref().unwrap_or_default()
     }
 
-    let mut tree_file: Vec<TreeData> = Vec::new();
-    for i in node.children.values() {
-        tree_file.push(TreeData {
-            data_type: (if i.is_file {
-                TreeDataType::File
-            } else {
-                TreeDataType::Folder
-            }),
-            timestamp: i.timestamp,
-            name: i.name.clone(),
-            hash: i.hash.clone(),
-        });
+    pub fn message(&self) -> &str {
+        &self.message
     }
 
-    let json_string = match serde_json::to_string(&tree_file) {
-        Ok(json) => json,
-        Err(e) => {
-            return Err(error_data!(
-                "travel_commit_tree",
+    pub fn timestamp(&self) -> u64 {
+        self.timestamp
+    }
+
+    pub fn author(&self) -> Option<&Signature> {
+        self.author.as_ref()
+    }
+
+    pub fn committer(&self) -> Option<&Signature> {
+        self.committer.as_ref()
+    }
+}
+
+// Intrarile sunt sortate dupa nume, ca acelasi continut sa dea mereu
+// acelasi JSON (si deci acelasi hash), indiferent de ordinea din HashMap
+pub(crate) fn serialize_tree(entries: &mut [TreeData]) -> Result<String, ErrorData> {
+    entries.sort_by(|a, b| a.name.cmp(&b.name));
+
+    serde_json::to_string(entries).map_err(|e| {
+        error_data!(
+            "serialize_tree",
+            e.to_string(),
+            "Failed to serialize tree data to JSON"
+        )
+    })
+}
+
+pub(crate) fn write_tree(root: &str, mut entries: Vec<TreeData>) -> Result<String, ErrorData> {
+    let json_string = serialize_tree(&mut entries)
+        .map_err(|e| error_data!("write_tree", e.to_string(), "Failed to serialize tree"))?;
+
+    let hash = hash_bytes(root, json_string.as_bytes())
+        .map_err(|e| error_data!("write_tree", e.to_string(), "Failed to hash tree object"))?;
+
+    if !object_exists(root, &hash) {
+        write_object(root, &hash, ObjectType::Tree, json_string.as_bytes()).map_err(|e| {
+            error_data!(
+                "write_tree",
                 e.to_string(),
-                "Failed to serialize tree data to JSON"
-            ));
+                "Failed to write tree object to file"
+            )
+        })?;
+    }
+
+    Ok(hash)
+}
+
+// Scrie arborele intrarilor din `entries`. `trees` da hash-ul arborilor
+// directoarelor neschimbate: acestia sunt refolositi fara sa fie cititi, iar
+// doar directoarele de pe caile schimbate sunt serializate si scrise.
+// Arborii noi sunt adaugati in `trees`.
+pub(crate) fn write_index_tree(
+    root: &str,
+    entries: &HashMap<String, FileInfo>,
+    trees: &mut TreeHashes,
+) -> Result<String, ErrorData> {
+    // Un arbore din cache sters intre timp (de `gc`) este scris din nou
+    let is_cached = |dir: &str| trees.get(dir).is_some_and(|h| object_exists(root, h));
+
+    if is_cached("") {
+        return Ok(trees[""].clone());
+    }
+
+    // Intrarile directoarelor care trebuie scrise; un subdirector scris si el
+    // primeste hash-ul abia dupa ce arborele lui este scris
+    let mut pending: HashMap<String, HashMap<String, TreeData>> = HashMap::new();
+    pending.insert(String::new(), HashMap::new());
+
+    // Directoarele deja intalnite si daca arborele lor este refolosit
+    let mut seen: HashMap<&str, bool> = HashMap::new();
+
+    let name_of = |path: &str| {
+        Path::new(path)
+            .file_name()
+            .map(|n| n.to_string_lossy().to_string())
+            .unwrap_or_default()
+    };
+
+    for (path, info) in entries {
+        let mut parent = "";
+        let mut reused = false;
+
+        // De la radacina in jos, pana la primul director refolosit
+        for (end, _) in path.match_indices(std::path::is_separator) {
+            let dir = &path[..end];
+
+            let cached = match seen.get(dir) {
+                Some(cached) => *cached,
+                None => {
+                    let cached = is_cached(dir);
+                    let hash = match cached {
+                        true => trees[dir].clone(),
+                        false => String::new(),
+                    };
+
+                    pending.entry(parent.to_string()).or_default().insert(
+                        name_of(dir),
+                        TreeData {
+                            data_type: TreeDataType::Folder,
+                            name: name_of(dir),
+                            hash,
+                            mode: FileMode::Regular,
+                        },
+                    );
+
+                    if !cached {
+                        pending.insert(dir.to_string(), HashMap::new());
+                    }
+
+                    seen.insert(dir, cached);
+                    cached
+                }
+            };
+
+            if cached {
+                reused = true;
+                break;
+            }
+
+            parent = dir;
+        }
+
+        if !reused {
+            pending.entry(parent.to_string()).or_default().insert(
+                name_of(path),
+                TreeData {
+                    data_type: TreeDataType::File,
+                    name: name_of(path),
+                    hash: info.hash.clone(),
+                    mode: info.mode,
+                },
+            );
+        }
+    }
+
+    // Directoarele cele mai adanci sunt scrise primele
+    let mut dirs: Vec<String> = pending.keys().cloned().collect();
+    dirs.sort_by_key(|dir| std::cmp::Reverse(parent_dirs(dir).count()));
+
+    let mut written = TreeHashes::new();
+
+    for dir in dirs {
+        let children = pending.remove(&dir).unwrap_or_default();
+
+        let hash = write_tree(root, children.into_values().collect()).map_err(|e| {
+            error_data!(
+                "write_index_tree",
+                e.to_string(),
+                "Failed to write tree object"
+            )
+        })?;
+
+        if let Some(parent) = parent_dirs(&dir).next()
+            && let Some(entry) = pending
+                .get_mut(parent)
+                .and_then(|p| p.get_mut(&name_of(&dir)))
+        {
+            entry.hash = hash.clone();
+        }
+
+        written.insert(dir, hash);
+    }
+
+    let root_hash = written.get("").cloned().unwrap_or_default();
+    trees.extend(written);
+
+    Ok(root_hash)
+}
+
+#[derive(PartialEq, Clone)]
+pub enum MessageSource {
+    Editor,
+    // Fiecare `-m` devine un paragraf separat
+    Paragraphs(Vec<String>),
+    // `-F <file>`; "-" inseamna stdin
+    File(String),
+}
+
+#[derive(PartialEq, Clone, Default)]
+pub struct CommitOptions {
+    pub amend: bool,
+    // `-a`: fisierele urmarite sunt actualizate in index inainte de commit
+    pub all: bool,
+    pub allow_empty: bool,
+    // Sare peste hook-urile pre-commit si commit-msg (doar comanda `commit` le ruleaza)
+    pub no_verify: bool,
+    // `commit <pathspec>...`: cai relative la radacina depozitului
+    pub paths: Vec<String>,
+}
+
+pub enum CommitOutcome {
+    // `gc` este colectarea pornita automat dupa commit, daca a fost cazul
+    Created { hash: String, gc: Option<GcReport> },
+    // Calea data la `commit <pathspec>` nu corespunde niciunui fisier urmarit
+    PathspecNotMatched(String),
+    NothingToAmend,
+    // Arborele este identic cu al parintelui
+    NothingToCommit,
+}
+
+const COMMIT_TEMPLATE: &str = "
+# Please enter the commit message for your changes. Lines starting
+# with '#' will be ignored, and an empty message aborts the commit.
+#
+";
+
+// Elimina spatiile de la final de linie, liniile goale de la inceput si de la
+// sfarsit si pe cele repetate; optional si liniile de comentariu
+pub fn cleanup_message(message: &str, strip_comments: bool) -> String {
+    let mut lines: Vec<&str> = Vec::new();
+
+    for line in message.lines() {
+        if strip_comments && line.starts_with('#') {
+            continue;
+        }
+
+        let line = line.trim_end();
+        if line.is_empty() && lines.last().is_none_or(|l| l.is_empty()) {
+            continue;
+        }
+
+        lines.push(line);
+    }
+
+    while lines.last().is_some_and(|l| l.is_empty()) {
+        lines.pop();
+    }
+
+    lines.join("\n")
+}
+
+fn editor_command(root: &str) -> Result<String, ErrorData> {
+    let config = load_config(root)
+        .map_err(|e| error_data!("editor_command", e.to_string(), "Failed to load config"))?;
+
+    if let Some(editor) = config.get("core.editor") {
+        return Ok(editor.to_string());
+    }
+
+    for var in ["VISUAL", "EDITOR"] {
+        if let Ok(editor) = env::var(var)
+            && !editor.trim().is_empty()
+        {
+            return Ok(editor);
         }
+    }
+
+    Ok(String::from(if cfg!(windows) { "notepad" } else { "vi" }))
+}
+
+pub(crate) fn launch_editor(root: &str, path: &str) -> Result<(), ErrorData> {
+    let editor = editor_command(root)
+        .map_err(|e| error_data!("launch_editor", e.to_string(), "Failed to find an editor"))?;
+
+    // Editorul poate contine si argumente (ex. "code --wait")
+    let status = if cfg!(windows) {
+        process::Command::new("cmd")
+            .args(["/C", &format!("{} \"{}\"", editor, path)])
+            .status()
+    } else {
+        process::Command::new("sh")
+            .arg("-c")
+            .arg(format!("{} \"$@\"", editor))
+            .arg(&editor)
+            .arg(path)
+            .status()
+    }
+    .map_err(|e| error_data!("launch_editor", e.to_string(), "Failed to start the editor"))?;
+
+    if !status.success() {
+        return Err(error_data!(
+            "launch_editor",
+            format!("'{}' exited with {}", editor, status),
+            "There was a problem with the editor"
+        ));
+    }
+
+    Ok(())
+}
+
+fn edit_message(root: &str, initial: &str) -> Result<String, ErrorData> {
+    let report = collect_status(root)
+        .map_err(|e| error_data!("edit_message", e.to_string(), "Failed to collect status"))?;
+
+    let head = match &report.head {
+        Head::Branch(branch) => format!("On branch {}", branch),
+        Head::Detached(hash) => format!("HEAD detached at {}", &hash[..7.min(hash.len())]),
     };
 
-    let mut hasher = Sha1::new();
-    hasher.update(json_string.as_bytes());
-    let hash = hasher.finalize();
+    let mut template = format!("{}{}# {}\n", initial, COMMIT_TEMPLATE, head);
 
-    fs::write(
-        format_path(&vec![root, ".my_svn", "objects", &hex::encode(hash)]),
-        json_string,
-    )
-    .map_err(|e| {
+    if !report.staged.is_empty() {
+        template.push_str("# Changes to be committed:\n");
+        for (path, file_status) in &report.staged {
+            template.push_str(&format!("#\t{}{}\n", file_status.label(), path));
+        }
+    }
+
+    let path = format_path(&vec![root, ".my_svn", "COMMIT_EDITMSG"]);
+    fs::write(&path, template).map_err(|e| {
         error_data!(
-            "travel_commit_tree",
+            "edit_message",
             e.to_string(),
-            "Failed to write tree object to file"
+            "Failed to write COMMIT_EDITMSG"
         )
     })?;
 
-    node.hash = hex::encode(hash);
+    launch_editor(root, &path).map_err(|e| {
+        error_data!(
+            "edit_message",
+            e.to_string(),
+            "Failed to edit commit message"
+        )
+    })?;
 
-    Ok(())
+    fs::read_to_string(&path).map_err(|e| {
+        error_data!(
+            "edit_message",
+            e.to_string(),
+            "Failed to read COMMIT_EDITMSG"
+        )
+    })
+}
+
+fn resolve_message(root: &str, source: &MessageSource, amend: bool) -> Result<String, ErrorData> {
+    match source {
+        MessageSource::Paragraphs(paragraphs) => {
+            Ok(cleanup_message(&paragraphs.join("\n\n"), false))
+        }
+        MessageSource::File(file) => {
+            let content = if file == "-" {
+                let mut buffer = String::new();
+                io::stdin().read_to_string(&mut buffer).map_err(|e| {
+                    error_data!(
+                        "resolve_message",
+                        e.to_string(),
+                        "Failed to read commit message from stdin"
+                    )
+                })?;
+                buffer
+            } else {
+                fs::read_to_string(file).map_err(|e| {
+                    error_data!(
+                        "resolve_message",
+                        e.to_string(),
+                        "Failed to read commit message file"
+                    )
+                })?
+            };
+
+            Ok(cleanup_message(&content, false))
+        }
+        MessageSource::Editor => {
+            // La --amend editorul porneste cu mesajul commit-ului inlocuit
+            let mut initial = String::new();
+
+            if amend {
+                let tip = head_commit(root).map_err(|e| {
+                    error_data!(
+                        "resolve_message",
+                        e.to_string(),
+                        "Failed to get HEAD commit"
+                    )
+                })?;
+
+                if !tip.is_empty() {
+                    let tip_commit: CommitObject = load_object_json(root, &tip).map_err(|e| {
+                        error_data!(
+                            "resolve_message",
+                            e.to_string(),
+                            "Failed to load commit to amend"
+                        )
+                    })?;
+                    initial = format!("{}\n", tip_commit.message);
+                }
+            }
+
+            let content = edit_message(root, &initial).map_err(|e| {
+                error_data!(
+                    "resolve_message",
+                    e.to_string(),
+                    "Failed to get commit message"
+                )
+            })?;
+
+            Ok(cleanup_message(&content, true))
+        }
+    }
 }
 
-pub fn commit(message: String, from_merge: &str) -> Result<(), ErrorData> {
+// Intoarce false daca nu s-a creat niciun commit
+pub fn commit(source: MessageSource, options: CommitOptions) -> Result<bool, ErrorData> {
     let root = match find_repo_root(&"./".to_string()) {
         Ok(rep_loc) => rep_loc,
         Err(e) => match e {
             ErrorData(ed) => {
                 return Err(error_data!(
-                    "add",
+                    "commit",
                     ed.to_string(),
                     "Failed to find repository root"
                 ));
             }
             RepositoryNotFoundError => {
                 println!("fatal: not a svn repository (or any of the parent directories): .my_svn");
-                return Ok(());
+                return Ok(false);
             }
         },
     };
 
-    let index_files = match IndexData::new() {
-        Ok(data) => data,
-        Err(e) => {
-            return Err(error_data!(
-                "commit",
-                e.to_string(),
-                "Failed to load index data"
-            ));
+    commit_in(Repository::from_location(root), source, options)
+}
+
+// Comanda `commit` intr-un depozit deja gasit: hook-uri, mesaj, commit si raport
+fn commit_in(
+    repo: Repository,
+    source: MessageSource,
+    options: CommitOptions,
+) -> Result<bool, ErrorData> {
+    let root = repo.root().to_string();
+    let mut options = options;
+
+    // Ca in git, `-a` actualizeaza indexul inainte de pre-commit, ca hook-ul
+    // sa vada exact continutul care va fi salvat
+    if options.all {
+        stage_tracked_changes(&root)
+            .map_err(|e| error_data!("commit", e.to_string(), "Failed to stage tracked changes"))?;
+        options.all = false;
+    }
+
+    // La fel, fisierele din `commit <pathspec>` ajung in index inainte de pre-commit
+    if !options.paths.is_empty()
+        && let Err(path) = stage_pathspec(&root, &options.paths)
+            .map_err(|e| error_data!("commit", e.to_string(), "Failed to stage selected paths"))?
+    {
+        return report_commit(&root, CommitOutcome::PathspecNotMatched(path));
+    }
+
+    if !options.no_verify {
+        let hook = run_hook(&root, "pre-commit", &[])
+            .map_err(|e| error_data!("commit", e.to_string(), "Failed to run pre-commit hook"))?;
+        print_hook_outcome("pre-commit", &hook);
+
+        if !hook.passed() {
+            println!("fatal: pre-commit hook failed, commit aborted");
+            return Ok(false);
         }
-    };
+    }
 
-    let mut head: TreeNode = TreeNode {
-        name: String::new(),
-        is_file: false,
-        hash: String::new(),
-        timestamp: None,
-        children: HashMap::new(),
-    };
+    // Un commit care nu ar fi creat este refuzat inainte de a cere mesajul
+    if let Some(outcome) = precheck_commit(&root, &options)
+        .map_err(|e| error_data!("commit", e.to_string(), "Failed to check commit"))?
+    {
+        return report_commit(&root, outcome);
+    }
 
-    for (path, info) in index_files.entries {
-        let mut temp_head = &mut head;
+    let mut message = resolve_message(&root, &source, options.amend)
+        .map_err(|e| error_data!("commit", e.to_string(), "Failed to get commit message"))?;
 
-        for path_component in Path::new(&path)
-            .components()
-            .filter_map(|c| c.as_os_str().to_str())
+    if message.is_empty() {
+        println!("Aborting commit due to empty commit message.");
+        return Ok(false);
+    }
+
+    if !options.no_verify {
+        match run_commit_msg_hook(&root, &message)
+            .map_err(|e| error_data!("commit", e.to_string(), "Failed to run commit-msg hook"))?
         {
-            temp_head = temp_head
-                .children
-                .entry(path_component.to_string())
-                .or_insert_with(|| {
-                    Box::new(TreeNode {
-                        name: path_component.to_string(),
-                        is_file: false,
-                        hash: String::new(),
-                        timestamp: None,
-                        children: HashMap::new(),
-                    })
-                })
-                .as_mut();
+            None => {
+                println!("fatal: commit-msg hook failed, commit aborted");
+                return Ok(false);
+            }
+            Some(edited) if edited.is_empty() => {
+                println!("Aborting commit due to empty commit message.");
+                return Ok(false);
+            }
+            Some(edited) => message = edited,
         }
+    }
+
+    let outcome = repo
+        .commit(&message, &options)
+        .map_err(|e| error_data!("commit", e.to_string(), "Failed to create commit"))?;
 
-        temp_head.timestamp = Some(info.timestamp);
-        temp_head.is_file = true;
-        temp_head.hash = info.hash;
+    report_commit(&root, outcome)
+}
+
+// Adauga in index fisierele urmarite de sub `paths`; `Err` contine prima cale
+// care nu se potriveste cu niciun fisier urmarit
+fn stage_pathspec(root: &str, paths: &[String]) -> Result<Result<(), String>, ErrorData> {
+    let tip = head_commit(root)
+        .map_err(|e| error_data!("stage_pathspec", e.to_string(), "Failed to get HEAD commit"))?;
+    let (head_entries, _) = read_commit_trees(root, &tip).map_err(|e| {
+        error_data!(
+            "stage_pathspec",
+            e.to_string(),
+            "Failed to read HEAD commit"
+        )
+    })?;
+
+    let mut index_data = IndexData::lock(root)
+        .map_err(|e| error_data!("stage_pathspec", e.to_string(), "Failed to lock index data"))?;
+
+    let selected = match select_paths(paths, &index_data.entries, &head_entries) {
+        Ok(s) => s,
+        Err(path) => return Ok(Err(path)),
+    };
+
+    stage_selected(root, &mut index_data, &selected).map_err(|e| {
+        error_data!(
+            "stage_pathspec",
+            e.to_string(),
+            "Failed to stage selected paths"
+        )
+    })?;
+
+    index_data
+        .save_index()
+        .map_err(|e| error_data!("stage_pathspec", e.to_string(), "Failed to save index data"))?;
+
+    Ok(Ok(()))
+}
+
+// Afiseaza rezultatul si, pentru un commit creat, ruleaza post-commit;
+// `true` daca a fost creat un commit
+fn report_commit(root: &str, outcome: CommitOutcome) -> Result<bool, ErrorData> {
+    match outcome {
+        CommitOutcome::Created { gc, .. } => {
+            if let Some(report) = gc {
+                print_auto_gc_report(&report);
+            }
+
+            // Rezultatul post-commit nu mai poate anula commit-ul
+            let hook = run_hook(root, "post-commit", &[]).map_err(|e| {
+                error_data!(
+                    "report_commit",
+                    e.to_string(),
+                    "Failed to run post-commit hook"
+                )
+            })?;
+            print_hook_outcome("post-commit", &hook);
+            Ok(true)
+        }
+        CommitOutcome::NothingToAmend => {
+            println!("fatal: You have nothing to amend.");
+            Ok(false)
+        }
+        CommitOutcome::PathspecNotMatched(path) => {
+            println!(
+                "error: pathspec '{}' did not match any file(s) known to my_svn",
+                path
+            );
+            Ok(false)
+        }
+        CommitOutcome::NothingToCommit => {
+            println!("nothing to commit (use --allow-empty to record a commit anyway)");
+            Ok(false)
+        }
     }
+}
 
-    match travel_commit_tree(&root.root, &mut head) {
-        Ok(_) => {}
-        Err(e) => {
-            return Err(error_data!(
-                "commit",
-                e.to_string(),
-                "Failed to travel commit tree"
-            ));
+// commit-msg primeste calea fisierului cu mesajul si il poate modifica;
+// `None` inseamna ca hook-ul a respins mesajul
+fn run_commit_msg_hook(root: &str, message: &str) -> Result<Option<String>, ErrorData> {
+    let path = format_path(&vec![root, ".my_svn", "COMMIT_EDITMSG"]);
+
+    fs::write(&path, format!("{}\n", message)).map_err(|e| {
+        error_data!(
+            "run_commit_msg_hook",
+            e.to_string(),
+            "Failed to write commit message file"
+        )
+    })?;
+
+    let hook = run_hook(root, "commit-msg", &[".my_svn/COMMIT_EDITMSG"]).map_err(|e| {
+        error_data!(
+            "run_commit_msg_hook",
+            e.to_string(),
+            "Failed to run commit-msg hook"
+        )
+    })?;
+    print_hook_outcome("commit-msg", &hook);
+
+    if !hook.passed() {
+        return Ok(None);
+    }
+
+    let edited = fs::read_to_string(&path).map_err(|e| {
+        error_data!(
+            "run_commit_msg_hook",
+            e.to_string(),
+            "Failed to read commit message file"
+        )
+    })?;
+
+    Ok(Some(cleanup_message(&edited, false)))
+}
+
+// Fisierele urmarite (din index sau din varful ramurii) aflate sub fiecare
+// cale; `Err` contine prima cale care nu se potriveste cu niciun fisier urmarit
+fn select_paths(
+    pathspecs: &[String],
+    index: &HashMap<String, FileInfo>,
+    head: &HashMap<String, FileInfo>,
+) -> Result<Vec<String>, String> {
+    let mut selected: HashSet<String> = HashSet::new();
+
+    for pathspec in pathspecs {
+        let matches: Vec<&String> = index
+            .keys()
+            .chain(head.keys())
+            .filter(|path| pathspec.is_empty() || Path::new(path).starts_with(pathspec))
+            .collect();
+
+        if matches.is_empty() {
+            return Err(pathspec.clone());
         }
+
+        selected.extend(matches.into_iter().cloned());
     }
 
-    let commit_parent;
-    let current_branch = match get_current_branch(&root.root) {
-        Ok(b) => b,
-        Err(e) => {
-            return Err(error_data!(
-                "commit",
+    Ok(selected.into_iter().collect())
+}
+
+// Arborele noului commit si daca acesta nu contine niciun fisier; `Err` contine
+// prima cale din `paths` care nu se potriveste cu niciun fisier urmarit
+fn commit_tree(
+    root: &str,
+    index_data: &mut IndexData,
+    tip: &str,
+    paths: &[String],
+) -> Result<Result<(String, bool), String>, ErrorData> {
+    // Cu `commit <pathspec>` arborele porneste de la cel al varfului ramurii;
+    // indexul primeste doar fisierele alese, restul modificarilor din el
+    // raman pentru un commit ulterior
+    if paths.is_empty() {
+        let hash = write_index_tree(root, &index_data.entries, &mut index_data.trees)
+            .map_err(|e| error_data!("commit_tree", e.to_string(), "Failed to write tree"))?;
+
+        Ok(Ok((hash, index_data.entries.is_empty())))
+    } else {
+        let (mut entries, mut trees) = read_commit_trees(root, tip).map_err(|e| {
+            error_data!("commit_tree", e.to_string(), "Failed to read parent commit")
+        })?;
+
+        let selected = match select_paths(paths, &index_data.entries, &entries) {
+            Ok(s) => s,
+            Err(path) => return Ok(Err(path)),
+        };
+
+        stage_selected(root, index_data, &selected).map_err(|e| {
+            error_data!(
+                "commit_tree",
                 e.to_string(),
-                "Failed to get current branch"
-            ));
+                "Failed to stage selected paths"
+            )
+        })?;
+
+        for path in selected {
+            match index_data.entries.get(&path) {
+                Some(info) => entries.insert(path.clone(), info.clone()),
+                None => entries.remove(&path),
+            };
+
+            for dir in parent_dirs(&path) {
+                trees.remove(dir);
+            }
         }
+
+        let hash = write_index_tree(root, &entries, &mut trees)
+            .map_err(|e| error_data!("commit_tree", e.to_string(), "Failed to write tree"))?;
+
+        Ok(Ok((hash, entries.is_empty())))
+    }
+}
+
+// Un commit fara parinte este gol daca nu are fisiere
+fn unchanged_tree(parent_tree: Option<&str>, tree: &str, empty: bool) -> bool {
+    match parent_tree {
+        Some(parent) => parent == tree,
+        None => empty,
+    }
+}
+
+// Verificarile lui `create_commit` care nu depind de mesaj, facute de `commit`
+// inainte de a cere mesajul (editorul nu este deschis degeaba). Indexul nu este
+// salvat; sunt scrise doar obiectele de care commit-ul ar avea oricum nevoie.
+pub(crate) fn precheck_commit(
+    root: &str,
+    options: &CommitOptions,
+) -> Result<Option<CommitOutcome>, ErrorData> {
+    let tip = head_commit(root).map_err(|e| {
+        error_data!(
+            "precheck_commit",
+            e.to_string(),
+            "Failed to get HEAD commit"
+        )
+    })?;
+
+    if options.amend {
+        return Ok(tip.is_empty().then_some(CommitOutcome::NothingToAmend));
+    }
+
+    let mut index_data = IndexData::new(root).map_err(|e| {
+        error_data!(
+            "precheck_commit",
+            e.to_string(),
+            "Failed to load index data"
+        )
+    })?;
+
+    let (tree, empty) = match commit_tree(root, &mut index_data, &tip, &options.paths)
+        .map_err(|e| error_data!("precheck_commit", e.to_string(), "Failed to write tree"))?
+    {
+        Ok(tree) => tree,
+        Err(path) => return Ok(Some(CommitOutcome::PathspecNotMatched(path))),
     };
 
-    match get_branch_hash(&root.root, &current_branch) {
-        Ok(h) => {
-            if from_merge.is_empty() {
-                commit_parent = Some(vec![h]);
-            } else {
-                commit_parent = Some(vec![h, from_merge.to_string()]);
-            }
+    if options.allow_empty {
+        return Ok(None);
+    }
+
+    let parent_tree = match tip.is_empty() {
+        true => None,
+        false => {
+            let commit: CommitObject = load_object_json(root, &tip).map_err(|e| {
+                error_data!(
+                    "precheck_commit",
+                    e.to_string(),
+                    "Failed to load parent commit"
+                )
+            })?;
+            Some(commit.tree)
         }
-        Err(e) => {
-            return Err(error_data!(
-                "commit",
+    };
+
+    Ok(unchanged_tree(parent_tree.as_deref(), &tree, empty)
+        .then_some(CommitOutcome::NothingToCommit))
+}
+
+// `from_merge` este al doilea parinte al unui commit de merge (sau gol)
+pub fn create_commit(
+    root: &str,
+    message: String,
+    from_merge: &str,
+    options: &CommitOptions,
+) -> Result<CommitOutcome, ErrorData> {
+    if options.all {
+        stage_tracked_changes(root).map_err(|e| {
+            error_data!(
+                "create_commit",
                 e.to_string(),
-                "Failed to get branch hash"
-            ));
+                "Failed to stage tracked changes"
+            )
+        })?;
+    }
+
+    let current_head = read_head(root)
+        .map_err(|e| error_data!("create_commit", e.to_string(), "Failed to read HEAD"))?;
+
+    // Referinta ramane blocata de la citirea parintelui pana la scrierea noului commit
+    let ref_lock = lock_head_ref(root, &current_head)
+        .map_err(|e| error_data!("create_commit", e.to_string(), "Failed to lock HEAD"))?;
+
+    create_commit_locked(root, message, from_merge, options, current_head, ref_lock)
+}
+
+// Referinta mutata de un commit: ramura curenta sau, cu HEAD detasat, HEAD insusi
+pub(crate) fn lock_head_ref(root: &str, head: &Head) -> Result<LockFile, ErrorData> {
+    let ref_path = match head {
+        Head::Branch(branch) => format_path(&vec![root, ".my_svn", "refs", "heads", branch]),
+        Head::Detached(_) => format_path(&vec![root, ".my_svn", "HEAD"]),
+    };
+
+    LockFile::acquire(&ref_path).map_err(|e| {
+        error_data!(
+            "lock_head_ref",
+            e.to_string(),
+            "Failed to lock branch reference"
+        )
+    })
+}
+
+// Ca `create_commit`, pentru apelantii care au blocat deja referinta lui HEAD
+// (`merge` o blocheaza inainte sa modifice directorul de lucru)
+pub(crate) fn create_commit_locked(
+    root: &str,
+    message: String,
+    from_merge: &str,
+    options: &CommitOptions,
+    current_head: Head,
+    ref_lock: LockFile,
+) -> Result<CommitOutcome, ErrorData> {
+    let commit_parent;
+
+    let tip = match &current_head {
+        Head::Branch(branch) => get_branch_hash(root, branch).map_err(|e| {
+            error_data!("create_commit", e.to_string(), "Failed to get branch hash")
+        })?,
+        Head::Detached(hash) => hash.clone(),
+    };
+
+    let tip_commit: Option<CommitObject> = if tip.is_empty() {
+        None
+    } else {
+        Some(load_object_json(root, &tip).map_err(|e| {
+            error_data!(
+                "create_commit",
+                e.to_string(),
+                "Failed to load parent commit"
+            )
+        })?)
+    };
+
+    let mut index_data = IndexData::lock(root)
+        .map_err(|e| error_data!("create_commit", e.to_string(), "Failed to lock index data"))?;
+
+    let (tree_hash, entries_empty) = match commit_tree(root, &mut index_data, &tip, &options.paths)
+        .map_err(|e| error_data!("create_commit", e.to_string(), "Failed to write tree"))?
+    {
+        Ok(tree) => tree,
+        Err(path) => return Ok(CommitOutcome::PathspecNotMatched(path)),
+    };
+
+    // Arborele varfului ramurii, pentru a recunoaste un commit fara schimbari
+    let base_tree = tip_commit.as_ref().map(|c| c.tree.clone());
+
+    // Intrarea din reflog: tipul commit-ului si primul rand al mesajului
+    let kind = if options.amend {
+        "commit (amend)"
+    } else if !from_merge.is_empty() {
+        "commit (merge)"
+    } else if tip.is_empty() {
+        "commit (initial)"
+    } else {
+        "commit"
+    };
+    let reflog_message = format!("{}: {}", kind, message.lines().next().unwrap_or_default());
+    let old_tip = tip.clone();
+
+    // --amend inlocuieste varful ramurii, pastrandu-i parintii si autorul
+    let mut amended: Option<CommitObject> = None;
+
+    if options.amend {
+        let tip_commit = match tip_commit {
+            Some(c) => c,
+            None => return Ok(CommitOutcome::NothingToAmend),
+        };
+
+        commit_parent = tip_commit.parent.clone();
+        amended = Some(tip_commit);
+    } else if from_merge.is_empty() {
+        // Un commit care nu schimba arborele parintelui nu este creat
+        if !options.allow_empty && unchanged_tree(base_tree.as_deref(), &tree_hash, entries_empty) {
+            return Ok(CommitOutcome::NothingToCommit);
         }
+
+        commit_parent = if tip.is_empty() {
+            None
+        } else {
+            Some(vec![tip])
+        };
+    } else {
+        commit_parent = Some(vec![tip, from_merge.to_string()]);
+    }
+
+    // Un commit nu poate avea acelasi parinte de doua ori (ex. `merge HEAD`)
+    if let Some(parents) = &commit_parent
+        && parents
+            .iter()
+            .enumerate()
+            .any(|(i, p)| p.is_empty() || parents[..i].contains(p))
+    {
+        return Err(error_data!(
+            "create_commit",
+            parents.join(" "),
+            "Invalid commit parents"
+        ));
     }
 
     let start = SystemTime::now();
@@ -214,74 +979,89 @@ pub fn commit(message: String, from_merge: &str) -> Result<(), ErrorData> {
         Ok(n) => n,
         Err(e) => {
             return Err(error_data!(
-                "commit",
+                "create_commit",
                 e.to_string(),
                 "SystemTime before UNIX EPOCH!"
             ));
         }
     };
 
+    let now = timestamp_duration.as_secs();
+
+    let author = match amended.and_then(|c| c.author) {
+        Some(a) => a,
+        None => resolve_identity(root, IdentityRole::Author, now)
+            .map_err(|e| error_data!("create_commit", e.to_string(), "Failed to get author"))?,
+    };
+    let committer = resolve_identity(root, IdentityRole::Committer, now)
+        .map_err(|e| error_data!("create_commit", e.to_string(), "Failed to get committer"))?;
+
     let commit_obj: CommitObject = CommitObject {
-        tree: head.hash,
+        tree: tree_hash,
         parent: commit_parent,
         message,
-        timestamp: timestamp_duration.as_secs(),
+        timestamp: committer.timestamp,
+        author: Some(author),
+        committer: Some(committer),
     };
     let json_string = match serde_json::to_string(&commit_obj) {
         Ok(j) => j,
         Err(e) => {
             return Err(error_data!(
-                "commit",
+                "create_commit",
                 e.to_string(),
                 "Failed to serialize commit object to JSON"
             ));
         }
     };
 
-    let mut hasher = Sha1::new();
-    hasher.update(json_string.as_bytes());
-    let hash = hasher.finalize();
+    let hash = hash_bytes(root, json_string.as_bytes()).map_err(|e| {
+        error_data!(
+            "create_commit",
+            e.to_string(),
+            "Failed to hash commit object"
+        )
+    })?;
 
-    fs::write(
-        format_path(&vec![&root.root, ".my_svn", "objects", &hex::encode(hash)]),
-        json_string,
-    )
-    .map_err(|e| {
+    write_object(root, &hash, ObjectType::Commit, json_string.as_bytes()).map_err(|e| {
         error_data!(
-            "commit",
+            "create_commit",
             e.to_string(),
             "Failed to write commit object to file"
         )
     })?;
 
-    fs::write(
-        format_path(&vec![
-            &root.root,
-            ".my_svn",
-            "refs",
-            "heads",
-            &current_branch,
-        ]),
-        hex::encode(hash),
-    )
-    .map_err(|e| {
+    ref_lock.commit(hash.as_bytes()).map_err(|e| {
         error_data!(
-            "commit",
+            "create_commit",
             e.to_string(),
             "Failed to write commit reference to file"
         )
     })?;
 
-    Ok(())
+    append_head_reflog(root, &current_head, &old_tip, &hash, &reflog_message)
+        .map_err(|e| error_data!("create_commit", e.to_string(), "Failed to update reflog"))?;
+
+    // Indexul pastreaza arborii scrisi, ca urmatorul commit sa ii refoloseasca
+    index_data
+        .save_index()
+        .map_err(|e| error_data!("create_commit", e.to_string(), "Failed to save index data"))?;
+
+    let gc = auto_gc(root)
+        .map_err(|e| error_data!("create_commit", e.to_string(), "Failed to run automatic gc"))?;
+
+    Ok(CommitOutcome::Created { hash, gc })
 }
 
+// `trees` primeste hash-ul arborelui fiecarui director ("" este radacina)
 fn read_commit_data_rec(
-    objects_path: &String,
+    root: &str,
     hash: String,
     path: String,
     data: &mut HashMap<String, FileInfo>,
+    trees: &mut TreeHashes,
 ) -> Result<(), ErrorData> {
-    let content: Vec<TreeData> = match load_json(&format_path(&vec![objects_path, &hash])) {
+    let content: Vec<TreeData> = match load_object_json(root, &hash) {
         Ok(c) => c,
         Err(e) => {
             return Err(error_data!(
@@ -292,6 +1072,8 @@ fn read_commit_data_rec(
         }
     };
 
+    trees.insert(path.clone(), hash);
+
     for i in content {
         let new_path = if path.is_empty() {
             i.name
@@ -300,13 +1082,16 @@ fn read_commit_data_rec(
         };
 
         if i.data_type == TreeDataType::Folder {
-            read_commit_data_rec(objects_path, i.hash, new_path, data)?;
+            read_commit_data_rec(root, i.hash, new_path, data, trees)?;
         } else {
             data.insert(
                 new_path,
                 FileInfo {
                     hash: i.hash,
-                    timestamp: i.timestamp.unwrap_or(0),
+                    // Arborii nu retin data modificarii; 0 nu se potriveste
+                    // cu niciun fisier, deci continutul este comparat dupa hash
+                    timestamp: 0,
+                    mode: i.mode,
                 },
             );
         }
@@ -334,23 +1119,23 @@ pub fn read_commit(root: &str, branch: &str) -> Result<HashMap<String, FileInfo>
         return Ok(commit_data);
     }
 
-    let tree_root: CommitObject =
-        match load_json(&format_path(&vec![root, ".my_svn", "objects", &tree_hash])) {
-            Ok(data) => data,
-            Err(e) => {
-                return Err(error_data!(
-                    "read_commit",
-                    e.to_string(),
-                    "Failed to load commit object"
-                ));
-            }
-        };
+    let tree_root: CommitObject = match load_object_json(root, &tree_hash) {
+        Ok(data) => data,
+        Err(e) => {
+            return Err(error_data!(
+                "read_commit",
+                e.to_string(),
+                "Failed to load commit object"
+            ));
+        }
+    };
 
     match read_commit_data_rec(
-        &format_path(&vec![root, ".my_svn", "objects"]),
+        root,
         tree_root.tree,
         String::new(),
         &mut commit_data,
+        &mut HashMap::new(),
     ) {
         Ok(_) => (),
         Err(e) => {
@@ -368,21 +1153,21 @@ pub fn read_commit(root: &str, branch: &str) -> Result<HashMap<String, FileInfo>
 pub fn find_base_commit(
     current_commit: &String,
     target_commit: &str,
-    root: &String,
+    root: &str,
 ) -> Result<Option<String>, ErrorData> {
     let mut current_parents: HashSet<String> = HashSet::new();
-    let mut temp_commit = current_commit.to_owned();
     let mut parent_queue: VecDeque<String> = VecDeque::new();
 
-    while !temp_commit.is_empty() {
-        current_parents.insert(temp_commit.clone());
+    if !current_commit.is_empty() {
+        parent_queue.push_back(current_commit.to_owned());
+    }
+
+    while let Some(temp_commit) = parent_queue.pop_front() {
+        if !current_parents.insert(temp_commit.clone()) {
+            continue;
+        }
 
-        let commit_obj: CommitObject = match load_json(&format_path(&vec![
-            &root,
-            ".my_svn",
-            "objects",
-            &temp_commit,
-        ])) {
+        let commit_obj: CommitObject = match load_object_json(root, &temp_commit) {
             Ok(data) => data,
             Err(e) => {
                 return Err(error_data!(
@@ -393,27 +1178,11 @@ pub fn find_base_commit(
             }
         };
 
-        if let Some(parent) = commit_obj.parent {
-            temp_commit = parent[0].clone();
-
-            if parent.len() > 1 {
-                for i in parent.iter().skip(1) {
-                    parent_queue.push_back(i.clone());
-                }
-            }
-        } else if !parent_queue.is_empty() {
-            temp_commit = String::new();
-            while !parent_queue.is_empty() && temp_commit.is_empty() {
-                temp_commit = parent_queue.pop_front().unwrap_or_default();
-            }
-        } else {
-            break;
-        }
+        parent_queue.extend(commit_obj.parents().iter().cloned());
     }
 
-    temp_commit = target_commit.to_owned();
-    parent_queue.clear();
-    parent_queue.push_back(temp_commit);
+    let mut temp_commit;
+    parent_queue.push_back(target_commit.to_owned());
 
     while !parent_queue.is_empty() {
         temp_commit = match parent_queue.pop_front() {
@@ -425,12 +1194,7 @@ pub fn find_base_commit(
             return Ok(Some(temp_commit));
         }
 
-        let commit_obj: CommitObject = match load_json(&format_path(&vec![
-            &root,
-            ".my_svn",
-            "objects",
-            &temp_commit,
-        ])) {
+        let commit_obj: CommitObject = match load_object_json(root, &temp_commit) {
             Ok(data) => data,
             Err(e) => {
                 return Err(error_data!(
@@ -441,57 +1205,222 @@ pub fn find_base_commit(
             }
         };
 
-        if let Some(parent) = commit_obj.parent {
-            for i in parent {
-                parent_queue.push_back(i.clone());
-            }
-        }
+        parent_queue.extend(commit_obj.parents().iter().cloned());
     }
 
     Ok(None)
 }
 
-pub fn read_commit_from_hash(
-    root: &String,
+// Fisierele unui commit, impreuna cu arborele fiecarui director
+pub(crate) fn read_commit_trees(
+    root: &str,
     commit_hash: &str,
-) -> Result<HashMap<String, FileInfo>, ErrorData> {
+) -> Result<(HashMap<String, FileInfo>, TreeHashes), ErrorData> {
     let mut commit_data: HashMap<String, FileInfo> = HashMap::new();
+    let mut trees = TreeHashes::new();
 
     if commit_hash.is_empty() {
-        return Ok(commit_data);
+        return Ok((commit_data, trees));
     }
 
-    let tree_root: CommitObject = match load_json(&format_path(&vec![
-        &root,
-        ".my_svn",
-        "objects",
-        &commit_hash,
-    ])) {
-        Ok(data) => data,
-        Err(e) => {
-            return Err(error_data!(
-                "read_commit_from_hash",
-                e.to_string(),
-                "Failed to load commit object"
-            ));
-        }
-    };
+    let commit: CommitObject = load_object_json(root, commit_hash).map_err(|e| {
+        error_data!(
+            "read_commit_trees",
+            e.to_string(),
+            "Failed to load commit object"
+        )
+    })?;
 
-    match read_commit_data_rec(
-        &(root.to_owned() + "/.my_svn/objects/"),
-        tree_root.tree,
+    read_commit_data_rec(
+        root,
+        commit.tree,
         String::new(),
         &mut commit_data,
-    ) {
-        Ok(_) => (),
-        Err(e) => {
-            return Err(error_data!(
+        &mut trees,
+    )
+    .map_err(|e| {
+        error_data!(
+            "read_commit_trees",
+            e.to_string(),
+            "Failed to read commit data recursively"
+        )
+    })?;
+
+    Ok((commit_data, trees))
+}
+
+pub fn read_commit_from_hash(
+    root: &str,
+    commit_hash: &str,
+) -> Result<HashMap<String, FileInfo>, ErrorData> {
+    read_commit_trees(root, commit_hash)
+        .map(|(commit_data, _)| commit_data)
+        .map_err(|e| {
+            error_data!(
                 "read_commit_from_hash",
                 e.to_string(),
-                "Failed to read commit data recursively"
-            ));
+                "Failed to read commit"
+            )
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::{
+        config::{Config, repo_config_path},
+        object::{list_loose_objects, object_path},
+        testing::TempRepo,
+    };
+
+    #[cfg(unix)]
+    fn hook(repo: &TempRepo, name: &str, body: &str) {
+        use std::os::unix::fs::PermissionsExt;
+
+        let relative = format!(".my_svn/hooks/{}", name);
+        repo.write(&relative, &format!("#!/bin/sh\n{}", body));
+        fs::set_permissions(repo.path(&relative), fs::Permissions::from_mode(0o755)).unwrap();
+    }
+
+    // Un commit gol, ca testele hook-urilor sa nu depinda de index
+    fn commit_message(repo: &TempRepo, message: &str) -> bool {
+        let options = CommitOptions {
+            allow_empty: true,
+            ..Default::default()
+        };
+        Config::update(&repo_config_path(&repo.root), |c| {
+            c.set("user.name", "tester")
+        })
+        .unwrap();
+        let location = Repository::open(&repo.root).ok().expect("open repository");
+
+        commit_in(
+            location,
+            MessageSource::Paragraphs(vec![message.to_string()]),
+            options,
+        )
+        .unwrap()
+    }
+
+    fn head_message(repo: &TempRepo) -> String {
+        let tip = head_commit(&repo.root).unwrap();
+        let commit: CommitObject = load_object_json(&repo.root, &tip).unwrap();
+        commit.message
+    }
+
+    fn file(hash: &str) -> FileInfo {
+        FileInfo {
+            hash: hash.to_string(),
+            timestamp: 0,
+            mode: FileMode::Regular,
         }
     }
 
-    Ok(commit_data)
+    // 20 directoare cu cate 50 de fisiere, plus cate un subdirector
+    fn many_entries(repo: &TempRepo) -> HashMap<String, FileInfo> {
+        let mut entries = HashMap::new();
+
+        for d in 0..20 {
+            for f in 0..50 {
+                let content = format!("{}/{}", d, f);
+                let hash = hash_bytes(&repo.root, content.as_bytes()).unwrap();
+                entries.insert(format!("d{}/f{}", d, f), file(&hash));
+                entries.insert(format!("d{}/sub/f{}", d, f), file(&hash));
+            }
+        }
+
+        entries
+    }
+
+    #[test]
+    fn unchanged_directories_are_reused_without_reading() {
+        let repo = TempRepo::new();
+        let mut entries = many_entries(&repo);
+
+        let mut trees = TreeHashes::new();
+        let first = write_index_tree(&repo.root, &entries, &mut trees).unwrap();
+        assert_eq!(trees.len(), 41);
+        assert_eq!(trees[""], first);
+
+        // Arborii directoarelor neschimbate nu sunt cititi: unul stricat nu conteaza
+        fs::write(object_path(&repo.root, &trees["d3"]), b"not a tree").unwrap();
+
+        let changed = hash_bytes(&repo.root, b"changed").unwrap();
+        entries.insert(String::from("d5/sub/f1"), file(&changed));
+        for dir in parent_dirs("d5/sub/f1") {
+            trees.remove(dir);
+        }
+
+        let before = list_loose_objects(&repo.root).unwrap().len();
+        let second = write_index_tree(&repo.root, &entries, &mut trees).unwrap();
+
+        // Doar arborii de pe calea schimbata sunt scrisi: d5/sub, d5 si radacina
+        assert_eq!(list_loose_objects(&repo.root).unwrap().len(), before + 3);
+        assert_ne!(second, first);
+        assert_eq!(trees[""], second);
+
+        // Acelasi rezultat ca scrierea tuturor arborilor de la zero
+        assert_eq!(
+            write_index_tree(&repo.root, &entries, &mut TreeHashes::new()).unwrap(),
+            second
+        );
+    }
+
+    #[test]
+    fn missing_cached_tree_is_written_again() {
+        let repo = TempRepo::new();
+        let entries = many_entries(&repo);
+
+        let mut trees = TreeHashes::new();
+        let first = write_index_tree(&repo.root, &entries, &mut trees).unwrap();
+
+        // Ca dupa un `gc` care a sters arborii unui commit abandonat
+        fs::remove_file(object_path(&repo.root, &trees["d7"])).unwrap();
+        fs::remove_file(object_path(&repo.root, &first)).unwrap();
+
+        assert_eq!(
+            write_index_tree(&repo.root, &entries, &mut trees).unwrap(),
+            first
+        );
+        assert!(object_exists(&repo.root, &trees["d7"]));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn failing_pre_commit_hook_aborts_the_commit() {
+        let repo = TempRepo::new();
+        hook(&repo, "pre-commit", "exit 1\n");
+
+        assert!(!commit_message(&repo, "blocked"));
+        assert_eq!(head_commit(&repo.root).unwrap(), "");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn commit_msg_hook_can_rewrite_the_message() {
+        let repo = TempRepo::new();
+        hook(
+            &repo,
+            "commit-msg",
+            "echo \"rewritten: $(cat \"$1\")\" > \"$1\"\n",
+        );
+
+        assert!(commit_message(&repo, "original"));
+        assert_eq!(head_message(&repo), "rewritten: original");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn post_commit_exit_status_is_ignored() {
+        let repo = TempRepo::new();
+        hook(
+            &repo,
+            "post-commit",
+            "touch .my_svn/post-commit-ran\nexit 1\n",
+        );
+
+        assert!(commit_message(&repo, "kept"));
+        assert_eq!(head_message(&repo), "kept");
+        assert!(Path::new(&repo.path(".my_svn/post-commit-ran")).is_file());
+    }
 }
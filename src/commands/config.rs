@@ -0,0 +1,105 @@
+use crate::{
+    error_data,
+    utils::{
+        config::{Config, load_config, repo_config_path, user_config_path},
+        error::ErrorData,
+        path::{RepoLocationError::*, find_repo_root},
+    },
+};
+
+#[derive(PartialEq)]
+pub enum ConfigAction {
+    Get(String),
+    Set(String, String),
+    Unset(String),
+    List,
+}
+
+// Cheile care descriu formatul pe disc sunt fixate la `init`
+const READ_ONLY_KEYS: [&str; 2] = ["core.repositoryformatversion", "core.objectformat"];
+
+pub fn config(action: ConfigAction, global: bool) -> Result<(), ErrorData> {
+    let path = if global {
+        match user_config_path() {
+            Some(p) => p,
+            None => {
+                println!("fatal: could not determine the home directory for the user config");
+                return Ok(());
+            }
+        }
+    } else {
+        match find_repo_root(&"./".to_string()) {
+            Ok(rep_loc) => repo_config_path(&rep_loc.root),
+            Err(e) => match e {
+                ErrorData(ed) => {
+                    return Err(error_data!(
+                        "config",
+                        ed.to_string(),
+                        "Failed to find repository root"
+                    ));
+                }
+                RepositoryNotFoundError => {
+                    println!(
+                        "fatal: not a svn repository (or any of the parent directories): .my_svn"
+                    );
+                    return Ok(());
+                }
+            },
+        }
+    };
+
+    if let ConfigAction::Set(key, _) | ConfigAction::Unset(key) = &action
+        && READ_ONLY_KEYS.contains(&key.to_lowercase().as_str())
+    {
+        println!(
+            "fatal: {} can only be set when the repository is created",
+            key
+        );
+        return Ok(());
+    }
+
+    let file_config = Config::load(&path)
+        .map_err(|e| error_data!("config", e.to_string(), "Failed to load config file"))?;
+
+    // Fara --global, citirile vad config-ul utilizatorului combinat cu cel al depozitului
+    let effective_config = if global {
+        None
+    } else {
+        match find_repo_root(&"./".to_string()) {
+            Ok(rep_loc) => Some(load_config(&rep_loc.root).map_err(|e| {
+                error_data!("config", e.to_string(), "Failed to load merged config")
+            })?),
+            Err(_) => None,
+        }
+    };
+    let read_config = effective_config.as_ref().unwrap_or(&file_config);
+
+    match action {
+        ConfigAction::Get(key) => {
+            if let Some(value) = read_config.get(&key) {
+                println!("{}", value);
+            }
+        }
+        ConfigAction::List => {
+            for (key, value) in read_config.entries() {
+                println!("{}={}", key, value);
+            }
+        }
+        ConfigAction::Set(key, value) => {
+            if !Config::update(&path, |config| config.set(&key, &value))
+                .map_err(|e| error_data!("config", e.to_string(), "Failed to save config file"))?
+            {
+                println!("fatal: invalid key '{}' (expected section.name)", key);
+            }
+        }
+        ConfigAction::Unset(key) => {
+            if !Config::update(&path, |config| config.unset(&key))
+                .map_err(|e| error_data!("config", e.to_string(), "Failed to save config file"))?
+            {
+                println!("fatal: key '{}' is not set", key);
+            }
+        }
+    }
+
+    Ok(())
+}
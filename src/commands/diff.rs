@@ -1,20 +1,19 @@
 use std::collections::HashMap;
 use std::hash::{DefaultHasher, Hash, Hasher};
-use std::path::Path;
 
 use colored::Colorize;
 
-use crate::commands::branch::{get_branch_hash, get_current_branch};
+use crate::commands::branch::head_commit;
 use crate::commands::commit::CommitObject;
-use crate::utils::json::load_json;
+use crate::utils::json::load_object_json;
+use crate::utils::object::read_object;
 use crate::utils::path::RepoLocationError::*;
+use crate::utils::revision::{RevisionError, Revisions, resolve_revisions};
 use crate::{
     commands::commit::read_commit_from_hash,
     error_data,
-    utils::{
-        error::ErrorData,
-        path::{find_repo_root, format_path},
-    },
+    repository::Repository,
+    utils::{error::ErrorData, path::find_repo_root},
 };
 
 struct FileView {
@@ -24,9 +23,7 @@ struct FileView {
 }
 
 impl FileView {
-    fn new(filepath: &str) -> std::io::Result<Self> {
-        let data = std::fs::read(filepath)?;
-
+    fn new(data: Vec<u8>) -> Self {
         let mut hashes = Vec::new();
         let mut offsets = Vec::new();
 
@@ -52,11 +49,11 @@ impl FileView {
             offsets.push((start, data.len()));
         }
 
-        Ok(FileView {
+        FileView {
             data,
             line_hashes: hashes,
             line_offsets: offsets,
-        })
+        }
     }
 
     fn get_line(&self, index: usize) -> &str {
@@ -72,11 +69,34 @@ impl FileView {
 }
 
 #[derive(Debug, Clone, PartialEq)]
-enum DiffChange {
+pub enum DiffChange {
     Insert(String),
     Delete(String),
 }
 
+#[derive(Debug, Clone, PartialEq)]
+pub enum FileDiff {
+    Added(String),
+    Deleted(String),
+    Modified(String, Vec<DiffChange>),
+}
+
+impl FileDiff {
+    pub fn path(&self) -> &str {
+        match self {
+            FileDiff::Added(path) | FileDiff::Deleted(path) | FileDiff::Modified(path, _) => path,
+        }
+    }
+}
+
+pub enum DiffOutcome {
+    NoCommits,
+    InvalidRevision(RevisionError),
+    SameCommit,
+    NoParent,
+    Changes(Vec<FileDiff>),
+}
+
 fn myers_diff(old_lines: &FileView, new_lines: &FileView) -> Vec<DiffChange> {
     let n = old_lines.line_hashes.len() as isize;
     let m = new_lines.line_hashes.len() as isize;
@@ -214,99 +234,139 @@ fn backtrack(
 }
 
 //first_hash = commit-ul curent / second_hash = commit-ul cu care se face diff
-fn diff_between_hash(first_hash: &str, second_hash: &str, root: &str) -> Result<(), ErrorData> {
-    let first_commit = match read_commit_from_hash(&root.to_string(), first_hash) {
+pub fn diff_commits(
+    root: &str,
+    first_hash: &str,
+    second_hash: &str,
+) -> Result<Vec<FileDiff>, ErrorData> {
+    let first_commit = match read_commit_from_hash(root, first_hash) {
         Ok(data) => data,
         Err(e) => {
             return Err(error_data!(
-                "diff",
+                "diff_commits",
                 e.to_string(),
                 "Error reading commit for diff"
             ));
         }
     };
-    let mut second_commit = match read_commit_from_hash(&root.to_string(), second_hash) {
+    let mut second_commit = match read_commit_from_hash(root, second_hash) {
         Ok(data) => data,
         Err(e) => {
             return Err(error_data!(
-                "diff",
+                "diff_commits",
                 e.to_string(),
                 "Error reading commit for diff"
             ));
         }
     };
 
-    let mut diff_found = false;
+    let mut diffs: Vec<FileDiff> = Vec::new();
 
     for (path, info) in first_commit.iter() {
         if second_commit.contains_key(path) {
             if second_commit[path].hash != info.hash {
-                println!("File {} was modified:", path.yellow());
-
-                let mut tabs: String = String::new();
-                for _ in 0..(path.len() + String::from("File  was modified:").len()) {
-                    tabs.push(' ');
-                }
-
-                let second_commit_file = match FileView::new(&format_path(&vec![
-                    &root,
-                    ".my_svn",
-                    "objects",
-                    &second_commit[path].hash,
-                ])) {
-                    Ok(data) => data,
+                let second_commit_file = match read_object(root, &second_commit[path].hash) {
+                    Ok(data) => FileView::new(data),
                     Err(e) => {
                         return Err(error_data!(
-                            "diff",
+                            "diff_commits",
                             e.to_string(),
                             "Error opening file for diff"
                         ));
                     }
                 };
 
-                let first_commit_file = match FileView::new(&format_path(&vec![
-                    &root, ".my_svn", "objects", &info.hash,
-                ])) {
-                    Ok(data) => data,
+                let first_commit_file = match read_object(root, &info.hash) {
+                    Ok(data) => FileView::new(data),
                     Err(e) => {
                         return Err(error_data!(
-                            "diff",
+                            "diff_commits",
                             e.to_string(),
                             "Error opening file for diff"
                         ));
                     }
                 };
 
-                let diffs = myers_diff(&second_commit_file, &first_commit_file);
-                diff_found = diff_found || !diffs.is_empty();
-                for diff in diffs {
-                    match diff {
-                        DiffChange::Insert(line) => {
-                            println!("{}{}", tabs, format!("+{}", line).green());
-                        }
-                        DiffChange::Delete(line) => {
-                            println!("{}{}", tabs, format!("-{}", line).red());
-                        }
-                    }
-                }
+                diffs.push(FileDiff::Modified(
+                    path.clone(),
+                    myers_diff(&second_commit_file, &first_commit_file),
+                ));
             }
 
             second_commit.remove(path);
         } else {
-            diff_found = true;
-            println!("File {} was added.", path.green());
+            diffs.push(FileDiff::Added(path.clone()));
         }
     }
 
-    for (path, _) in second_commit.iter() {
-        println!("File {} was deleted.", path.red());
+    for path in second_commit.into_keys() {
+        diffs.push(FileDiff::Deleted(path));
     }
 
-    if !diff_found && second_commit.is_empty() {
-        println!("No differences found between the specified commits.");
+    diffs.sort_by(|a, b| a.path().cmp(b.path()));
+
+    Ok(diffs)
+}
+
+pub fn diff_against(root: &str, target: Option<&str>) -> Result<DiffOutcome, ErrorData> {
+    let current_commit_hash = head_commit(root).map_err(|e| {
+        error_data!(
+            "diff_against",
+            e.to_string(),
+            "Failed to get current commit hash"
+        )
+    })?;
+
+    if current_commit_hash.is_empty() {
+        return Ok(DiffOutcome::NoCommits);
     }
 
-    Ok(())
+    // `diff a..b` compara b cu a; `diff <rev>` compara HEAD cu rev
+    let (new_commit_hash, old_commit_hash) = if let Some(spec) = target {
+        let (new_hash, old_hash) = match resolve_revisions(root, spec).map_err(|e| {
+            error_data!(
+                "diff_against",
+                e.to_string(),
+                "Failed to resolve target revision"
+            )
+        })? {
+            Ok(Revisions::Single(hash)) => (current_commit_hash, hash),
+            Ok(Revisions::Range { from, to }) => (to, from),
+            Err(e) => return Ok(DiffOutcome::InvalidRevision(e)),
+        };
+
+        if new_hash == old_hash {
+            return Ok(DiffOutcome::SameCommit);
+        }
+
+        (new_hash, old_hash)
+    } else {
+        let current_commit = match load_object_json::<CommitObject>(root, &current_commit_hash) {
+            Ok(c) => c,
+            Err(e) => {
+                return Err(error_data!(
+                    "diff_against",
+                    e.to_string(),
+                    "Failed to read current commit"
+                ));
+            }
+        };
+
+        match current_commit.parents().first() {
+            Some(parent) => (current_commit_hash.clone(), parent.clone()),
+            None => return Ok(DiffOutcome::NoParent),
+        }
+    };
+
+    let diffs = diff_commits(root, &new_commit_hash, &old_commit_hash).map_err(|e| {
+        error_data!(
+            "diff_against",
+            e.to_string(),
+            "Error during diff between commits"
+        )
+    })?;
+
+    Ok(DiffOutcome::Changes(diffs))
 }
 
 pub fn diff(commit: Option<String>) -> Result<(), ErrorData> {
@@ -315,7 +375,7 @@ pub fn diff(commit: Option<String>) -> Result<(), ErrorData> {
         Err(e) => match e {
             ErrorData(ed) => {
                 return Err(error_data!(
-                    "add",
+                    "diff",
                     ed.to_string(),
                     "Failed to find repository root"
                 ));
@@ -327,84 +387,67 @@ pub fn diff(commit: Option<String>) -> Result<(), ErrorData> {
         },
     };
 
-    let branch = match get_current_branch(&root.root) {
-        Ok(b) => b,
-        Err(e) => {
-            return Err(error_data!(
-                "diff",
-                e.to_string(),
-                "Failed to get current branch"
-            ));
+    let outcome = Repository::from_location(root)
+        .diff(commit.as_deref())
+        .map_err(|e| error_data!("diff", e.to_string(), "Error during diff between commits"))?;
+
+    let diffs = match outcome {
+        DiffOutcome::NoCommits => {
+            println!("No commits found on the current branch to diff.");
+            return Ok(());
         }
-    };
-    let current_commit_hash = match get_branch_hash(&root.root, &branch) {
-        Ok(h) => h,
-        Err(e) => {
-            return Err(error_data!(
-                "diff",
-                e.to_string(),
-                "Failed to get current commit hash"
-            ));
+        DiffOutcome::InvalidRevision(e) => {
+            println!("fatal: {}", e);
+            return Ok(());
+        }
+        DiffOutcome::SameCommit => {
+            println!("No differences between the same commit.");
+            return Ok(());
+        }
+        DiffOutcome::NoParent => {
+            println!("No parent commit to diff against.");
+            return Ok(());
         }
+        DiffOutcome::Changes(diffs) => diffs,
     };
 
-    if current_commit_hash.is_empty() {
-        println!("No commits found on the current branch to diff.");
-        return Ok(());
-    }
+    let mut diff_found = false;
 
-    if let Some(commit_name) = commit {
-        let target_commit_hash = if Path::new(&format_path(&vec![
-            &root.root,
-            ".my_svn",
-            "refs",
-            "heads",
-            &commit_name,
-        ]))
-        .exists()
-        {
-            get_branch_hash(&root.root, &commit_name).map_err(|e| {
-                error_data!("diff", e.to_string(), "Failed to get target commit hash")
-            })?
-        } else {
-            println!("fatal: A branch named '{}' does not exist.", commit_name);
-            return Ok(());
-        };
+    for file_diff in diffs {
+        match file_diff {
+            FileDiff::Modified(path, changes) => {
+                println!("File {} was modified:", path.yellow());
 
-        if current_commit_hash == target_commit_hash {
-            println!("No differences between the same commit.");
-            return Ok(());
-        }
+                let mut tabs: String = String::new();
+                for _ in 0..(path.len() + String::from("File  was modified:").len()) {
+                    tabs.push(' ');
+                }
 
-        diff_between_hash(&current_commit_hash, &target_commit_hash, &root.root)
-            .map_err(|e| error_data!("diff", e.to_string(), "Error during diff between commits"))?;
-    } else {
-        let current_commit = match load_json::<CommitObject>(&format_path(&vec![
-            &root.root,
-            ".my_svn",
-            "objects",
-            &current_commit_hash,
-        ])) {
-            Ok(c) => c,
-            Err(e) => {
-                return Err(error_data!(
-                    "diff",
-                    e.to_string(),
-                    "Failed to read current commit"
-                ));
+                diff_found = diff_found || !changes.is_empty();
+                for change in changes {
+                    match change {
+                        DiffChange::Insert(line) => {
+                            println!("{}{}", tabs, format!("+{}", line).green());
+                        }
+                        DiffChange::Delete(line) => {
+                            println!("{}{}", tabs, format!("-{}", line).red());
+                        }
+                    }
+                }
             }
-        };
-
-        let parent_commit_hash = match &current_commit.parent {
-            Some(parents) => parents[0].clone(),
-            None => {
-                println!("No parent commit to diff against.");
-                return Ok(());
+            FileDiff::Added(path) => {
+                diff_found = true;
+                println!("File {} was added.", path.green());
             }
-        };
+            FileDiff::Deleted(path) => {
+                diff_found = true;
+                println!("File {} was deleted.", path.red());
+            }
+        }
+    }
 
-        diff_between_hash(&current_commit_hash, &parent_commit_hash, &root.root)
-            .map_err(|e| error_data!("diff", e.to_string(), "Error during diff between commits"))?;
+    if !diff_found {
+        println!("No differences found between the specified commits.");
     }
 
     Ok(())
@@ -0,0 +1,334 @@
+use colored::Colorize;
+use std::collections::{HashMap, HashSet};
+
+use crate::{
+    commands::{
+        branch::{Head, get_branch_hash, list_branches, read_head},
+        commit::{CommitObject, TreeData, TreeDataType, serialize_tree},
+        tag::{TagObject, get_tag_hash, list_tags},
+    },
+    error_data,
+    utils::{
+        error::ErrorData,
+        hash::hash_bytes,
+        index::IndexData,
+        object::{ObjectType, list_loose_objects, object_exists, read_typed_object},
+        pack::list_packed_objects,
+        path::{RepoLocationError::*, find_repo_root},
+        reflog::reflog_hashes,
+    },
+};
+
+// Obiectele la care trimite un commit, un tree sau un tag, impreuna cu tipul asteptat
+pub(crate) fn object_links(
+    object_type: ObjectType,
+    data: &[u8],
+) -> Result<Vec<(String, ObjectType)>, String> {
+    match object_type {
+        ObjectType::Commit => {
+            let commit: CommitObject =
+                serde_json::from_slice(data).map_err(|e| format!("invalid commit: {}", e))?;
+
+            let mut links = vec![(commit.tree.clone(), ObjectType::Tree)];
+            for parent in commit.parents() {
+                links.push((parent.clone(), ObjectType::Commit));
+            }
+
+            Ok(links)
+        }
+        ObjectType::Tree => {
+            let entries: Vec<TreeData> =
+                serde_json::from_slice(data).map_err(|e| format!("invalid tree: {}", e))?;
+
+            Ok(entries
+                .into_iter()
+                .map(|entry| {
+                    let entry_type = if entry.data_type == TreeDataType::Folder {
+                        ObjectType::Tree
+                    } else {
+                        ObjectType::Blob
+                    };
+                    (entry.hash, entry_type)
+                })
+                .collect())
+        }
+        ObjectType::Tag => {
+            let tag: TagObject =
+                serde_json::from_slice(data).map_err(|e| format!("invalid tag: {}", e))?;
+
+            let target_type = ObjectType::from_name(&tag.object_type)
+                .ok_or_else(|| format!("invalid tag: unknown type '{}'", tag.object_type))?;
+
+            Ok(vec![(tag.object, target_type)])
+        }
+        ObjectType::Blob => Ok(Vec::new()),
+    }
+}
+
+// Un arbore este canonic daca serializarea lui sortata da exact aceiasi octeti.
+// Campurile necunoscute (ca `timestamp`, scris de versiunile vechi) sunt ignorate
+// la citire, deci si arborii care le contin ies necanonici. Arborii care nu pot
+// fi cititi sunt raportati separat, de `object_links`
+pub(crate) fn is_canonical_tree(data: &[u8]) -> bool {
+    let mut entries: Vec<TreeData> = match serde_json::from_slice(data) {
+        Ok(e) => e,
+        Err(_) => return true,
+    };
+
+    match serialize_tree(&mut entries) {
+        Ok(json) => json.as_bytes() == data,
+        Err(_) => true,
+    }
+}
+
+// Returneaza true daca depozitul are obiecte lipsa sau corupte
+pub fn fsck() -> Result<bool, ErrorData> {
+    let root = match find_repo_root(&"./".to_string()) {
+        Ok(rep_loc) => rep_loc,
+        Err(e) => match e {
+            ErrorData(ed) => {
+                return Err(error_data!(
+                    "fsck",
+                    ed.to_string(),
+                    "Failed to find repository root"
+                ));
+            }
+            RepositoryNotFoundError => {
+                println!("fatal: not a svn repository (or any of the parent directories): .my_svn");
+                return Ok(true);
+            }
+        },
+    };
+
+    let mut damaged = false;
+
+    let mut stack: Vec<(String, ObjectType)> = Vec::new();
+
+    for branch in list_branches(&root.root)
+        .map_err(|e| error_data!("fsck", e.to_string(), "Failed to list branches"))?
+    {
+        match get_branch_hash(&root.root, &branch) {
+            Ok(hash) if hash.is_empty() => {}
+            Ok(hash) => stack.push((hash, ObjectType::Commit)),
+            Err(e) => {
+                damaged = true;
+                println!("{} refs/heads/{}: {}", "error:".red(), branch, e.source);
+            }
+        }
+    }
+
+    for tag in list_tags(&root.root)
+        .map_err(|e| error_data!("fsck", e.to_string(), "Failed to list tags"))?
+    {
+        match get_tag_hash(&root.root, &tag) {
+            // Un tag simplu indica direct spre un commit, unul adnotat spre obiectul lui
+            Ok(Some(hash)) => {
+                let expected_type = match read_typed_object(&root.root, &hash) {
+                    Ok((ObjectType::Tag, _)) => ObjectType::Tag,
+                    _ => ObjectType::Commit,
+                };
+                stack.push((hash, expected_type));
+            }
+            Ok(None) => {}
+            Err(e) => {
+                damaged = true;
+                println!("{} refs/tags/{}: {}", "error:".red(), tag, e.source);
+            }
+        }
+    }
+
+    if let Head::Detached(hash) = read_head(&root.root)
+        .map_err(|e| error_data!("fsck", e.to_string(), "Failed to read HEAD"))?
+    {
+        stack.push((hash, ObjectType::Commit));
+    }
+
+    // Vechile valori ale referintelor nu sunt raportate ca neaccesibile
+    for hash in reflog_hashes(&root.root, 0)
+        .map_err(|e| error_data!("fsck", e.to_string(), "Failed to read reflogs"))?
+    {
+        if !object_exists(&root.root, &hash) {
+            continue;
+        }
+
+        let expected_type = match read_typed_object(&root.root, &hash) {
+            Ok((ObjectType::Tag, _)) => ObjectType::Tag,
+            _ => ObjectType::Commit,
+        };
+        stack.push((hash, expected_type));
+    }
+
+    let index = IndexData::new(&root.root)
+        .map_err(|e| error_data!("fsck", e.to_string(), "Failed to load index data"))?;
+    for info in index.entries.values() {
+        stack.push((info.hash.clone(), ObjectType::Blob));
+    }
+
+    let mut reachable: HashSet<String> = HashSet::new();
+
+    while let Some((hash, expected_type)) = stack.pop() {
+        if !reachable.insert(hash.clone()) {
+            continue;
+        }
+
+        if !object_exists(&root.root, &hash) {
+            damaged = true;
+            println!("{} {} {}", "missing".red(), expected_type.as_str(), hash);
+            continue;
+        }
+
+        let (object_type, data) = match read_typed_object(&root.root, &hash) {
+            Ok(o) => o,
+            Err(e) => {
+                damaged = true;
+                println!(
+                    "{} {} {}: {}",
+                    "corrupt".red(),
+                    expected_type.as_str(),
+                    hash,
+                    e.source.lines().last().unwrap_or_default()
+                );
+                continue;
+            }
+        };
+
+        let actual_hash = hash_bytes(&root.root, &data)
+            .map_err(|e| error_data!("fsck", e.to_string(), "Failed to hash object"))?;
+
+        if actual_hash != hash {
+            damaged = true;
+            println!(
+                "{} {} {}: content hashes to {}",
+                "corrupt".red(),
+                object_type.as_str(),
+                hash,
+                actual_hash
+            );
+            continue;
+        }
+
+        if object_type != expected_type {
+            damaged = true;
+            println!(
+                "{} {} {}: expected a {}",
+                "corrupt".red(),
+                object_type.as_str(),
+                hash,
+                expected_type.as_str()
+            );
+            continue;
+        }
+
+        if object_type == ObjectType::Tree && !is_canonical_tree(&data) {
+            println!(
+                "{} tree {}: entries are not in canonical form (run `my_svn migrate`)",
+                "noncanonical".yellow(),
+                hash
+            );
+        }
+
+        match object_links(object_type, &data) {
+            Ok(links) => stack.extend(links),
+            Err(reason) => {
+                damaged = true;
+                println!(
+                    "{} {} {}: {}",
+                    "corrupt".red(),
+                    object_type.as_str(),
+                    hash,
+                    reason
+                );
+            }
+        }
+    }
+
+    let mut all_objects: HashSet<String> = list_packed_objects(&root.root)
+        .map_err(|e| error_data!("fsck", e.to_string(), "Failed to list packed objects"))?
+        .into_iter()
+        .collect();
+    for (hash, _) in list_loose_objects(&root.root)
+        .map_err(|e| error_data!("fsck", e.to_string(), "Failed to list loose objects"))?
+    {
+        all_objects.insert(hash);
+    }
+
+    // Un obiect neaccesibil este "dangling" daca nici alt obiect neaccesibil nu trimite la el
+    let mut unreachable: HashMap<String, ObjectType> = HashMap::new();
+    let mut referenced: HashSet<String> = HashSet::new();
+
+    for hash in all_objects.iter().filter(|h| !reachable.contains(*h)) {
+        match read_typed_object(&root.root, hash) {
+            Ok((object_type, data)) => {
+                if let Ok(links) = object_links(object_type, &data) {
+                    referenced.extend(links.into_iter().map(|(h, _)| h));
+                }
+                unreachable.insert(hash.clone(), object_type);
+            }
+            Err(e) => {
+                damaged = true;
+                println!(
+                    "{} object {}: {}",
+                    "corrupt".red(),
+                    hash,
+                    e.source.lines().last().unwrap_or_default()
+                );
+            }
+        }
+    }
+
+    let mut unreachable_sorted: Vec<(&String, &ObjectType)> = unreachable.iter().collect();
+    unreachable_sorted.sort_by(|a, b| a.0.cmp(b.0));
+
+    for (hash, object_type) in unreachable_sorted {
+        if referenced.contains(hash) {
+            println!(
+                "{} {} {}",
+                "unreachable".yellow(),
+                object_type.as_str(),
+                hash
+            );
+        } else {
+            println!("{} {} {}", "dangling".yellow(), object_type.as_str(), hash);
+        }
+    }
+
+    println!(
+        "Checked {} reachable object(s), {} unreachable.",
+        reachable.len(),
+        unreachable.len()
+    );
+
+    Ok(damaged)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BLOB: &str = "ce013625030ba8dba906f756967f9e9ca394464a";
+
+    #[test]
+    fn sorted_tree_without_timestamps_is_canonical() {
+        let tree = format!(
+            r#"[{{"data_type":"File","name":"a","hash":"{0}"}},{{"data_type":"File","name":"b","hash":"{0}","mode":"Executable"}}]"#,
+            BLOB
+        );
+
+        assert!(is_canonical_tree(tree.as_bytes()));
+    }
+
+    #[test]
+    fn unsorted_or_timestamped_tree_is_noncanonical() {
+        let unsorted = format!(
+            r#"[{{"data_type":"File","name":"b","hash":"{0}"}},{{"data_type":"File","name":"a","hash":"{0}"}}]"#,
+            BLOB
+        );
+        let timestamped = format!(
+            r#"[{{"data_type":"File","name":"a","hash":"{}","timestamp":1700000000}}]"#,
+            BLOB
+        );
+
+        assert!(!is_canonical_tree(unsorted.as_bytes()));
+        assert!(!is_canonical_tree(timestamped.as_bytes()));
+    }
+}
@@ -0,0 +1,503 @@
+use chrono::{Local, NaiveDate, NaiveDateTime, TimeZone};
+use std::{
+    collections::HashSet,
+    fs,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use crate::{
+    commands::{
+        branch::{Head, get_branch_hash, list_branches, read_head},
+        fsck::object_links,
+        tag::{get_tag_hash, list_tags},
+    },
+    error_data,
+    utils::{
+        config::load_config,
+        error::ErrorData,
+        index::IndexData,
+        object::{ObjectType, list_loose_objects, object_exists, read_typed_object},
+        pack::{list_packed_objects, packed_object_mtime, repack_objects},
+        path::{RepoLocationError::*, find_repo_root},
+        reflog::{expire_reflogs, reflog_hashes},
+    },
+};
+
+const DEFAULT_PRUNE_EXPIRE: &str = "2.weeks.ago";
+const DEFAULT_REFLOG_EXPIRE: &str = "90.days.ago";
+const DEFAULT_AUTO_THRESHOLD: usize = 6700;
+
+// Accepta "now", "never", "<N>.<unitate>.ago" (ex. "2.weeks.ago"),
+// "YYYY-MM-DD" si "YYYY-MM-DD HH:MM:SS" (ora locala)
+pub fn parse_prune_date(value: &str) -> Option<u64> {
+    let now = SystemTime::now().duration_since(UNIX_EPOCH).ok()?.as_secs();
+
+    match value {
+        "now" => return Some(now),
+        "never" => return Some(0),
+        _ => {}
+    }
+
+    if let Some(relative) = value.strip_suffix(".ago") {
+        let (count, unit) = relative.split_once('.')?;
+        let count: u64 = count.parse().ok()?;
+
+        let seconds = match unit.trim_end_matches('s') {
+            "second" => 1,
+            "minute" => 60,
+            "hour" => 60 * 60,
+            "day" => 24 * 60 * 60,
+            "week" => 7 * 24 * 60 * 60,
+            "month" => 30 * 24 * 60 * 60,
+            "year" => 365 * 24 * 60 * 60,
+            _ => return None,
+        };
+
+        return Some(now.saturating_sub(count.checked_mul(seconds)?));
+    }
+
+    let datetime = match NaiveDateTime::parse_from_str(value, "%Y-%m-%d %H:%M:%S") {
+        Ok(dt) => dt,
+        Err(_) => NaiveDate::parse_from_str(value, "%Y-%m-%d")
+            .ok()?
+            .and_hms_opt(0, 0, 0)?,
+    };
+
+    let timestamp = Local.from_local_datetime(&datetime).earliest()?.timestamp();
+
+    u64::try_from(timestamp).ok()
+}
+
+// Toate obiectele accesibile din ramuri, tag-uri, HEAD, intrarile din reflog scrise dupa
+// `reflog_since` si din index. Un obiect lipsa sau corupt opreste colectarea, ca sa nu
+// stergem nimic dintr-un depozit deteriorat.
+pub fn reachable_objects(root: &str, reflog_since: u64) -> Result<HashSet<String>, ErrorData> {
+    let mut stack: Vec<String> = Vec::new();
+
+    for branch in list_branches(root).map_err(|e| {
+        error_data!(
+            "reachable_objects",
+            e.to_string(),
+            "Failed to list branches"
+        )
+    })? {
+        let hash = get_branch_hash(root, &branch).map_err(|e| {
+            error_data!(
+                "reachable_objects",
+                e.to_string(),
+                "Failed to get branch hash"
+            )
+        })?;
+
+        if !hash.is_empty() {
+            stack.push(hash);
+        }
+    }
+
+    for tag in list_tags(root)
+        .map_err(|e| error_data!("reachable_objects", e.to_string(), "Failed to list tags"))?
+    {
+        let hash = get_tag_hash(root, &tag).map_err(|e| {
+            error_data!("reachable_objects", e.to_string(), "Failed to get tag hash")
+        })?;
+
+        stack.extend(hash);
+    }
+
+    // Commit-urile facute cu HEAD detasat nu apartin niciunei ramuri
+    if let Head::Detached(hash) = read_head(root)
+        .map_err(|e| error_data!("reachable_objects", e.to_string(), "Failed to read HEAD"))?
+    {
+        stack.push(hash);
+    }
+
+    // Intrarile recente din reflog indica spre obiecte care trebuie pastrate; cele
+    // deja sterse de un gc mai vechi sunt ignorate
+    for hash in reflog_hashes(root, reflog_since)
+        .map_err(|e| error_data!("reachable_objects", e.to_string(), "Failed to read reflogs"))?
+    {
+        if object_exists(root, &hash) {
+            stack.push(hash);
+        }
+    }
+
+    let index = IndexData::new(root).map_err(|e| {
+        error_data!(
+            "reachable_objects",
+            e.to_string(),
+            "Failed to load index data"
+        )
+    })?;
+    stack.extend(index.entries.values().map(|info| info.hash.clone()));
+
+    let mut reachable: HashSet<String> = HashSet::new();
+
+    while let Some(hash) = stack.pop() {
+        if !reachable.insert(hash.clone()) {
+            continue;
+        }
+
+        let (object_type, data) = read_typed_object(root, &hash).map_err(|e| {
+            error_data!(
+                "reachable_objects",
+                e.to_string(),
+                "Failed to read reachable object (run fsck)"
+            )
+        })?;
+
+        if object_type == ObjectType::Blob {
+            continue;
+        }
+
+        let links = match object_links(object_type, &data) {
+            Ok(l) => l,
+            Err(reason) => {
+                return Err(error_data!(
+                    "reachable_objects",
+                    format!("{} {}: {}", object_type.as_str(), hash, reason),
+                    "Corrupt reachable object (run fsck)"
+                ));
+            }
+        };
+
+        stack.extend(links.into_iter().map(|(h, _)| h));
+    }
+
+    Ok(reachable)
+}
+
+fn older_than(mtime: Option<SystemTime>, cutoff: u64) -> bool {
+    match mtime.and_then(|t| t.duration_since(UNIX_EPOCH).ok()) {
+        Some(age) => age.as_secs() <= cutoff,
+        None => false,
+    }
+}
+
+// Rezultatul unei colectari; afisarea ramane in seama comenzii
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct GcReport {
+    // Obiectele libere gasite inainte de colectare
+    pub loose_objects: usize,
+    // Obiectele neaccesibile sterse (sau care ar fi sterse, cu `--dry-run`)
+    pub pruned: Vec<String>,
+    pub packed: usize,
+    pub deltas: usize,
+}
+
+// `cutoff` este varsta obiectelor neaccesibile sterse, `reflog_cutoff` a intrarilor
+// din reflog care nu mai tin obiectele in viata
+fn run_gc(
+    root: &str,
+    cutoff: u64,
+    reflog_cutoff: u64,
+    dry_run: bool,
+) -> Result<GcReport, ErrorData> {
+    let reachable = reachable_objects(root, reflog_cutoff)
+        .map_err(|e| error_data!("run_gc", e.to_string(), "Failed to compute reachability"))?;
+
+    let mut to_prune: HashSet<String> = HashSet::new();
+    let mut keep_loose: HashSet<String> = HashSet::new();
+    let mut loose_to_remove = Vec::new();
+
+    let loose = list_loose_objects(root)
+        .map_err(|e| error_data!("run_gc", e.to_string(), "Failed to list loose objects"))?;
+    let loose_objects = loose.len();
+
+    for (hash, path) in loose {
+        if reachable.contains(&hash) {
+            continue;
+        }
+
+        let mtime = fs::metadata(&path).and_then(|m| m.modified()).ok();
+        if older_than(mtime, cutoff) {
+            to_prune.insert(hash);
+            loose_to_remove.push(path);
+        } else {
+            // Obiectele neaccesibile recente raman libere pana expira
+            keep_loose.insert(hash);
+        }
+    }
+
+    for hash in list_packed_objects(root)
+        .map_err(|e| error_data!("run_gc", e.to_string(), "Failed to list packed objects"))?
+    {
+        if !reachable.contains(&hash) && older_than(packed_object_mtime(root, &hash), cutoff) {
+            to_prune.insert(hash);
+        }
+    }
+
+    let mut pruned: Vec<String> = to_prune.iter().cloned().collect();
+    pruned.sort();
+
+    if dry_run {
+        return Ok(GcReport {
+            loose_objects,
+            pruned,
+            ..Default::default()
+        });
+    }
+
+    expire_reflogs(root, reflog_cutoff)
+        .map_err(|e| error_data!("run_gc", e.to_string(), "Failed to expire reflogs"))?;
+
+    let mut excluded = to_prune;
+    excluded.extend(keep_loose);
+
+    let stats = repack_objects(root, true, &excluded)
+        .map_err(|e| error_data!("run_gc", e.to_string(), "Failed to repack objects"))?;
+
+    for path in loose_to_remove {
+        fs::remove_file(&path).map_err(|e| {
+            error_data!(
+                "run_gc",
+                e.to_string(),
+                "Failed to remove unreachable object"
+            )
+        })?;
+
+        if let Some(parent) = path.parent() {
+            let _ = fs::remove_dir(parent);
+        }
+    }
+
+    Ok(GcReport {
+        loose_objects,
+        pruned,
+        packed: stats.objects,
+        deltas: stats.deltas,
+    })
+}
+
+pub fn print_gc_report(report: &GcReport) {
+    for hash in &report.pruned {
+        println!("Removing unreachable object {}", hash);
+    }
+
+    println!(
+        "Removed {} unreachable object(s), packed {} object(s) ({} as deltas).",
+        report.pruned.len(),
+        report.packed,
+        report.deltas
+    );
+}
+
+// Afiseaza colectarea pornita automat de `add` sau `commit`
+pub fn print_auto_gc_report(report: &GcReport) {
+    println!(
+        "Auto packing the repository ({} loose objects)...",
+        report.loose_objects
+    );
+    print_gc_report(report);
+}
+
+pub fn gc(prune: Option<String>, dry_run: bool) -> Result<(), ErrorData> {
+    let root = match find_repo_root(&"./".to_string()) {
+        Ok(rep_loc) => rep_loc,
+        Err(e) => match e {
+            ErrorData(ed) => {
+                return Err(error_data!(
+                    "gc",
+                    ed.to_string(),
+                    "Failed to find repository root"
+                ));
+            }
+            RepositoryNotFoundError => {
+                println!("fatal: not a svn repository (or any of the parent directories): .my_svn");
+                return Ok(());
+            }
+        },
+    };
+
+    let config = load_config(&root.root)
+        .map_err(|e| error_data!("gc", e.to_string(), "Failed to load config"))?;
+
+    let prune = match prune {
+        Some(p) => p,
+        None => config
+            .get("gc.pruneexpire")
+            .unwrap_or(DEFAULT_PRUNE_EXPIRE)
+            .to_string(),
+    };
+
+    let cutoff = match parse_prune_date(&prune) {
+        Some(c) => c,
+        None => {
+            println!("fatal: invalid prune date '{}'", prune);
+            return Ok(());
+        }
+    };
+
+    let reflog_expire = config
+        .get("gc.reflogexpire")
+        .unwrap_or(DEFAULT_REFLOG_EXPIRE);
+
+    let reflog_cutoff = match parse_prune_date(reflog_expire) {
+        Some(c) => c,
+        None => {
+            println!("fatal: invalid gc.reflogexpire date '{}'", reflog_expire);
+            return Ok(());
+        }
+    };
+
+    let report = run_gc(&root.root, cutoff, reflog_cutoff, dry_run)
+        .map_err(|e| error_data!("gc", e.to_string(), "Failed to collect garbage"))?;
+
+    if dry_run {
+        for hash in &report.pruned {
+            println!("Would remove unreachable object {}", hash);
+        }
+    } else {
+        print_gc_report(&report);
+    }
+
+    Ok(())
+}
+
+// Rulata dupa comenzile care scriu obiecte; `gc.auto = 0` o dezactiveaza.
+// Intoarce `None` daca pragul nu a fost depasit
+pub fn auto_gc(root: &str) -> Result<Option<GcReport>, ErrorData> {
+    let config = load_config(root)
+        .map_err(|e| error_data!("auto_gc", e.to_string(), "Failed to load config"))?;
+
+    let threshold = match config.get("gc.auto") {
+        Some(value) => match value.parse::<usize>() {
+            Ok(t) => t,
+            Err(e) => {
+                return Err(error_data!(
+                    "auto_gc",
+                    format!("gc.auto = {} ({})", value, e),
+                    "Invalid gc.auto value"
+                ));
+            }
+        },
+        None => DEFAULT_AUTO_THRESHOLD,
+    };
+
+    if threshold == 0 {
+        return Ok(None);
+    }
+
+    let loose_count = list_loose_objects(root)
+        .map_err(|e| error_data!("auto_gc", e.to_string(), "Failed to count loose objects"))?
+        .len();
+
+    if loose_count <= threshold {
+        return Ok(None);
+    }
+
+    let prune = config.get("gc.pruneexpire").unwrap_or(DEFAULT_PRUNE_EXPIRE);
+
+    let cutoff = match parse_prune_date(prune) {
+        Some(c) => c,
+        None => {
+            return Err(error_data!(
+                "auto_gc",
+                format!("gc.pruneexpire = {}", prune),
+                "Invalid gc.pruneexpire value"
+            ));
+        }
+    };
+
+    let reflog_expire = config
+        .get("gc.reflogexpire")
+        .unwrap_or(DEFAULT_REFLOG_EXPIRE);
+
+    let reflog_cutoff = match parse_prune_date(reflog_expire) {
+        Some(c) => c,
+        None => {
+            return Err(error_data!(
+                "auto_gc",
+                format!("gc.reflogexpire = {}", reflog_expire),
+                "Invalid gc.reflogexpire value"
+            ));
+        }
+    };
+
+    run_gc(root, cutoff, reflog_cutoff, false)
+        .map(Some)
+        .map_err(|e| error_data!("auto_gc", e.to_string(), "Failed to collect garbage"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::{
+        hash::hash_bytes,
+        object::{object_exists, object_path, write_object},
+        pack::repack,
+        testing::TempRepo,
+    };
+    use std::time::Duration;
+
+    fn write_blob(repo: &TempRepo, data: &[u8], mtime: SystemTime) -> String {
+        let hash = hash_bytes(&repo.root, data).unwrap();
+        write_object(&repo.root, &hash, ObjectType::Blob, data).unwrap();
+
+        fs::File::options()
+            .write(true)
+            .open(object_path(&repo.root, &hash))
+            .unwrap()
+            .set_modified(mtime)
+            .unwrap();
+
+        hash
+    }
+
+    #[test]
+    fn packed_unreachable_objects_age_out() {
+        let repo = TempRepo::new();
+        let old = write_blob(
+            &repo,
+            b"unreachable since 2020",
+            UNIX_EPOCH + Duration::from_secs(1_577_836_800),
+        );
+        let recent = write_blob(&repo, b"unreachable, but recent", SystemTime::now());
+
+        // Pack-ul este scris acum, dar obiectele isi pastreaza data
+        repack(&repo.root).unwrap();
+        let two_weeks = parse_prune_date("2.weeks.ago").unwrap();
+        let report = run_gc(&repo.root, two_weeks, 0, false).unwrap();
+        assert_eq!(report.pruned, vec![old.clone()]);
+        assert!(object_exists(&repo.root, &recent));
+        assert!(!object_exists(&repo.root, &old));
+
+        // Un gc ulterior rescrie pack-ul fara sa intinereasca obiectul ramas
+        run_gc(&repo.root, two_weeks, 0, false).unwrap();
+        assert!(object_exists(&repo.root, &recent));
+        run_gc(&repo.root, parse_prune_date("now").unwrap(), 0, false).unwrap();
+        assert!(!object_exists(&repo.root, &recent));
+    }
+
+    #[test]
+    fn expired_reflog_entries_no_longer_keep_objects() {
+        let repo = TempRepo::new();
+        let in_2020 = UNIX_EPOCH + Duration::from_secs(1_577_836_800);
+        let old = write_blob(&repo, b"only in an old reflog entry", in_2020);
+        let recent = write_blob(&repo, b"in a recent reflog entry", in_2020);
+
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_secs();
+        let zero = "0".repeat(old.len());
+        repo.write(
+            ".my_svn/logs/refs/heads/gone",
+            &format!(
+                "{} {} 1577836800\tcommit: old\n{} {} {}\tbranch: Created\n",
+                zero, old, zero, recent, now
+            ),
+        );
+
+        let two_weeks = parse_prune_date("2.weeks.ago").unwrap();
+        let ninety_days = parse_prune_date("90.days.ago").unwrap();
+        let report = run_gc(&repo.root, two_weeks, ninety_days, false).unwrap();
+
+        assert_eq!(report.pruned, vec![old.clone()]);
+        assert!(object_exists(&repo.root, &recent));
+
+        // Intrarea expirata a fost stearsa din jurnal
+        let log = fs::read_to_string(repo.path(".my_svn/logs/refs/heads/gone")).unwrap();
+        assert_eq!(log.lines().count(), 1);
+        assert!(log.contains("branch: Created"));
+    }
+}
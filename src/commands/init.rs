@@ -1,8 +1,35 @@
-use crate::{error_data, utils::error::ErrorData, utils::path::get_absolute_path};
+use crate::{
+    error_data,
+    utils::{
+        config::{Config, REPOSITORY_FORMAT_VERSION},
+        error::ErrorData,
+        hash::HashAlgorithm,
+        path::{format_path, get_absolute_path},
+    },
+};
 use std::{fs, path::Path};
 
-pub fn init() -> Result<String, ErrorData> {
+pub fn init(object_format: Option<HashAlgorithm>) -> Result<String, ErrorData> {
     if Path::new(".my_svn").exists() {
+        if let Some(requested) = object_format {
+            let config = Config::load(".my_svn/config").map_err(|e| {
+                error_data!("init", e.to_string(), "Failed to load repository config")
+            })?;
+
+            let existing = config
+                .get("core.objectformat")
+                .and_then(HashAlgorithm::from_name)
+                .unwrap_or(HashAlgorithm::Sha1);
+
+            if existing != requested {
+                return Ok(format!(
+                    "fatal: attempt to reinitialize repository with different object format ({} -> {})",
+                    existing.name(),
+                    requested.name()
+                ));
+            }
+        }
+
         return Ok(format!(
             "Reinitialized existing Svn repository in {}",
             get_absolute_path().map_err(|e| error_data!(
@@ -13,63 +40,119 @@ pub fn init() -> Result<String, ErrorData> {
         ));
     }
 
-    fs::create_dir(".my_svn")
-        .map_err(|e| error_data!("init", e.to_string(), "Failed to create .my_svn directory"))?;
+    create_repository(".", object_format)
+        .map_err(|e| error_data!("init", e.to_string(), "Failed to create repository"))?;
 
-    fs::create_dir(".my_svn/objects").map_err(|e| {
-        error_data!(
+    Ok(format!(
+        "Initialized empty Svn repository in {}",
+        get_absolute_path().map_err(|e| error_data!(
             "init",
             e.to_string(),
+            "Failed to get absolute path"
+        ))?
+    ))
+}
+
+// Creeaza `.my_svn` in directorul `root`, care trebuie sa existe deja
+pub fn create_repository(
+    root: &str,
+    object_format: Option<HashAlgorithm>,
+) -> Result<(), ErrorData> {
+    let svn_path = |parts: &[&str]| {
+        let mut path = vec![root, ".my_svn"];
+        path.extend_from_slice(parts);
+        format_path(&path)
+    };
+
+    fs::create_dir(svn_path(&[])).map_err(|e| {
+        error_data!(
+            "create_repository",
+            e.to_string(),
+            "Failed to create .my_svn directory"
+        )
+    })?;
+
+    fs::create_dir(svn_path(&["objects"])).map_err(|e| {
+        error_data!(
+            "create_repository",
+            e.to_string(),
             "Failed to create .my_svn/objects directory"
         )
     })?;
 
-    fs::create_dir(".my_svn/refs").map_err(|e| {
+    fs::create_dir(svn_path(&["refs"])).map_err(|e| {
         error_data!(
-            "init",
+            "create_repository",
             e.to_string(),
             "Failed to create .my_svn/refs directory"
         )
     })?;
 
-    fs::create_dir(".my_svn/refs/heads").map_err(|e| {
+    fs::create_dir(svn_path(&["refs", "heads"])).map_err(|e| {
         error_data!(
-            "init",
+            "create_repository",
             e.to_string(),
             "Failed to create .my_svn/refs/heads directory"
         )
     })?;
 
-    fs::write(".my_svn/refs/heads/main", "").map_err(|e| {
+    fs::create_dir(svn_path(&["refs", "tags"])).map_err(|e| {
         error_data!(
-            "init",
+            "create_repository",
+            e.to_string(),
+            "Failed to create .my_svn/refs/tags directory"
+        )
+    })?;
+
+    fs::create_dir(svn_path(&["hooks"])).map_err(|e| {
+        error_data!(
+            "create_repository",
+            e.to_string(),
+            "Failed to create .my_svn/hooks directory"
+        )
+    })?;
+
+    fs::write(svn_path(&["refs", "heads", "main"]), "").map_err(|e| {
+        error_data!(
+            "create_repository",
             e.to_string(),
             "Failed to write in .my_svn/refs/heads/main file"
         )
     })?;
 
-    fs::write(".my_svn/HEAD", "main").map_err(|e| {
+    fs::write(svn_path(&["HEAD"]), "main").map_err(|e| {
         error_data!(
-            "init",
+            "create_repository",
             e.to_string(),
             "Failed to write in .my_svn/HEAD file"
         )
     })?;
 
-    fs::write(".my_svn/index", "{}").map_err(|e| {
+    fs::write(svn_path(&["index"]), "{}").map_err(|e| {
         error_data!(
-            "init",
+            "create_repository",
             e.to_string(),
             "Failed to write in .my_svn/index file"
         )
     })?;
 
-    Ok(format!(
-        "Initialized empty Svn repository in {}",
-        get_absolute_path().map_err(|e| error_data!(
-            "init",
+    let mut config = Config::default();
+    config.set(
+        "core.repositoryformatversion",
+        &REPOSITORY_FORMAT_VERSION.to_string(),
+    );
+    config.set(
+        "core.objectformat",
+        object_format.unwrap_or(HashAlgorithm::Sha1).name(),
+    );
+
+    config.save(&svn_path(&["config"])).map_err(|e| {
+        error_data!(
+            "create_repository",
             e.to_string(),
-            "Failed to get absolute path"
-        ))?
-    ))
+            "Failed to write in .my_svn/config file"
+        )
+    })?;
+
+    Ok(())
 }
@@ -3,27 +3,28 @@ use colored::Colorize;
 use std::cmp::Ordering;
 use std::collections::{BinaryHeap, HashSet};
 
-use crate::commands::branch::{get_branch_hash, get_current_branch};
+use crate::commands::branch::{Head, get_branch_hash};
 use crate::commands::commit::CommitObject;
 use crate::error_data;
-use crate::utils::json::load_json;
-use crate::utils::path::{RepoLocationError::*, format_path};
+use crate::repository::Repository;
+use crate::utils::json::load_object_json;
+use crate::utils::path::RepoLocationError::*;
 use crate::utils::{error::ErrorData, path::find_repo_root};
 
-struct CommitDataForLog {
-    commit: CommitObject,
-    hash: String,
+pub struct LogEntry {
+    pub hash: String,
+    pub commit: CommitObject,
 }
 
-impl PartialEq for CommitDataForLog {
+impl PartialEq for LogEntry {
     fn eq(&self, other: &Self) -> bool {
         self.commit.timestamp == other.commit.timestamp && self.hash == other.hash
     }
 }
 
-impl Eq for CommitDataForLog {}
+impl Eq for LogEntry {}
 
-impl Ord for CommitDataForLog {
+impl Ord for LogEntry {
     fn cmp(&self, other: &Self) -> Ordering {
         self.commit
             .timestamp
@@ -32,19 +33,145 @@ impl Ord for CommitDataForLog {
     }
 }
 
-impl PartialOrd for CommitDataForLog {
+impl PartialOrd for LogEntry {
     fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
         Some(self.cmp(other))
     }
 }
 
-pub fn log() -> Result<(), ErrorData> {
+// Parcurge istoricul in ordinea descrescatoare a datei; commit-urile
+// ajunse pe mai multe drumuri (dupa merge) sunt intoarse o singura data
+pub struct LogIter {
+    root: String,
+    heap: BinaryHeap<LogEntry>,
+    seen: HashSet<String>,
+}
+
+impl LogIter {
+    pub fn new(root: &str, start_hash: &str) -> Result<Self, ErrorData> {
+        let mut iter = LogIter {
+            root: root.to_string(),
+            heap: BinaryHeap::new(),
+            seen: HashSet::new(),
+        };
+
+        iter.push(start_hash).map_err(|e| {
+            error_data!(
+                "LogIter::new",
+                e.to_string(),
+                "Failed to load commit object"
+            )
+        })?;
+
+        Ok(iter)
+    }
+
+    // Commit-urile accesibile din `to`, dar nu si din `from` (`log from..to`)
+    pub fn range(root: &str, from: &str, to: &str) -> Result<Self, ErrorData> {
+        let mut iter = LogIter {
+            root: root.to_string(),
+            heap: BinaryHeap::new(),
+            seen: HashSet::new(),
+        };
+
+        for entry in LogIter::new(root, from)? {
+            let entry = entry.map_err(|e| {
+                error_data!(
+                    "LogIter::range",
+                    e.to_string(),
+                    "Failed to read excluded history"
+                )
+            })?;
+            iter.seen.insert(entry.hash);
+        }
+
+        iter.push(to).map_err(|e| {
+            error_data!(
+                "LogIter::range",
+                e.to_string(),
+                "Failed to load commit object"
+            )
+        })?;
+
+        Ok(iter)
+    }
+
+    pub fn from_branch(root: &str, branch: &str) -> Result<Self, ErrorData> {
+        let hash = get_branch_hash(root, branch).map_err(|e| {
+            error_data!(
+                "LogIter::from_branch",
+                e.to_string(),
+                "Failed to get branch hash"
+            )
+        })?;
+
+        LogIter::new(root, &hash)
+    }
+
+    fn push(&mut self, hash: &str) -> Result<(), ErrorData> {
+        if hash.is_empty() || self.seen.contains(hash) {
+            return Ok(());
+        }
+
+        let commit: CommitObject = load_object_json(&self.root, hash).map_err(|e| {
+            error_data!(
+                "LogIter::push",
+                e.to_string(),
+                "Failed to load commit object"
+            )
+        })?;
+
+        self.heap.push(LogEntry {
+            commit,
+            hash: hash.to_string(),
+        });
+
+        Ok(())
+    }
+}
+
+impl Iterator for LogIter {
+    type Item = Result<LogEntry, ErrorData>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let entry = self.heap.pop()?;
+
+            if !self.seen.insert(entry.hash.clone()) {
+                continue;
+            }
+
+            for parent in entry.commit.parents() {
+                if let Err(e) = self.push(parent) {
+                    return Some(Err(error_data!(
+                        "LogIter::next",
+                        e.to_string(),
+                        "Failed to load parent commit"
+                    )));
+                }
+            }
+
+            return Some(Ok(entry));
+        }
+    }
+}
+
+fn format_timestamp(timestamp: u64) -> Option<String> {
+    let data_time = DateTime::from_timestamp(timestamp as i64, 0)?;
+
+    let datetime: DateTime<Local> =
+        DateTime::from_naive_utc_and_offset(data_time.naive_utc(), *Local::now().offset());
+
+    Some(datetime.format("%Y-%m-%d %H:%M:%S").to_string())
+}
+
+pub fn log(target: Option<String>) -> Result<(), ErrorData> {
     let root = match find_repo_root(&"./".to_string()) {
         Ok(rep_loc) => rep_loc,
         Err(e) => match e {
             ErrorData(ed) => {
                 return Err(error_data!(
-                    "add",
+                    "log",
                     ed.to_string(),
                     "Failed to find repository root"
                 ));
@@ -56,114 +183,94 @@ pub fn log() -> Result<(), ErrorData> {
         },
     };
 
-    let current_branch = match get_current_branch(&root.root) {
-        Ok(b) => b,
-        Err(e) => {
-            return Err(error_data!(
-                "log",
-                e.to_string(),
-                "Failed to get current branch"
-            ));
-        }
-    };
+    let repo = Repository::from_location(root);
 
-    let mut commit_hash = match get_branch_hash(&root.root, &current_branch) {
-        Ok(h) => h,
-        Err(e) => {
-            return Err(error_data!(
-                "log",
-                e.to_string(),
-                "Failed to get current branch hash"
-            ));
-        }
-    };
+    // Fara argument se afiseaza istoricul lui HEAD
+    let entries = match &target {
+        None => {
+            let head = repo
+                .head()
+                .map_err(|e| error_data!("log", e.to_string(), "Failed to read HEAD"))?;
+
+            match head {
+                Head::Branch(branch) => println!(
+                    "{} -> {}",
+                    String::from("HEAD").bright_cyan(),
+                    branch.bright_green()
+                ),
+                Head::Detached(hash) => println!(
+                    "{} (detached at {})",
+                    String::from("HEAD").bright_cyan(),
+                    hash[..7.min(hash.len())].bright_green()
+                ),
+            }
 
-    let mut heap: BinaryHeap<CommitDataForLog> = BinaryHeap::new();
-    let mut commit_fr: HashSet<(u64, String)> = HashSet::new();
-
-    heap.push(CommitDataForLog {
-        commit: match load_json(&format_path(&vec![
-            &root.root,
-            ".my_svn",
-            "objects",
-            &commit_hash,
-        ])) {
-            Ok(c) => c,
+            repo.log()
+                .map_err(|e| error_data!("log", e.to_string(), "Failed to read history"))?
+        }
+        Some(spec) => match repo
+            .log_revisions(spec)
+            .map_err(|e| error_data!("log", e.to_string(), "Failed to read history"))?
+        {
+            Ok(entries) => entries,
             Err(e) => {
-                return Err(error_data!(
-                    "log",
-                    e.to_string(),
-                    "Failed to load commit object"
-                ));
+                println!("fatal: {}", e);
+                return Ok(());
             }
         },
-        hash: commit_hash.clone(),
-    });
-
-    println!(
-        "{} -> {}",
-        String::from("HEAD").bright_cyan(),
-        current_branch.bright_green()
-    );
-
-    while !heap.is_empty() {
-        let commit = match heap.pop() {
-            Some(c) => c,
-            None => continue,
-        };
+    };
 
-        if commit_fr.contains(&(commit.commit.timestamp, commit.hash.clone())) {
-            continue;
-        }
-        commit_fr.insert((commit.commit.timestamp, commit.hash.clone()));
+    for entry in entries {
+        let entry =
+            entry.map_err(|e| error_data!("log", e.to_string(), "Failed to read history"))?;
 
-        println!("Commit: {}", commit.hash.yellow());
+        println!("Commit: {}", entry.hash.yellow());
 
-        if let Some(parents) = &commit.commit.parent
-            && parents.len() > 1
-        {
+        let parents = entry.commit.parents();
+        if parents.len() > 1 {
             println!("Merge: {} + {}", parents[0], parents[1]);
         }
 
-        let data_time = match DateTime::from_timestamp(commit.commit.timestamp as i64, 0) {
-            Some(t) => t,
+        // Commit-urile vechi au doar data commit-ului
+        let author_timestamp = match entry.commit.author() {
+            Some(author) => {
+                println!("Author: {} <{}>", author.name, author.email);
+                author.timestamp
+            }
+            None => entry.commit.timestamp(),
+        };
+
+        let date_str = match format_timestamp(author_timestamp) {
+            Some(d) => d,
             None => {
-                println!("Invalid timestamp for commit {}", commit.hash);
+                println!("Invalid timestamp for commit {}", entry.hash);
                 continue;
             }
         };
+        println!("Date: {}", date_str);
 
-        let datetime: DateTime<Local> =
-            DateTime::from_naive_utc_and_offset(data_time.naive_utc(), *Local::now().offset());
-        let date_str = datetime.format("%Y-%m-%d %H:%M:%S").to_string();
+        if let (Some(author), Some(committer)) = (entry.commit.author(), entry.commit.committer())
+            && author != committer
+        {
+            println!("Committer: {} <{}>", committer.name, committer.email);
 
-        println!("Date: {}", date_str);
-        println!("Message: {}", commit.commit.message);
-        println!();
+            if let Some(commit_date) = format_timestamp(committer.timestamp) {
+                println!("CommitDate: {}", commit_date);
+            }
+        }
 
-        if let Some(parents) = commit.commit.parent {
-            for parent in parents {
-                commit_hash = parent;
-
-                if !commit_hash.is_empty() {
-                    heap.push(CommitDataForLog {
-                        commit: match load_json(
-                            &(root.root.clone() + "/.my_svn/objects/" + commit_hash.as_str()),
-                        ) {
-                            Ok(c) => c,
-                            Err(e) => {
-                                return Err(error_data!(
-                                    "log",
-                                    e.to_string(),
-                                    "Failed to load commit object"
-                                ));
-                            }
-                        },
-                        hash: commit_hash.clone(),
-                    });
-                }
+        // Liniile urmatoare ale mesajului sunt aliniate sub prima
+        if entry.commit.message().is_empty() {
+            println!("Message: ");
+        }
+        for (index, line) in entry.commit.message().lines().enumerate() {
+            match (index, line.is_empty()) {
+                (0, _) => println!("Message: {}", line),
+                (_, true) => println!(),
+                (_, false) => println!("         {}", line),
             }
         }
+        println!();
     }
 
     Ok(())
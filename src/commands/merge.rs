@@ -2,23 +2,41 @@ use std::collections::HashSet;
 use std::fs;
 use std::path::Path;
 
-use crate::commands::branch::{get_branch_hash, get_current_branch};
-use crate::commands::commit::{commit, find_base_commit, read_commit, read_commit_from_hash};
+use crate::commands::branch::{head_commit, read_head};
+use crate::commands::commit::{
+    CommitOptions, CommitOutcome, create_commit_locked, find_base_commit, lock_head_ref,
+    read_commit_from_hash,
+};
+use crate::commands::gc::{GcReport, print_auto_gc_report};
 use crate::error_data;
-use crate::utils::hash::calculate_hash;
+use crate::repository::Repository;
 use crate::utils::index::IndexData;
-use crate::utils::path::{
-    FileInfo, RepoLocationError::*, format_path, get_working_directory_optimized,
-};
+use crate::utils::object::restore_file;
+use crate::utils::path::{RepoLocationError::*, format_path, get_working_directory_optimized};
+use crate::utils::reflog::append_head_reflog;
+use crate::utils::revision::{RevisionError, resolve_commit};
 use crate::utils::{error::ErrorData, path::find_repo_root};
 
+pub enum MergeOutcome {
+    InvalidRevision(RevisionError),
+    // Tinta este deja continuta in HEAD; nu se creeaza niciun commit
+    AlreadyUpToDate,
+    NoCommonBase,
+    UncommittedChanges(String),
+    Conflict(String),
+    // HEAD era stramos al tintei si a fost mutat direct pe ea
+    FastForward { from: String, to: String },
+    // Commit-ul de merge si colectarea pornita automat dupa el
+    Merged { hash: String, gc: Option<GcReport> },
+}
+
 pub fn merge(branch_name: &str) -> Result<(), ErrorData> {
     let root = match find_repo_root(&"./".to_string()) {
         Ok(rep_loc) => rep_loc,
         Err(e) => match e {
             ErrorData(ed) => {
                 return Err(error_data!(
-                    "add",
+                    "merge",
                     ed.to_string(),
                     "Failed to find repository root"
                 ));
@@ -30,105 +48,157 @@ pub fn merge(branch_name: &str) -> Result<(), ErrorData> {
         },
     };
 
-    let branch_path = format_path(&vec![&root.root, ".my_svn", "refs", "heads", &branch_name]);
-    if !Path::new(&branch_path).exists() {
-        println!("fatal: A branch named '{}' does not exist.", branch_name);
-        return Ok(());
+    let outcome = Repository::from_location(root)
+        .merge(branch_name)
+        .map_err(|e| error_data!("merge", e.to_string(), "Failed to merge branch"))?;
+
+    match outcome {
+        MergeOutcome::InvalidRevision(e) => println!("fatal: {}", e),
+        MergeOutcome::AlreadyUpToDate => println!("Already up to date."),
+        MergeOutcome::FastForward { from, to } => {
+            println!(
+                "Updating {}..{}",
+                &from[..7.min(from.len())],
+                &to[..7.min(to.len())]
+            );
+            println!("Fast-forward");
+        }
+        MergeOutcome::NoCommonBase => {
+            println!("fatal: Could not find a common base commit for the merge.")
+        }
+        MergeOutcome::UncommittedChanges(path) => {
+            println!("fatal: Uncommitted changes in file {}!", path)
+        }
+        MergeOutcome::Conflict(path) => println!("fatal: Conflict at file {}!", path),
+        MergeOutcome::Merged { gc, .. } => {
+            if let Some(report) = gc {
+                print_auto_gc_report(&report);
+            }
+        }
     }
 
-    let current_branch = match get_current_branch(&root.root) {
-        Ok(b) => b,
+    Ok(())
+}
+
+pub fn merge_branch(root: &str, branch_name: &str) -> Result<MergeOutcome, ErrorData> {
+    let target_commit_hash = match resolve_commit(root, branch_name).map_err(|e| {
+        error_data!(
+            "merge_branch",
+            e.to_string(),
+            "Failed to resolve merge target"
+        )
+    })? {
+        Ok(hash) => hash,
+        Err(e) => return Ok(MergeOutcome::InvalidRevision(e)),
+    };
+
+    let head = match read_head(root) {
+        Ok(h) => h,
         Err(e) => {
             return Err(error_data!(
-                "merge",
+                "merge_branch",
                 e.to_string(),
                 "Failed to get current branch"
             ));
         }
     };
 
-    if current_branch == branch_name {
-        println!("fatal: You are already on branch '{}'.", branch_name);
-        return Ok(());
+    // Cu HEAD detasat commit-ul de merge este scris direct in HEAD. Referinta
+    // este blocata inainte de a citi varful, ca directorul de lucru si indexul
+    // sa fie modificate doar daca referinta poate fi apoi mutata
+    let ref_lock = lock_head_ref(root, &head)
+        .map_err(|e| error_data!("merge_branch", e.to_string(), "Failed to lock HEAD"))?;
+
+    if read_head(root)
+        .map_err(|e| error_data!("merge_branch", e.to_string(), "Failed to read HEAD"))?
+        != head
+    {
+        return Err(error_data!(
+            "merge_branch",
+            String::new(),
+            "HEAD moved during merge"
+        ));
     }
 
-    let mut your_commit = match read_commit(&root.root, &current_branch) {
-        Ok(c) => c,
+    let current_commit_hash = match head_commit(root) {
+        Ok(h) => h,
         Err(e) => {
             return Err(error_data!(
-                "merge",
+                "merge_branch",
                 e.to_string(),
-                "Failed to read current branch commit"
+                "Failed to get current branch hash"
             ));
         }
     };
 
-    let target_commit = match read_commit(&root.root, branch_name) {
-        Ok(c) => c,
+    // Comparatia se face intre commit-uri, nu intre nume: `merge HEAD` sau
+    // `merge HEAD~2` nu au nimic de unit
+    if target_commit_hash == current_commit_hash {
+        return Ok(MergeOutcome::AlreadyUpToDate);
+    }
+
+    let base_commit_hash = match find_base_commit(&current_commit_hash, &target_commit_hash, root) {
+        Ok(data) => match data {
+            Some(bc) => bc,
+            None => {
+                return Ok(MergeOutcome::NoCommonBase);
+            }
+        },
         Err(e) => {
             return Err(error_data!(
-                "merge",
+                "merge_branch",
                 e.to_string(),
-                "Failed to read target branch commit"
+                "Failed to find base commit"
             ));
         }
     };
 
-    let current_commit_hash = match get_branch_hash(&root.root, &current_branch) {
-        Ok(h) => h,
+    if base_commit_hash == target_commit_hash {
+        return Ok(MergeOutcome::AlreadyUpToDate);
+    }
+
+    // HEAD este stramos al tintei: fisierele sunt aduse la fel, dar ramura
+    // este mutata pe tinta in loc sa se creeze un commit de merge
+    let fast_forward = base_commit_hash == current_commit_hash;
+
+    let mut your_commit = match read_commit_from_hash(root, &current_commit_hash) {
+        Ok(c) => c,
         Err(e) => {
             return Err(error_data!(
-                "merge",
+                "merge_branch",
                 e.to_string(),
-                "Failed to get current branch hash"
+                "Failed to read current branch commit"
             ));
         }
     };
-    let target_commit_hash = match get_branch_hash(&root.root, branch_name) {
-        Ok(h) => h,
+
+    let target_commit = match read_commit_from_hash(root, &target_commit_hash) {
+        Ok(c) => c,
         Err(e) => {
             return Err(error_data!(
-                "merge",
+                "merge_branch",
                 e.to_string(),
-                "Failed to get target branch hash"
+                "Failed to read target branch commit"
             ));
         }
     };
 
-    let base_commit_hash =
-        match find_base_commit(&current_commit_hash, &target_commit_hash, &root.root) {
-            Ok(data) => match data {
-                Some(bc) => bc,
-                None => {
-                    println!("fatal: Could not find a common base commit for the merge.");
-                    return Ok(());
-                }
-            },
-            Err(e) => {
-                return Err(error_data!(
-                    "merge",
-                    e.to_string(),
-                    "Failed to find base commit"
-                ));
-            }
-        };
-
-    let base_commit = match read_commit_from_hash(&root.root, &base_commit_hash) {
+    let base_commit = match read_commit_from_hash(root, &base_commit_hash) {
         Ok(c) => c,
         Err(e) => {
             return Err(error_data!(
-                "merge",
+                "merge_branch",
                 e.to_string(),
                 "Failed to read base commit"
             ));
         }
     };
 
-    let mut working_dir_files = match get_working_directory_optimized(&root.root) {
+    let mut working_dir_files = match get_working_directory_optimized(root) {
         Ok(wd) => wd,
         Err(e) => {
             return Err(error_data!(
-                "merge",
+                "merge_branch",
                 e.to_string(),
                 "Failed to get working directory files"
             ));
@@ -137,41 +207,25 @@ pub fn merge(branch_name: &str) -> Result<(), ErrorData> {
 
     let mut dont_copy: HashSet<String> = HashSet::new();
 
+    // Datele din index evita citirea fisierelor nemodificate
+    let staged = IndexData::new(root)
+        .map_err(|e| error_data!("merge_branch", e.to_string(), "Failed to load index data"))?;
+
     for (path, info) in target_commit.iter() {
-        if working_dir_files.entries.contains_key(path) {
-            if working_dir_files.entries[path].timestamp == info.timestamp {
-                working_dir_files.entries.insert(path.clone(), info.clone());
-            } else if your_commit.contains_key(path)
-                && your_commit[path].timestamp == working_dir_files.entries[path].timestamp
+        if let Some(file) = working_dir_files.entries.get_mut(path) {
+            file.hash = staged.working_hash(root, path, file).map_err(|e| {
+                error_data!(
+                    "merge_branch",
+                    e.to_string(),
+                    "Failed to calculate file hash during merge"
+                )
+            })?;
+
+            // Indexul scris dupa merge pastreaza data fisierului nemodificat
+            if let Some(yours) = your_commit.get_mut(path)
+                && yours.hash == file.hash
             {
-                working_dir_files
-                    .entries
-                    .insert(path.clone(), your_commit[path].clone());
-            } else {
-                let file_hash = match calculate_hash(&format_path(&vec![&root.root, &path])) {
-                    Ok(h) => h,
-                    Err(e) => {
-                        return Err(error_data!(
-                            "merge",
-                            e.to_string(),
-                            "Failed to calculate file hash during merge"
-                        ));
-                    }
-                };
-
-                working_dir_files.entries.insert(
-                    path.clone(),
-                    FileInfo {
-                        hash: file_hash,
-                        timestamp: working_dir_files.entries[path].timestamp,
-                    },
-                );
-
-                if your_commit.contains_key(path)
-                    && your_commit[path].hash == working_dir_files.entries[path].hash
-                {
-                    your_commit.insert(path.clone(), working_dir_files.entries[path].clone());
-                }
+                yours.timestamp = file.timestamp;
             }
         }
 
@@ -180,28 +234,25 @@ pub fn merge(branch_name: &str) -> Result<(), ErrorData> {
                 && working_dir_files.entries[path].hash != info.hash
                 && working_dir_files.entries[path].hash != your_commit[path].hash
             {
-                println!("fatal: Uncommitted changes in file {}!", path);
-                return Ok(());
+                return Ok(MergeOutcome::UncommittedChanges(path.clone()));
             }
 
             let mut same_file = true;
 
             if base_commit.contains_key(path) {
-                if base_commit[path].hash != your_commit[path].hash
-                    && your_commit[path].hash != info.hash
-                    && base_commit[path].hash != info.hash
+                if !base_commit[path].same_version(&your_commit[path])
+                    && !your_commit[path].same_version(info)
+                    && !base_commit[path].same_version(info)
                 {
-                    println!("fatal: Conflict at file {}!", path);
-                    return Ok(());
-                } else if base_commit[path].hash == your_commit[path].hash
-                    && your_commit[path].hash != info.hash
+                    return Ok(MergeOutcome::Conflict(path.clone()));
+                } else if base_commit[path].same_version(&your_commit[path])
+                    && !your_commit[path].same_version(info)
                 {
                     same_file = false;
                     your_commit.insert(path.clone(), info.clone());
                 }
-            } else if your_commit[path].hash != info.hash {
-                println!("fatal: Conflict at file {}!", path);
-                return Ok(());
+            } else if !your_commit[path].same_version(info) {
+                return Ok(MergeOutcome::Conflict(path.clone()));
             }
 
             if same_file {
@@ -210,13 +261,11 @@ pub fn merge(branch_name: &str) -> Result<(), ErrorData> {
         } else if working_dir_files.entries.contains_key(path)
             && working_dir_files.entries[path].hash != info.hash
         {
-            println!("fatal: Uncommitted changes in file {}!", path);
-            return Ok(());
+            return Ok(MergeOutcome::UncommittedChanges(path.clone()));
         } else if !base_commit.contains_key(path) {
             your_commit.insert(path.clone(), info.clone());
-        } else if base_commit[path].hash != info.hash {
-            println!("fatal: Conflict at file {}!", path);
-            return Ok(());
+        } else if !base_commit[path].same_version(info) {
+            return Ok(MergeOutcome::Conflict(path.clone()));
         }
     }
 
@@ -225,9 +274,8 @@ pub fn merge(branch_name: &str) -> Result<(), ErrorData> {
     for (path, info) in your_commit.iter() {
         if !target_commit.contains_key(path) {
             if base_commit.contains_key(path) {
-                if base_commit[path].hash != info.hash {
-                    println!("fatal: Conflict at file {}!", path);
-                    return Ok(());
+                if !base_commit[path].same_version(info) {
+                    return Ok(MergeOutcome::Conflict(path.clone()));
                 } else {
                     to_delete.push(path.clone());
                 }
@@ -241,42 +289,50 @@ pub fn merge(branch_name: &str) -> Result<(), ErrorData> {
         your_commit.remove(path);
     }
 
+    let mut index_files: IndexData = IndexData::lock(root).map_err(|e| {
+        error_data!(
+            "merge_branch",
+            e.to_string(),
+            "Failed to lock index data during merge"
+        )
+    })?;
+
     for (path, info) in your_commit.iter() {
         if dont_copy.contains(path) {
             continue;
         }
 
-        let object_path = format_path(&vec![&root.root, ".my_svn", "objects", &info.hash]);
-        let dest_path = format_path(&vec![&root.root, &path]);
+        let dest_path = format_path(&vec![root, &path]);
 
         if let Some(parent) = Path::new(&dest_path).parent() {
             std::fs::create_dir_all(parent).map_err(|e| {
                 error_data!(
-                    "merge",
+                    "merge_branch",
                     e.to_string(),
                     "Failed to create parent directories during merge"
                 )
             })?;
         }
 
-        fs::copy(object_path, dest_path)
-            .map_err(|e| error_data!("merge", e.to_string(), "Failed to copy file during merge"))?;
+        restore_file(root, &info.hash, &dest_path, info.mode).map_err(|e| {
+            error_data!(
+                "merge_branch",
+                e.to_string(),
+                "Failed to copy file during merge"
+            )
+        })?;
     }
 
     for path in to_delete {
-        fs::remove_file(root.root.clone() + "\\" + &path).map_err(|e| {
-            error_data!("merge", e.to_string(), "Failed to remove file during merge")
+        fs::remove_file(format_path(&vec![root, &path])).map_err(|e| {
+            error_data!(
+                "merge_branch",
+                e.to_string(),
+                "Failed to remove file during merge"
+            )
         })?;
     }
 
-    let mut index_files: IndexData = IndexData::new().map_err(|e| {
-        error_data!(
-            "merge",
-            e.to_string(),
-            "Failed to load index data during merge"
-        )
-    })?;
-
     index_files.entries.clear();
 
     for (path, info) in your_commit.iter() {
@@ -285,14 +341,74 @@ pub fn merge(branch_name: &str) -> Result<(), ErrorData> {
 
     index_files.save_index().map_err(|e| {
         error_data!(
-            "merge",
+            "merge_branch",
             e.to_string(),
             "Failed to save index data during merge"
         )
     })?;
 
-    commit(format!("Merge branch {}", branch_name), &target_commit_hash)
-        .map_err(|e| error_data!("merge", e.to_string(), "Failed to create merge commit"))?;
+    if fast_forward {
+        ref_lock
+            .commit(target_commit_hash.as_bytes())
+            .map_err(|e| {
+                error_data!(
+                    "merge_branch",
+                    e.to_string(),
+                    "Failed to move branch reference"
+                )
+            })?;
 
-    Ok(())
+        append_head_reflog(
+            root,
+            &head,
+            &current_commit_hash,
+            &target_commit_hash,
+            &format!("merge {}: Fast-forward", branch_name),
+        )
+        .map_err(|e| error_data!("merge_branch", e.to_string(), "Failed to update reflog"))?;
+
+        return Ok(MergeOutcome::FastForward {
+            from: current_commit_hash,
+            to: target_commit_hash,
+        });
+    }
+
+    let is_branch = Path::new(&format_path(&vec![
+        root,
+        ".my_svn",
+        "refs",
+        "heads",
+        branch_name,
+    ]))
+    .is_file();
+    let message = if is_branch {
+        format!("Merge branch {}", branch_name)
+    } else {
+        format!("Merge {}", branch_name)
+    };
+
+    let outcome = create_commit_locked(
+        root,
+        message,
+        &target_commit_hash,
+        &CommitOptions::default(),
+        head,
+        ref_lock,
+    )
+    .map_err(|e| {
+        error_data!(
+            "merge_branch",
+            e.to_string(),
+            "Failed to create merge commit"
+        )
+    })?;
+
+    match outcome {
+        CommitOutcome::Created { hash, gc } => Ok(MergeOutcome::Merged { hash, gc }),
+        _ => Err(error_data!(
+            "merge_branch",
+            String::new(),
+            "Unexpected commit outcome"
+        )),
+    }
 }
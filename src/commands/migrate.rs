@@ -0,0 +1,507 @@
+use std::collections::HashMap;
+
+use crate::{
+    commands::{
+        branch::{Head, get_branch_hash, list_branches, read_head},
+        commit::{CommitObject, TreeData, TreeDataType, write_tree},
+        tag::{TagObject, get_tag_hash, list_tags},
+    },
+    error_data,
+    utils::{
+        error::ErrorData,
+        hash::hash_bytes,
+        json::load_object_json,
+        lock::write_locked,
+        object::{ObjectType, migrate_objects, read_typed_object, write_object},
+        path::{RepoLocationError::*, find_repo_root, format_path},
+        reflog::{append_head_reflog, append_reflog},
+    },
+};
+
+const MIGRATE_REFLOG_MESSAGE: &str = "migrate: rewrite trees in canonical form";
+
+// Corespondenta hash vechi -> hash nou, plus numarul obiectelor rescrise
+#[derive(Default)]
+struct Rewritten {
+    trees: HashMap<String, String>,
+    commits: HashMap<String, String>,
+    tree_count: usize,
+    commit_count: usize,
+}
+
+fn canonicalize_tree(
+    root: &str,
+    hash: &str,
+    rewritten: &mut Rewritten,
+) -> Result<String, ErrorData> {
+    if let Some(new_hash) = rewritten.trees.get(hash) {
+        return Ok(new_hash.clone());
+    }
+
+    let mut entries: Vec<TreeData> = load_object_json(root, hash).map_err(|e| {
+        error_data!(
+            "canonicalize_tree",
+            e.to_string(),
+            "Failed to load tree object"
+        )
+    })?;
+
+    for entry in entries.iter_mut() {
+        if entry.data_type == TreeDataType::Folder {
+            entry.hash = canonicalize_tree(root, &entry.hash, rewritten)?;
+        }
+    }
+
+    let new_hash = write_tree(root, entries).map_err(|e| {
+        error_data!(
+            "canonicalize_tree",
+            e.to_string(),
+            "Failed to write canonical tree"
+        )
+    })?;
+
+    if new_hash != hash {
+        rewritten.tree_count += 1;
+    }
+    rewritten.trees.insert(hash.to_string(), new_hash.clone());
+
+    Ok(new_hash)
+}
+
+// Parintii sunt rescrisi inaintea copiilor (parcurgere post-ordine fara
+// recursivitate, ca istoricele lungi sa nu umple stiva)
+fn canonicalize_commit(
+    root: &str,
+    tip: &str,
+    rewritten: &mut Rewritten,
+) -> Result<String, ErrorData> {
+    let mut stack: Vec<(String, bool)> = vec![(tip.to_string(), false)];
+
+    while let Some((hash, parents_done)) = stack.pop() {
+        if rewritten.commits.contains_key(&hash) {
+            continue;
+        }
+
+        let mut commit: CommitObject = load_object_json(root, &hash).map_err(|e| {
+            error_data!(
+                "canonicalize_commit",
+                e.to_string(),
+                "Failed to load commit object"
+            )
+        })?;
+
+        if !parents_done {
+            stack.push((hash, true));
+            for parent in commit.parents() {
+                if !rewritten.commits.contains_key(parent) {
+                    stack.push((parent.clone(), false));
+                }
+            }
+            continue;
+        }
+
+        let tree = canonicalize_tree(root, commit.tree(), rewritten)?;
+        let parents: Vec<String> = commit
+            .parents()
+            .iter()
+            .map(|p| rewritten.commits[p].clone())
+            .collect();
+
+        // Commit-urile neschimbate isi pastreaza octetii (si hash-ul) originali
+        if tree == commit.tree() && parents == commit.parents() {
+            rewritten.commits.insert(hash.clone(), hash);
+            continue;
+        }
+
+        commit.tree = tree;
+        commit.parent = if parents.is_empty() {
+            None
+        } else {
+            Some(parents)
+        };
+
+        let json_string = serde_json::to_string(&commit).map_err(|e| {
+            error_data!(
+                "canonicalize_commit",
+                e.to_string(),
+                "Failed to serialize commit object to JSON"
+            )
+        })?;
+
+        let new_hash = hash_bytes(root, json_string.as_bytes()).map_err(|e| {
+            error_data!(
+                "canonicalize_commit",
+                e.to_string(),
+                "Failed to hash commit object"
+            )
+        })?;
+
+        write_object(root, &new_hash, ObjectType::Commit, json_string.as_bytes()).map_err(|e| {
+            error_data!(
+                "canonicalize_commit",
+                e.to_string(),
+                "Failed to write commit object"
+            )
+        })?;
+
+        rewritten.commit_count += 1;
+        rewritten.commits.insert(hash, new_hash);
+    }
+
+    Ok(rewritten.commits[tip].clone())
+}
+
+// Rescrie arborii din istoricul tuturor ramurilor si tag-urilor in forma canonica
+// (intrari sortate, fara data modificarii fisierelor); commit-urile afectate primesc hash-uri noi, iar referintele sunt mutate
+fn canonicalize_history(root: &str) -> Result<(usize, usize), ErrorData> {
+    let mut rewritten = Rewritten::default();
+    let head = read_head(root)
+        .map_err(|e| error_data!("canonicalize_history", e.to_string(), "Failed to read HEAD"))?;
+
+    for branch in list_branches(root).map_err(|e| {
+        error_data!(
+            "canonicalize_history",
+            e.to_string(),
+            "Failed to list branches"
+        )
+    })? {
+        let tip = get_branch_hash(root, &branch).map_err(|e| {
+            error_data!(
+                "canonicalize_history",
+                e.to_string(),
+                "Failed to get branch hash"
+            )
+        })?;
+
+        if tip.is_empty() {
+            continue;
+        }
+
+        let new_tip = canonicalize_commit(root, &tip, &mut rewritten)?;
+
+        if new_tip != tip {
+            write_locked(
+                &format_path(&vec![root, ".my_svn", "refs", "heads", &branch]),
+                new_tip.as_bytes(),
+            )
+            .map_err(|e| {
+                error_data!(
+                    "canonicalize_history",
+                    e.to_string(),
+                    "Failed to update branch"
+                )
+            })?;
+
+            // HEAD se muta odata cu ramura curenta
+            let logged = if head == Head::Branch(branch.clone()) {
+                append_head_reflog(root, &head, &tip, &new_tip, MIGRATE_REFLOG_MESSAGE)
+            } else {
+                let ref_name = format!("refs/heads/{}", branch);
+                append_reflog(root, &ref_name, &tip, &new_tip, MIGRATE_REFLOG_MESSAGE)
+            };
+
+            logged.map_err(|e| {
+                error_data!(
+                    "canonicalize_history",
+                    e.to_string(),
+                    "Failed to update reflog"
+                )
+            })?;
+        }
+    }
+
+    // Un HEAD detasat indica direct un commit, care este rescris la fel
+    if let Head::Detached(tip) = &head {
+        let new_tip = canonicalize_commit(root, tip, &mut rewritten)?;
+
+        if new_tip != *tip {
+            write_locked(
+                &format_path(&vec![root, ".my_svn", "HEAD"]),
+                new_tip.as_bytes(),
+            )
+            .map_err(|e| {
+                error_data!(
+                    "canonicalize_history",
+                    e.to_string(),
+                    "Failed to update HEAD"
+                )
+            })?;
+
+            append_head_reflog(root, &head, tip, &new_tip, MIGRATE_REFLOG_MESSAGE).map_err(
+                |e| {
+                    error_data!(
+                        "canonicalize_history",
+                        e.to_string(),
+                        "Failed to update reflog"
+                    )
+                },
+            )?;
+        }
+    }
+
+    // Tag-urile adnotate sunt rescrise si ele, cu acelasi tagger si mesaj
+    for tag in list_tags(root)
+        .map_err(|e| error_data!("canonicalize_history", e.to_string(), "Failed to list tags"))?
+    {
+        let hash = match get_tag_hash(root, &tag).map_err(|e| {
+            error_data!(
+                "canonicalize_history",
+                e.to_string(),
+                "Failed to get tag hash"
+            )
+        })? {
+            Some(hash) => hash,
+            None => continue,
+        };
+
+        let new_hash = canonicalize_tag(root, &hash, &mut rewritten)?;
+
+        if new_hash != hash {
+            write_locked(
+                &format_path(&vec![root, ".my_svn", "refs", "tags", &tag]),
+                new_hash.as_bytes(),
+            )
+            .map_err(|e| {
+                error_data!(
+                    "canonicalize_history",
+                    e.to_string(),
+                    "Failed to update tag"
+                )
+            })?;
+
+            append_reflog(
+                root,
+                &format!("refs/tags/{}", tag),
+                &hash,
+                &new_hash,
+                MIGRATE_REFLOG_MESSAGE,
+            )
+            .map_err(|e| {
+                error_data!(
+                    "canonicalize_history",
+                    e.to_string(),
+                    "Failed to update reflog"
+                )
+            })?;
+        }
+    }
+
+    Ok((rewritten.tree_count, rewritten.commit_count))
+}
+
+fn canonicalize_tag(
+    root: &str,
+    hash: &str,
+    rewritten: &mut Rewritten,
+) -> Result<String, ErrorData> {
+    let (object_type, data) = read_typed_object(root, hash).map_err(|e| {
+        error_data!(
+            "canonicalize_tag",
+            e.to_string(),
+            "Failed to read tag target"
+        )
+    })?;
+
+    if object_type != ObjectType::Tag {
+        return canonicalize_commit(root, hash, rewritten);
+    }
+
+    let mut tag: TagObject = serde_json::from_slice(&data).map_err(|e| {
+        error_data!(
+            "canonicalize_tag",
+            e.to_string(),
+            "Failed to parse tag object"
+        )
+    })?;
+
+    let new_object = canonicalize_commit(root, &tag.object, rewritten)?;
+    if new_object == tag.object {
+        return Ok(hash.to_string());
+    }
+    tag.object = new_object;
+
+    let json_string = serde_json::to_string(&tag).map_err(|e| {
+        error_data!(
+            "canonicalize_tag",
+            e.to_string(),
+            "Failed to serialize tag object"
+        )
+    })?;
+
+    let new_hash = hash_bytes(root, json_string.as_bytes()).map_err(|e| {
+        error_data!(
+            "canonicalize_tag",
+            e.to_string(),
+            "Failed to hash tag object"
+        )
+    })?;
+
+    write_object(root, &new_hash, ObjectType::Tag, json_string.as_bytes()).map_err(|e| {
+        error_data!(
+            "canonicalize_tag",
+            e.to_string(),
+            "Failed to write tag object"
+        )
+    })?;
+
+    Ok(new_hash)
+}
+
+pub fn migrate() -> Result<(), ErrorData> {
+    let root = match find_repo_root(&"./".to_string()) {
+        Ok(rep_loc) => rep_loc,
+        Err(e) => match e {
+            ErrorData(ed) => {
+                return Err(error_data!(
+                    "migrate",
+                    ed.to_string(),
+                    "Failed to find repository root"
+                ));
+            }
+            RepositoryNotFoundError => {
+                println!("fatal: not a svn repository (or any of the parent directories): .my_svn");
+                return Ok(());
+            }
+        },
+    };
+
+    let migrated = migrate_objects(&root.root)
+        .map_err(|e| error_data!("migrate", e.to_string(), "Failed to migrate objects"))?;
+
+    let (trees, commits) = canonicalize_history(&root.root).map_err(|e| {
+        error_data!(
+            "migrate",
+            e.to_string(),
+            "Failed to rewrite trees in canonical form"
+        )
+    })?;
+
+    if migrated == 0 && trees == 0 {
+        println!("All objects are already up to date.");
+    }
+    if migrated > 0 {
+        println!("Migrated {} object(s).", migrated);
+    }
+    if trees > 0 {
+        println!(
+            "Rewrote {} tree(s) in canonical form and {} commit(s) referring to them.",
+            trees, commits
+        );
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{commands::fsck::is_canonical_tree, utils::testing::TempRepo};
+
+    fn write_raw_tree(repo: &TempRepo, json: &str) -> String {
+        let hash = hash_bytes(&repo.root, json.as_bytes()).unwrap();
+        write_object(&repo.root, &hash, ObjectType::Tree, json.as_bytes()).unwrap();
+        hash
+    }
+
+    #[test]
+    fn trees_with_timestamps_are_rewritten() {
+        let repo = TempRepo::new();
+        let blob = hash_bytes(&repo.root, b"content\n").unwrap();
+        write_object(&repo.root, &blob, ObjectType::Blob, b"content\n").unwrap();
+
+        // Formatul vechi: data modificarii in fiecare intrare, `null` la directoare
+        let old_sub = write_raw_tree(
+            &repo,
+            &format!(
+                r#"[{{"data_type":"File","name":"a","hash":"{}","timestamp":1700000000}}]"#,
+                blob
+            ),
+        );
+        let old_root = write_raw_tree(
+            &repo,
+            &format!(
+                r#"[{{"data_type":"Folder","name":"dir","hash":"{}","timestamp":null}}]"#,
+                old_sub
+            ),
+        );
+
+        let mut rewritten = Rewritten::default();
+        let new_root = canonicalize_tree(&repo.root, &old_root, &mut rewritten).unwrap();
+        assert_eq!(rewritten.tree_count, 2);
+
+        let new_sub = rewritten.trees[&old_sub].clone();
+        assert_eq!(
+            new_sub,
+            write_tree(
+                &repo.root,
+                vec![TreeData {
+                    data_type: TreeDataType::File,
+                    name: String::from("a"),
+                    hash: blob,
+                    mode: Default::default(),
+                }],
+            )
+            .unwrap()
+        );
+
+        for hash in [&new_root, &new_sub] {
+            let (_, data) = read_typed_object(&repo.root, hash).unwrap();
+            assert!(is_canonical_tree(&data));
+        }
+
+        // A doua trecere nu mai are ce rescrie
+        let mut again = Rewritten::default();
+        assert_eq!(
+            canonicalize_tree(&repo.root, &new_root, &mut again).unwrap(),
+            new_root
+        );
+        assert_eq!(again.tree_count, 0);
+    }
+
+    #[test]
+    fn detached_head_follows_rewritten_commit() {
+        let repo = TempRepo::new();
+        let blob = hash_bytes(&repo.root, b"content\n").unwrap();
+        write_object(&repo.root, &blob, ObjectType::Blob, b"content\n").unwrap();
+
+        let old_tree = write_raw_tree(
+            &repo,
+            &format!(
+                r#"[{{"data_type":"File","name":"a","hash":"{}","timestamp":1700000000}}]"#,
+                blob
+            ),
+        );
+        let commit = serde_json::to_string(&CommitObject {
+            tree: old_tree,
+            parent: None,
+            message: String::from("old"),
+            timestamp: 1700000000,
+            author: None,
+            committer: None,
+        })
+        .unwrap();
+        let old_commit = hash_bytes(&repo.root, commit.as_bytes()).unwrap();
+        write_object(
+            &repo.root,
+            &old_commit,
+            ObjectType::Commit,
+            commit.as_bytes(),
+        )
+        .unwrap();
+
+        // Niciun branch nu indica acest commit, doar HEAD
+        repo.write(".my_svn/HEAD", &old_commit);
+
+        assert_eq!(canonicalize_history(&repo.root).unwrap(), (1, 1));
+
+        let new_commit = match read_head(&repo.root).unwrap() {
+            Head::Detached(hash) => hash,
+            head => panic!("HEAD should stay detached, got {:?}", head),
+        };
+        assert_ne!(new_commit, old_commit);
+
+        let entries = crate::utils::reflog::read_reflog(&repo.root, "HEAD").unwrap();
+        assert_eq!(entries[0].old, old_commit);
+        assert_eq!(entries[0].new, new_commit);
+    }
+}
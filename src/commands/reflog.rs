@@ -0,0 +1,60 @@
+use colored::Colorize;
+
+use crate::{
+    error_data,
+    repository::Repository,
+    utils::{
+        error::ErrorData,
+        path::{RepoLocationError::*, find_repo_root},
+    },
+};
+
+// Fara argument se afiseaza jurnalul lui HEAD, cea mai noua intrare prima
+pub fn reflog(target: Option<String>) -> Result<(), ErrorData> {
+    let root = match find_repo_root(&"./".to_string()) {
+        Ok(rep_loc) => rep_loc,
+        Err(e) => match e {
+            ErrorData(ed) => {
+                return Err(error_data!(
+                    "reflog",
+                    ed.to_string(),
+                    "Failed to find repository root"
+                ));
+            }
+            RepositoryNotFoundError => {
+                println!("fatal: not a svn repository (or any of the parent directories): .my_svn");
+                return Ok(());
+            }
+        },
+    };
+
+    let name = target.unwrap_or_else(|| String::from("HEAD"));
+
+    let entries = match Repository::from_location(root)
+        .reflog(&name)
+        .map_err(|e| error_data!("reflog", e.to_string(), "Failed to read reflog"))?
+    {
+        Some(entries) => entries,
+        None => {
+            println!("fatal: no reflog for '{}'", name);
+            return Ok(());
+        }
+    };
+
+    for (index, entry) in entries.iter().enumerate() {
+        let hash = match entry.new.is_empty() {
+            true => String::from("0000000"),
+            false => entry.new[..7.min(entry.new.len())].to_string(),
+        };
+
+        println!(
+            "{} {}@{{{}}}: {}",
+            hash.yellow(),
+            name,
+            index,
+            entry.message
+        );
+    }
+
+    Ok(())
+}
@@ -0,0 +1,41 @@
+use crate::{
+    error_data,
+    utils::{
+        error::ErrorData,
+        pack::repack as repack_objects,
+        path::{RepoLocationError::*, find_repo_root},
+    },
+};
+
+pub fn repack() -> Result<(), ErrorData> {
+    let root = match find_repo_root(&"./".to_string()) {
+        Ok(rep_loc) => rep_loc,
+        Err(e) => match e {
+            ErrorData(ed) => {
+                return Err(error_data!(
+                    "repack",
+                    ed.to_string(),
+                    "Failed to find repository root"
+                ));
+            }
+            RepositoryNotFoundError => {
+                println!("fatal: not a svn repository (or any of the parent directories): .my_svn");
+                return Ok(());
+            }
+        },
+    };
+
+    let stats = repack_objects(&root.root)
+        .map_err(|e| error_data!("repack", e.to_string(), "Failed to pack loose objects"))?;
+
+    if stats.objects == 0 {
+        println!("Nothing to pack.");
+    } else {
+        println!(
+            "Packed {} object(s), {} stored as deltas.",
+            stats.objects, stats.deltas
+        );
+    }
+
+    Ok(())
+}
@@ -1,94 +1,91 @@
 use colored::Colorize;
-use std::{collections::HashMap};
+use std::collections::HashMap;
 
 use crate::{
-    commands::{branch::get_current_branch, commit::read_commit},
+    commands::{
+        branch::{Head, head_commit, read_head},
+        commit::read_commit_from_hash,
+    },
     error_data,
+    repository::Repository,
     utils::{
         error::ErrorData,
         hash::calculate_hash,
         index::{IndexData, get_svn_ignore, ignore_file},
         path::{
-            FileInfo, RepoLocation, RepoLocationError::*, find_repo_root, format_path,
-            get_working_directory_optimized, relative_to_root,
+            FileInfo, RepoLocationError::*, find_repo_root, format_path,
+            get_working_directory_optimized, relative_to_root, same_mode,
         },
     },
 };
 
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum FileStatus {
+    New,
+    Modified,
+    Deleted,
+    ModeChanged,
+}
+
+impl FileStatus {
+    pub fn label(&self) -> &'static str {
+        match self {
+            FileStatus::New => "new file:   ",
+            FileStatus::Modified => "modified:   ",
+            FileStatus::Deleted => "deleted:    ",
+            FileStatus::ModeChanged => "mode change: ",
+        }
+    }
+}
+
+pub type FileChanges = Vec<(String, FileStatus)>;
+
+// Caile sunt relative la radacina depozitului si sortate
+pub struct StatusReport {
+    pub head: Head,
+    pub staged: FileChanges,
+    pub unstaged: FileChanges,
+    pub untracked: Vec<String>,
+}
+
+impl StatusReport {
+    pub fn is_clean(&self) -> bool {
+        self.staged.is_empty() && self.unstaged.is_empty() && self.untracked.is_empty()
+    }
+}
+
 fn compare_last_commit_with_index(
     index_files: &HashMap<String, FileInfo>,
     commit_data: &mut HashMap<String, FileInfo>,
-    root: &RepoLocation,
-) -> bool {
-    let mut first = false;
+) -> FileChanges {
+    let mut changes: FileChanges = Vec::new();
 
     for i in index_files {
         if let Some(commit_hash) = commit_data.get(i.0) {
             if i.1.hash != commit_hash.hash {
-                if !first {
-                    println!("Changes to be committed:");
-                    first = true;
-                }
-                println!(
-                    "{}",
-                    format!(
-                        "        modified:   {}",
-                        relative_to_root(
-                            &format_path(&vec![&root.root, &root.relative]),
-                            &format_path(&vec![&root.root, &i.0])
-                        )
-                    )
-                    .green()
-                );
+                changes.push((i.0.clone(), FileStatus::Modified));
+            } else if i.1.mode != commit_hash.mode {
+                changes.push((i.0.clone(), FileStatus::ModeChanged));
             }
             commit_data.remove(i.0);
         } else {
-            if !first {
-                println!("Changes to be committed:");
-                first = true;
-            }
-            println!(
-                "{}",
-                format!(
-                    "        new file:   {}",
-                    relative_to_root(
-                        &format_path(&vec![&root.root, &root.relative]),
-                        &format_path(&vec![&root.root, &i.0])
-                    )
-                )
-                .green()
-            );
+            changes.push((i.0.clone(), FileStatus::New));
         }
     }
 
     for i in commit_data {
-        if !first {
-            println!("Changes to be committed:");
-            first = true;
-        }
-        println!(
-            "{}",
-            format!(
-                "        deleted:    {}",
-                relative_to_root(
-                    &format_path(&vec![&root.root, &root.relative]),
-                    &format_path(&vec![&root.root, &i.0])
-                )
-            )
-            .green()
-        );
+        changes.push((i.0.clone(), FileStatus::Deleted));
     }
 
-    first
+    changes.sort_by(|a, b| a.0.cmp(&b.0));
+    changes
 }
 
 fn compare_index_with_working_directory(
     index_files: &mut HashMap<String, FileInfo>,
-    root: &RepoLocation,
-) -> Result<bool, ErrorData> {
-    let mut changes = false;
-
-    let working_directory = match get_working_directory_optimized(&root.root) {
+    root: &str,
+) -> Result<(FileChanges, Vec<String>), ErrorData> {
+    let working_directory = match get_working_directory_optimized(root) {
         Ok(f) => f,
         Err(e) => {
             return Err(error_data!(
@@ -99,24 +96,27 @@ fn compare_index_with_working_directory(
         }
     };
 
-    let ignore_rules = get_svn_ignore(&format_path(&vec![&root.root, ".svnignore"]));
+    let ignore_rules = get_svn_ignore(&format_path(&vec![root, ".svnignore"]));
 
     let mut untracked_files: Vec<String> = Vec::new();
-    let mut modified_files: Vec<String> = Vec::new();
+    let mut changes: FileChanges = Vec::new();
 
     for (path, info) in working_directory.entries {
         if ignore_file(&path, &ignore_rules) {
             continue;
         }
 
-        if index_files.contains_key(&path) {
-            if index_files[&path].timestamp != info.timestamp
-                && index_files[&path].hash
-                    != calculate_hash(&format_path(&vec![&root.root, &path])).map_err(|e| {
+        if let Some(staged) = index_files.get(&path) {
+            let modified = staged.timestamp != info.timestamp
+                && staged.hash
+                    != calculate_hash(root, &format_path(&vec![root, &path])).map_err(|e| {
                         error_data!("status", e.to_string(), "Failed to calculate file hash")
-                    })?
-            {
-                modified_files.push(path.to_owned());
+                    })?;
+
+            if modified {
+                changes.push((path.to_owned(), FileStatus::Modified));
+            } else if !same_mode(staged.mode, info.mode) {
+                changes.push((path.to_owned(), FileStatus::ModeChanged));
             }
 
             index_files.remove(&path);
@@ -125,55 +125,64 @@ fn compare_index_with_working_directory(
         }
     }
 
-    if !modified_files.is_empty() || !index_files.is_empty() {
-        changes = true;
-        println!("Changes not staged for commit:");
+    for i in index_files.keys() {
+        changes.push((i.clone(), FileStatus::Deleted));
     }
 
-    for i in modified_files {
-        println!(
-            "{}",
-            format!(
-                "        modified:   {}",
-                relative_to_root(
-                    &format_path(&vec![&root.root, &root.relative]),
-                    &format_path(&vec![&root.root, &i])
-                )
-            )
-            .red()
-        );
-    }
+    changes.sort_by(|a, b| a.0.cmp(&b.0));
+    untracked_files.sort();
 
-    for i in index_files {
-        println!(
-            "{}",
-            format!(
-                "        deleted:    {}",
-                relative_to_root(
-                    &format_path(&vec![&root.root, &root.relative]),
-                    &format_path(&vec![&root.root, &i.0])
-                )
-            )
-            .red()
-        );
-    }
+    Ok((changes, untracked_files))
+}
 
-    if !untracked_files.is_empty() {
-        changes = true;
-        println!("Untracked files:");
-    }
-    for i in untracked_files {
-        println!(
-            "        {}",
-            relative_to_root(
-                &format_path(&vec![&root.root, &root.relative]),
-                &format_path(&vec![&root.root, &i])
-            )
-            .red()
-        );
-    }
+pub fn collect_status(root: &str) -> Result<StatusReport, ErrorData> {
+    let mut index_files = match IndexData::new(root) {
+        Ok(data) => data,
+        Err(e) => {
+            return Err(error_data!(
+                "collect_status",
+                e.to_string(),
+                "Failed to load index data"
+            ));
+        }
+    };
+
+    let head = read_head(root)
+        .map_err(|e| error_data!("collect_status", e.to_string(), "Failed to read HEAD"))?;
+
+    let head_hash = head_commit(root)
+        .map_err(|e| error_data!("collect_status", e.to_string(), "Failed to get HEAD commit"))?;
+
+    let mut commit_data = match read_commit_from_hash(root, &head_hash) {
+        Ok(cd) => cd,
+        Err(e) => {
+            return Err(error_data!(
+                "collect_status",
+                e.to_string(),
+                "Failed to read last commit data"
+            ));
+        }
+    };
 
-    Ok(changes)
+    let staged = compare_last_commit_with_index(&index_files.entries, &mut commit_data);
+    let (unstaged, untracked) =
+        match compare_index_with_working_directory(&mut index_files.entries, root) {
+            Ok(c) => c,
+            Err(e) => {
+                return Err(error_data!(
+                    "collect_status",
+                    e.to_string(),
+                    "Failed to compare index with working directory"
+                ));
+            }
+        };
+
+    Ok(StatusReport {
+        head,
+        staged,
+        unstaged,
+        untracked,
+    })
 }
 
 pub fn status() -> Result<(), ErrorData> {
@@ -194,52 +203,55 @@ pub fn status() -> Result<(), ErrorData> {
         },
     };
 
-    let mut index_files = match IndexData::new() {
-        Ok(data) => data,
-        Err(e) => {
-            return Err(error_data!(
-                "status",
-                e.to_string(),
-                "Failed to load index data"
-            ));
-        }
-    };
+    let repo = Repository::from_location(root);
+    let report = repo
+        .status()
+        .map_err(|e| error_data!("status", e.to_string(), "Failed to collect status"))?;
 
-    let branch = match get_current_branch(&root.root) {
-        Ok(b) => b,
-        Err(e) => {
-            return Err(error_data!(
-                "status",
-                e.to_string(),
-                "Failed to get current branch"
-            ));
-        }
+    // Caile sunt afisate relativ la directorul curent
+    let location = repo.location();
+    let display = |path: &String| {
+        relative_to_root(
+            &format_path(&vec![&location.root, &location.relative]),
+            &format_path(&vec![&location.root, path]),
+        )
     };
 
-    let mut commit_data = match read_commit(&root.root, &branch) {
-        Ok(cd) => cd,
-        Err(e) => {
-            return Err(error_data!(
-                "status",
-                e.to_string(),
-                "Failed to read last commit data"
-            ));
-        }
-    };
+    if let Head::Detached(hash) = &report.head {
+        println!(
+            "{}",
+            format!("HEAD detached at {}", &hash[..7.min(hash.len())]).red()
+        );
+    }
 
-    let mut changes = compare_last_commit_with_index(&index_files.entries, &mut commit_data, &root);
-    changes |= match compare_index_with_working_directory(&mut index_files.entries, &root) {
-        Ok(c) => c,
-        Err(e) => {
-            return Err(error_data!(
-                "status",
-                e.to_string(),
-                "Failed to compare index with working directory"
-            ));
-        }
-    };
+    if !report.staged.is_empty() {
+        println!("Changes to be committed:");
+    }
+    for (path, file_status) in &report.staged {
+        println!(
+            "{}",
+            format!("        {}{}", file_status.label(), display(path)).green()
+        );
+    }
+
+    if !report.unstaged.is_empty() {
+        println!("Changes not staged for commit:");
+    }
+    for (path, file_status) in &report.unstaged {
+        println!(
+            "{}",
+            format!("        {}{}", file_status.label(), display(path)).red()
+        );
+    }
+
+    if !report.untracked.is_empty() {
+        println!("Untracked files:");
+    }
+    for path in &report.untracked {
+        println!("        {}", display(path).red());
+    }
 
-    if !changes {
+    if report.is_clean() {
         println!("Nothing to commit, working tree clean");
     }
 
@@ -0,0 +1,432 @@
+use serde::{Deserialize, Serialize};
+use std::{
+    fs,
+    path::Path,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use crate::{
+    commands::{
+        branch::head_commit,
+        commit::{cleanup_message, launch_editor},
+    },
+    error_data,
+    utils::{
+        error::ErrorData,
+        hash::{check_object_name, hash_bytes},
+        identity::{IdentityRole, Signature, resolve_identity},
+        lock::LockFile,
+        object::{ObjectType, read_typed_object, write_object},
+        path::{RepoLocationError::*, find_repo_root, format_path},
+        reflog::{append_reflog, delete_reflog},
+        revision::{RevisionError, resolve_commit, valid_ref_name},
+    },
+};
+
+// Obiectul unui tag adnotat; un tag simplu este doar un fisier in
+// `refs/tags` care contine hash-ul commit-ului
+#[derive(Serialize, Deserialize)]
+pub struct TagObject {
+    pub object: String,
+    #[serde(rename = "type")]
+    pub object_type: String,
+    pub tag: String,
+    pub tagger: Signature,
+    pub message: String,
+}
+
+#[derive(PartialEq)]
+pub enum TagAction {
+    List,
+    // `annotate` cu mesajul gol deschide editorul; `-m` implica `-a`
+    Create {
+        name: String,
+        target: Option<String>,
+        annotate: bool,
+        paragraphs: Vec<String>,
+    },
+    Delete(String),
+}
+
+pub enum CreateTagOutcome {
+    // Hash-ul scris in `refs/tags/<name>` (commit-ul sau obiectul tag)
+    Created(String),
+    AlreadyExists,
+    InvalidName,
+    InvalidTarget(RevisionError),
+    // Ramura curenta nu are inca niciun commit
+    NoCommits,
+}
+
+pub enum DeleteTagOutcome {
+    Deleted(String),
+    NotFound,
+}
+
+const TAG_TEMPLATE: &str = "
+#
+# Write a message for tag:
+#   {}
+# Lines starting with '#' will be ignored.
+";
+
+fn tag_path(root: &str, name: &str) -> String {
+    format_path(&vec![root, ".my_svn", "refs", "tags", name])
+}
+
+pub fn list_tags(root: &str) -> Result<Vec<String>, ErrorData> {
+    let tags_path = format_path(&vec![root, ".my_svn", "refs", "tags"]);
+
+    // Depozitele create inainte de tag-uri nu au directorul
+    if !Path::new(&tags_path).is_dir() {
+        return Ok(Vec::new());
+    }
+
+    let entries = fs::read_dir(&tags_path).map_err(|e| {
+        error_data!(
+            "list_tags",
+            e.to_string(),
+            "Failed to read refs/tags directory"
+        )
+    })?;
+
+    let mut tags: Vec<String> = Vec::new();
+    for entry in entries {
+        let entry = entry.map_err(|e| {
+            error_data!("list_tags", e.to_string(), "Failed to read refs/tags entry")
+        })?;
+
+        let name = entry.file_name().to_string_lossy().to_string();
+        if entry.path().is_file() && !name.ends_with(".lock") {
+            tags.push(name);
+        }
+    }
+
+    tags.sort();
+    Ok(tags)
+}
+
+// Continutul lui `refs/tags/<name>`, `None` daca tag-ul nu exista
+pub fn get_tag_hash(root: &str, name: &str) -> Result<Option<String>, ErrorData> {
+    let path = tag_path(root, name);
+
+    if !valid_ref_name(name) || !Path::new(&path).is_file() {
+        return Ok(None);
+    }
+
+    let hash = fs::read_to_string(&path)
+        .map_err(|e| error_data!("get_tag_hash", e.to_string(), "Failed to read tag file"))?
+        .trim()
+        .to_string();
+
+    check_object_name(root, &hash).map_err(|e| {
+        error_data!(
+            "get_tag_hash",
+            e.to_string(),
+            "Tag points to an object of another format"
+        )
+    })?;
+
+    Ok(Some(hash))
+}
+
+// Urmeaza tag-urile adnotate pana la commit-ul la care indica
+pub fn peel_to_commit(root: &str, hash: &str) -> Result<String, ErrorData> {
+    let mut hash = hash.to_string();
+
+    loop {
+        let (object_type, data) = read_typed_object(root, &hash)
+            .map_err(|e| error_data!("peel_to_commit", e.to_string(), "Failed to read object"))?;
+
+        match object_type {
+            ObjectType::Commit => return Ok(hash),
+            ObjectType::Tag => {
+                let tag: TagObject = serde_json::from_slice(&data).map_err(|e| {
+                    error_data!(
+                        "peel_to_commit",
+                        e.to_string(),
+                        "Failed to parse tag object"
+                    )
+                })?;
+                hash = tag.object;
+            }
+            other => {
+                return Err(error_data!(
+                    "peel_to_commit",
+                    format!("Object {} is a {}", hash, other.as_str()),
+                    "Tag does not point to a commit"
+                ));
+            }
+        }
+    }
+}
+
+// Cu `message` se creeaza un tag adnotat (obiect `tag` in baza de obiecte)
+pub fn create_tag_ref(
+    root: &str,
+    name: &str,
+    target: Option<&str>,
+    message: Option<&str>,
+) -> Result<CreateTagOutcome, ErrorData> {
+    if !valid_ref_name(name) {
+        return Ok(CreateTagOutcome::InvalidName);
+    }
+
+    let path = tag_path(root, name);
+
+    let commit_hash = match target {
+        Some(target) => match resolve_commit(root, target)
+            .map_err(|e| error_data!("create_tag_ref", e.to_string(), "Failed to resolve target"))?
+        {
+            Ok(hash) => hash,
+            Err(e) => return Ok(CreateTagOutcome::InvalidTarget(e)),
+        },
+        None => {
+            let hash = head_commit(root).map_err(|e| {
+                error_data!("create_tag_ref", e.to_string(), "Failed to get HEAD commit")
+            })?;
+
+            if hash.is_empty() {
+                return Ok(CreateTagOutcome::NoCommits);
+            }
+            hash
+        }
+    };
+
+    fs::create_dir_all(format_path(&vec![root, ".my_svn", "refs", "tags"])).map_err(|e| {
+        error_data!(
+            "create_tag_ref",
+            e.to_string(),
+            "Failed to create refs/tags directory"
+        )
+    })?;
+
+    let lock = LockFile::acquire(&path).map_err(|e| {
+        error_data!(
+            "create_tag_ref",
+            e.to_string(),
+            "Failed to lock tag reference"
+        )
+    })?;
+
+    // Existenta se verifica sub blocare, ca un tag creat intre timp sa nu fie suprascris
+    if Path::new(&path).exists() {
+        return Ok(CreateTagOutcome::AlreadyExists);
+    }
+
+    let hash = match message {
+        None => commit_hash,
+        Some(message) => {
+            let now = SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map_err(|e| {
+                    error_data!(
+                        "create_tag_ref",
+                        e.to_string(),
+                        "Failed to get current time"
+                    )
+                })?
+                .as_secs();
+
+            let tagger = resolve_identity(root, IdentityRole::Committer, now).map_err(|e| {
+                error_data!("create_tag_ref", e.to_string(), "Failed to get tagger")
+            })?;
+
+            let tag = TagObject {
+                object: commit_hash,
+                object_type: String::from(ObjectType::Commit.as_str()),
+                tag: name.to_string(),
+                tagger,
+                message: message.to_string(),
+            };
+
+            let json_string = serde_json::to_string(&tag).map_err(|e| {
+                error_data!(
+                    "create_tag_ref",
+                    e.to_string(),
+                    "Failed to serialize tag object to JSON"
+                )
+            })?;
+
+            let hash = hash_bytes(root, json_string.as_bytes()).map_err(|e| {
+                error_data!("create_tag_ref", e.to_string(), "Failed to hash tag object")
+            })?;
+
+            write_object(root, &hash, ObjectType::Tag, json_string.as_bytes()).map_err(|e| {
+                error_data!(
+                    "create_tag_ref",
+                    e.to_string(),
+                    "Failed to write tag object"
+                )
+            })?;
+
+            hash
+        }
+    };
+
+    lock.commit(hash.as_bytes()).map_err(|e| {
+        error_data!(
+            "create_tag_ref",
+            e.to_string(),
+            "Failed to write tag reference"
+        )
+    })?;
+
+    append_reflog(
+        root,
+        &format!("refs/tags/{}", name),
+        "",
+        &hash,
+        "tag: created",
+    )
+    .map_err(|e| error_data!("create_tag_ref", e.to_string(), "Failed to update reflog"))?;
+
+    Ok(CreateTagOutcome::Created(hash))
+}
+
+pub fn delete_tag_ref(root: &str, name: &str) -> Result<DeleteTagOutcome, ErrorData> {
+    let hash = match get_tag_hash(root, name)
+        .map_err(|e| error_data!("delete_tag_ref", e.to_string(), "Failed to read tag"))?
+    {
+        Some(hash) => hash,
+        None => return Ok(DeleteTagOutcome::NotFound),
+    };
+
+    let path = tag_path(root, name);
+    let lock = LockFile::acquire(&path).map_err(|e| {
+        error_data!(
+            "delete_tag_ref",
+            e.to_string(),
+            "Failed to lock tag reference"
+        )
+    })?;
+
+    fs::remove_file(&path)
+        .map_err(|e| error_data!("delete_tag_ref", e.to_string(), "Failed to remove tag file"))?;
+    drop(lock);
+
+    delete_reflog(root, &format!("refs/tags/{}", name)).map_err(|e| {
+        error_data!(
+            "delete_tag_ref",
+            e.to_string(),
+            "Failed to remove tag reflog"
+        )
+    })?;
+
+    Ok(DeleteTagOutcome::Deleted(hash))
+}
+
+fn edit_tag_message(root: &str, name: &str) -> Result<String, ErrorData> {
+    let path = format_path(&vec![root, ".my_svn", "TAG_EDITMSG"]);
+
+    fs::write(&path, TAG_TEMPLATE.replace("{}", name)).map_err(|e| {
+        error_data!(
+            "edit_tag_message",
+            e.to_string(),
+            "Failed to write TAG_EDITMSG"
+        )
+    })?;
+
+    launch_editor(root, &path).map_err(|e| {
+        error_data!(
+            "edit_tag_message",
+            e.to_string(),
+            "Failed to edit tag message"
+        )
+    })?;
+
+    let content = fs::read_to_string(&path).map_err(|e| {
+        error_data!(
+            "edit_tag_message",
+            e.to_string(),
+            "Failed to read TAG_EDITMSG"
+        )
+    })?;
+
+    Ok(cleanup_message(&content, true))
+}
+
+pub fn tag(action: TagAction) -> Result<(), ErrorData> {
+    let root = match find_repo_root(&"./".to_string()) {
+        Ok(rep_loc) => rep_loc,
+        Err(e) => match e {
+            ErrorData(ed) => {
+                return Err(error_data!(
+                    "tag",
+                    ed.to_string(),
+                    "Failed to find repository root"
+                ));
+            }
+            RepositoryNotFoundError => {
+                println!("fatal: not a svn repository (or any of the parent directories): .my_svn");
+                return Ok(());
+            }
+        },
+    };
+
+    match action {
+        TagAction::List => {
+            for name in list_tags(&root.root)
+                .map_err(|e| error_data!("tag", e.to_string(), "Failed to list tags"))?
+            {
+                println!("{}", name);
+            }
+        }
+        TagAction::Create {
+            name,
+            target,
+            annotate,
+            paragraphs,
+        } => {
+            let message =
+                if !paragraphs.is_empty() {
+                    Some(cleanup_message(&paragraphs.join("\n\n"), false))
+                } else if annotate {
+                    // Tag-ul trebuie sa existe inainte de a deschide editorul
+                    if Path::new(&tag_path(&root.root, &name)).exists() {
+                        println!("fatal: tag '{}' already exists", name);
+                        return Ok(());
+                    }
+
+                    Some(edit_tag_message(&root.root, &name).map_err(|e| {
+                        error_data!("tag", e.to_string(), "Failed to get tag message")
+                    })?)
+                } else {
+                    None
+                };
+
+            if message.as_deref() == Some("") {
+                println!("fatal: no tag message?");
+                return Ok(());
+            }
+
+            match create_tag_ref(&root.root, &name, target.as_deref(), message.as_deref())
+                .map_err(|e| error_data!("tag", e.to_string(), "Failed to create tag"))?
+            {
+                CreateTagOutcome::Created(_) => {}
+                CreateTagOutcome::AlreadyExists => {
+                    println!("fatal: tag '{}' already exists", name)
+                }
+                CreateTagOutcome::InvalidName => {
+                    println!("fatal: '{}' is not a valid tag name.", name)
+                }
+                CreateTagOutcome::InvalidTarget(e) => println!("fatal: {}", e),
+                CreateTagOutcome::NoCommits => {
+                    println!("fatal: Failed to resolve 'HEAD' as a valid ref.")
+                }
+            }
+        }
+        TagAction::Delete(name) => match delete_tag_ref(&root.root, &name)
+            .map_err(|e| error_data!("tag", e.to_string(), "Failed to delete tag"))?
+        {
+            DeleteTagOutcome::Deleted(hash) => println!(
+                "Deleted tag '{}' (was {})",
+                name,
+                &hash[..7.min(hash.len())]
+            ),
+            DeleteTagOutcome::NotFound => println!("error: tag '{}' not found.", name),
+        },
+    }
+
+    Ok(())
+}
@@ -0,0 +1,56 @@
+use crate::{
+    error_data,
+    utils::{
+        error::ErrorData,
+        lock::{STALE_LOCK_AGE, remove_stale_locks},
+        path::{RepoLocationError::*, find_repo_root},
+    },
+};
+
+// Fara `force` lock-urile recente raman pe loc: pot apartine unei comenzi care ruleaza
+pub fn unlock(force: bool) -> Result<(), ErrorData> {
+    let root = match find_repo_root(&"./".to_string()) {
+        Ok(rep_loc) => rep_loc,
+        Err(e) => match e {
+            ErrorData(ed) => {
+                return Err(error_data!(
+                    "unlock",
+                    ed.to_string(),
+                    "Failed to find repository root"
+                ));
+            }
+            RepositoryNotFoundError => {
+                println!("fatal: not a svn repository (or any of the parent directories): .my_svn");
+                return Ok(());
+            }
+        },
+    };
+
+    let cleanup = remove_stale_locks(&root.root, force)
+        .map_err(|e| error_data!("unlock", e.to_string(), "Failed to remove stale locks"))?;
+
+    if cleanup.removed.is_empty() && cleanup.kept.is_empty() {
+        println!("No lock files found.");
+    }
+
+    for path in &cleanup.removed {
+        println!("Removed stale lock .my_svn/{}", path);
+    }
+
+    for (path, age) in &cleanup.kept {
+        println!(
+            "Kept .my_svn/{} (created {}s ago, another my_svn process may still be using it)",
+            path,
+            age.as_secs()
+        );
+    }
+
+    if !cleanup.kept.is_empty() {
+        println!(
+            "Locks younger than {} minutes are kept; use `my_svn unlock --force` if no other my_svn process is running.",
+            STALE_LOCK_AGE.as_secs() / 60
+        );
+    }
+
+    Ok(())
+}
@@ -0,0 +1,5 @@
+pub mod commands;
+pub mod repository;
+pub mod utils;
+
+pub use repository::Repository;
@@ -1,65 +1,157 @@
 use colored::*;
 
-use crate::utils::parser::{Command, parse_args};
-
-mod commands;
-mod utils;
+use my_svn::utils::parser::{Command, parse_args};
 
 fn main() {
     match parse_args() {
         Ok(cmd) => match cmd {
-            Command::Init => match crate::commands::init::init() {
+            Command::Init(object_format) => match my_svn::commands::init::init(object_format) {
                 Ok(msg) => println!("{}", msg),
                 Err(e) => println!("{}{}", String::from("Error at init:\n").red(), e),
             },
-            Command::Add(files) => match crate::commands::add::add(&files) {
+            Command::Add(files) => match my_svn::commands::add::add(&files) {
                 Ok(_) => {}
                 Err(e) => println!("{}{}", String::from("Error at add:\n").red(), e),
             },
-            Command::Status => match crate::commands::status::status() {
+            Command::Status => match my_svn::commands::status::status() {
                 Ok(_) => {}
                 Err(e) => println!("{}{}", String::from("Error at status:\n").red(), e),
             },
-            Command::Commit(message) => match crate::commands::commit::commit(message, "") {
+            Command::Commit(source, options) => {
+                match my_svn::commands::commit::commit(source, options) {
+                    Ok(committed) => {
+                        if !committed {
+                            std::process::exit(1);
+                        }
+                    }
+                    Err(e) => {
+                        println!("{}{}", String::from("Error at commit:\n").red(), e);
+                        std::process::exit(1);
+                    }
+                }
+            }
+            Command::Branch(action) => match my_svn::commands::branch::branch(action) {
+                Ok(_) => {}
+                Err(e) => println!("{}{}", String::from("Error at branch:\n").red(), e),
+            },
+            Command::Tag(action) => match my_svn::commands::tag::tag(action) {
                 Ok(_) => {}
-                Err(e) => println!("{}{}", String::from("Error at commit:\n").red(), e),
+                Err(e) => println!("{}{}", String::from("Error at tag:\n").red(), e),
             },
-            Command::Branch(branch_name) => {
-                match crate::commands::branch::create_branch(&branch_name) {
+            Command::Checkout(branch, orphan) => {
+                let result = if orphan {
+                    my_svn::commands::checkout::checkout_orphan(&branch)
+                } else {
+                    my_svn::commands::checkout::checkout(&branch)
+                };
+
+                match result {
                     Ok(_) => {}
-                    Err(e) => println!("{}{}", String::from("Error at branch:\n").red(), e),
+                    Err(e) => println!("{}{}", String::from("Error at checkout:\n").red(), e),
                 }
             }
-            Command::Checkout(branch) => match crate::commands::checkout::checkout(&branch) {
-                Ok(_) => {}
-                Err(e) => println!("{}{}", String::from("Error at checkout:\n").red(), e),
-            },
-            Command::Merge(branch) => match crate::commands::merge::merge(&branch) {
+            Command::Merge(branch) => match my_svn::commands::merge::merge(&branch) {
                 Ok(_) => {}
                 Err(e) => println!("{}{}", String::from("Error at merge:\n").red(), e),
             },
-            Command::Log => match crate::commands::log::log() {
+            Command::Log(target) => match my_svn::commands::log::log(target) {
                 Ok(_) => {}
                 Err(e) => println!("{}{}", String::from("Error at log:\n").red(), e),
             },
-            Command::Diff(commit) => match crate::commands::diff::diff(commit) {
+            Command::Reflog(target) => match my_svn::commands::reflog::reflog(target) {
+                Ok(_) => {}
+                Err(e) => println!("{}{}", String::from("Error at reflog:\n").red(), e),
+            },
+            Command::Diff(commit) => match my_svn::commands::diff::diff(commit) {
                 Ok(_) => {}
                 Err(e) => println!("{}{}", String::from("Error at diff:\n").red(), e),
             },
+            Command::Migrate => match my_svn::commands::migrate::migrate() {
+                Ok(_) => {}
+                Err(e) => println!("{}{}", String::from("Error at migrate:\n").red(), e),
+            },
+            Command::Repack => match my_svn::commands::repack::repack() {
+                Ok(_) => {}
+                Err(e) => println!("{}{}", String::from("Error at repack:\n").red(), e),
+            },
+            Command::Fsck => match my_svn::commands::fsck::fsck() {
+                Ok(damaged) => {
+                    if damaged {
+                        std::process::exit(1);
+                    }
+                }
+                Err(e) => {
+                    println!("{}{}", String::from("Error at fsck:\n").red(), e);
+                    std::process::exit(1);
+                }
+            },
+            Command::Gc(prune, dry_run) => match my_svn::commands::gc::gc(prune, dry_run) {
+                Ok(_) => {}
+                Err(e) => println!("{}{}", String::from("Error at gc:\n").red(), e),
+            },
+            Command::CatFile(mode, object) => {
+                match my_svn::commands::cat_file::cat_file(mode, &object) {
+                    Ok(_) => {}
+                    Err(e) => println!("{}{}", String::from("Error at cat-file:\n").red(), e),
+                }
+            }
+            Command::Config(action, global) => {
+                match my_svn::commands::config::config(action, global) {
+                    Ok(_) => {}
+                    Err(e) => println!("{}{}", String::from("Error at config:\n").red(), e),
+                }
+            }
+            Command::Unlock(force) => match my_svn::commands::unlock::unlock(force) {
+                Ok(_) => {}
+                Err(e) => println!("{}{}", String::from("Error at unlock:\n").red(), e),
+            },
             Command::Help => {
                 println!("My_SVN - A simple version control system");
                 println!();
                 println!("Available commands:");
-                println!("  init                 Initialize a new repository");
+                println!("  init [--object-format=<sha1|sha256>]  Initialize a new repository");
                 println!("  add <files>         Add files to the staging area");
-                println!("  commit -m <message> Commit staged changes with a message");
+                println!(
+                    "  commit [-a] [-m <message>]... [-F <file>] [--amend] [--allow-empty] [--no-verify] [<path>...]  Commit staged changes, or only the given paths (opens $EDITOR without -m/-F)"
+                );
                 println!("  status              Show the status of the working directory");
+                println!("  branch [-v]         List branches (with tip commits)");
                 println!("  branch <name>      Create a new branch");
-                println!("  checkout <branch>  Switch to a different branch");
-                println!("  merge <branch>     Merge a branch into the current branch");
-                println!("  log                 Show commit history");
+                println!("  branch -d|-D <name>  Delete a merged branch (-D: even if unmerged)");
+                println!("  branch -m [<old>] <new>  Rename a branch");
+                println!("  tag [-l]            List tags");
+                println!(
+                    "  tag [-a] [-m <message>]... <name> [<rev>]  Create a tag (annotated with -a/-m) at HEAD or the given commit"
+                );
+                println!("  tag -d <name>       Delete a tag");
+                println!(
+                    "  checkout <branch|rev>  Switch to a branch, or detach HEAD at any other revision"
+                );
+                println!(
+                    "  checkout --orphan <name>  Start a new branch with no history from the current index"
+                );
+                println!("  merge <rev>         Merge a branch or commit into the current branch");
+                println!("  log [<rev>|<rev>..<rev>]  Show commit history");
+                println!(
+                    "  reflog [<ref>]      Show where HEAD (or a branch or tag) pointed before each change"
+                );
+                println!(
+                    "  diff [<rev>|<rev>..<rev>]  Show differences between commits or working directory"
+                );
+                println!("  migrate             Upgrade objects of an older repository");
+                println!("  repack              Pack loose objects using delta compression");
+                println!("  fsck                Verify the integrity of the repository objects");
+                println!(
+                    "  gc [--dry-run] [--prune=<date>]  Remove unreachable objects and pack the rest"
+                );
+                println!(
+                    "  cat-file -t|-s|-p <object>  Show the type, size or content of an object (hash or revision)"
+                );
+                println!(
+                    "  config [--global] <key> [<value>]  Get or set a config value (--unset <key>, --list)"
+                );
                 println!(
-                    "  diff [commit]      Show differences between commits or working directory"
+                    "  unlock [--force]    Remove lock files left behind by a crashed command (--force: even recent ones)"
                 );
                 println!("  help                Show this help message");
             }
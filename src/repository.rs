@@ -0,0 +1,275 @@
+use std::path::{Component, Path};
+
+use crate::{
+    commands::{
+        add::{AddOutcome, add_paths},
+        branch::{
+            Branch, DeleteBranchOutcome, Head, RenameBranchOutcome, delete_branch_ref,
+            get_current_branch, head_commit, list_branch_details, read_head, rename_branch_ref,
+        },
+        commit::{CommitOptions, CommitOutcome, create_commit},
+        diff::{DiffOutcome, diff_against},
+        log::LogIter,
+        merge::{MergeOutcome, merge_branch},
+        status::{StatusReport, collect_status},
+        tag::{CreateTagOutcome, DeleteTagOutcome, create_tag_ref, delete_tag_ref, list_tags},
+    },
+    error_data,
+    utils::{
+        error::ErrorData,
+        hooks::{HookOutcome, run_hook},
+        path::{RepoLocation, RepoLocationError, find_repo_root, format_path},
+        reflog::{ReflogEntry, read_reflog, reflog_exists, reflog_ref},
+        revision::{RevisionError, Revisions, resolve_revisions},
+    },
+};
+
+// Punctul de intrare al bibliotecii: comenzile intorc valori structurate,
+// iar afisarea ramane in seama binarului.
+//
+// Doua cache-uri sunt globale procesului si indexate dupa radacina:
+// `PACK_CACHE` (indexurile pack-urilor) si `OBJECT_FORMATS` (algoritmul de
+// hash din config). Primul este citit din nou cand directorul `pack` se
+// schimba; al doilea niciodata, deci un proces care sterge si reinitializeaza
+// un depozit la aceeasi cale cu alt `--object-format` vede formatul vechi.
+pub struct Repository {
+    location: RepoLocation,
+}
+
+impl Repository {
+    // Deschide depozitul a carui radacina este exact `path`
+    pub fn open(path: &str) -> Result<Self, RepoLocationError> {
+        let location = find_repo_root(&path.to_string())?;
+
+        if !location.relative.is_empty() {
+            return Err(RepoLocationError::RepositoryNotFoundError);
+        }
+
+        Ok(Self { location })
+    }
+
+    // Cauta depozitul in `path` si in directoarele parinte
+    pub fn discover(path: &str) -> Result<Self, RepoLocationError> {
+        Ok(Self {
+            location: find_repo_root(&path.to_string())?,
+        })
+    }
+
+    pub fn from_location(location: RepoLocation) -> Self {
+        Self { location }
+    }
+
+    pub fn root(&self) -> &str {
+        &self.location.root
+    }
+
+    pub fn location(&self) -> &RepoLocation {
+        &self.location
+    }
+
+    // Calea relativa la radacina, fara componentele `.` si `..`
+    fn root_relative(&self, path: &str) -> String {
+        let mut parts: Vec<String> = Vec::new();
+
+        for component in Path::new(&self.location.relative).join(path).components() {
+            match component {
+                Component::Normal(part) => parts.push(part.to_string_lossy().to_string()),
+                Component::ParentDir => {
+                    parts.pop();
+                }
+                _ => {}
+            }
+        }
+
+        format_path(&parts.iter().map(|p| p.as_str()).collect())
+    }
+
+    pub fn head(&self) -> Result<Head, ErrorData> {
+        read_head(self.root())
+            .map_err(|e| error_data!("Repository::head", e.to_string(), "Failed to read HEAD"))
+    }
+
+    pub fn current_branch(&self) -> Result<String, ErrorData> {
+        get_current_branch(self.root()).map_err(|e| {
+            error_data!(
+                "Repository::current_branch",
+                e.to_string(),
+                "Failed to get current branch"
+            )
+        })
+    }
+
+    pub fn branches(&self) -> Result<Vec<Branch>, ErrorData> {
+        list_branch_details(self.root()).map_err(|e| {
+            error_data!(
+                "Repository::branches",
+                e.to_string(),
+                "Failed to list branches"
+            )
+        })
+    }
+
+    // Fara `force`, ramurile care nu sunt unite in cea curenta nu sunt sterse
+    pub fn delete_branch(&self, name: &str, force: bool) -> Result<DeleteBranchOutcome, ErrorData> {
+        delete_branch_ref(self.root(), name, force).map_err(|e| {
+            error_data!(
+                "Repository::delete_branch",
+                e.to_string(),
+                "Failed to delete branch"
+            )
+        })
+    }
+
+    pub fn rename_branch(
+        &self,
+        old_name: &str,
+        new_name: &str,
+    ) -> Result<RenameBranchOutcome, ErrorData> {
+        rename_branch_ref(self.root(), old_name, new_name).map_err(|e| {
+            error_data!(
+                "Repository::rename_branch",
+                e.to_string(),
+                "Failed to rename branch"
+            )
+        })
+    }
+
+    pub fn tags(&self) -> Result<Vec<String>, ErrorData> {
+        list_tags(self.root())
+            .map_err(|e| error_data!("Repository::tags", e.to_string(), "Failed to list tags"))
+    }
+
+    // Cu `message` tag-ul este adnotat; fara `target` indica spre HEAD
+    pub fn create_tag(
+        &self,
+        name: &str,
+        target: Option<&str>,
+        message: Option<&str>,
+    ) -> Result<CreateTagOutcome, ErrorData> {
+        create_tag_ref(self.root(), name, target, message).map_err(|e| {
+            error_data!(
+                "Repository::create_tag",
+                e.to_string(),
+                "Failed to create tag"
+            )
+        })
+    }
+
+    pub fn delete_tag(&self, name: &str) -> Result<DeleteTagOutcome, ErrorData> {
+        delete_tag_ref(self.root(), name).map_err(|e| {
+            error_data!(
+                "Repository::delete_tag",
+                e.to_string(),
+                "Failed to delete tag"
+            )
+        })
+    }
+
+    pub fn status(&self) -> Result<StatusReport, ErrorData> {
+        collect_status(self.root()).map_err(|e| {
+            error_data!(
+                "Repository::status",
+                e.to_string(),
+                "Failed to collect status"
+            )
+        })
+    }
+
+    // Caile sunt relative la directorul din care a fost gasit depozitul
+    pub fn add(&self, paths: &[String]) -> Result<AddOutcome, ErrorData> {
+        add_paths(&self.location, paths)
+            .map_err(|e| error_data!("Repository::add", e.to_string(), "Failed to add paths"))
+    }
+
+    // `options.paths` sunt relative la directorul din care a fost gasit depozitul.
+    // Hook-urile nu sunt rulate aici; comanda `commit` le ruleaza cu `run_hook`
+    pub fn commit(
+        &self,
+        message: &str,
+        options: &CommitOptions,
+    ) -> Result<CommitOutcome, ErrorData> {
+        let mut options = options.clone();
+        options.paths = options
+            .paths
+            .iter()
+            .map(|p| self.root_relative(p))
+            .collect();
+
+        create_commit(self.root(), message.to_string(), "", &options).map_err(|e| {
+            error_data!(
+                "Repository::commit",
+                e.to_string(),
+                "Failed to create commit"
+            )
+        })
+    }
+
+    // Iesirea hook-ului este capturata in rezultat, nu afisata
+    pub fn run_hook(&self, name: &str, args: &[&str]) -> Result<HookOutcome, ErrorData> {
+        run_hook(self.root(), name, args)
+            .map_err(|e| error_data!("Repository::run_hook", e.to_string(), "Failed to run hook"))
+    }
+
+    // Istoricul lui HEAD, de la cel mai nou commit spre cele mai vechi
+    pub fn log(&self) -> Result<LogIter, ErrorData> {
+        let hash = head_commit(self.root())
+            .map_err(|e| error_data!("Repository::log", e.to_string(), "Failed to read HEAD"))?;
+
+        LogIter::new(self.root(), &hash)
+            .map_err(|e| error_data!("Repository::log", e.to_string(), "Failed to start log"))
+    }
+
+    // Istoricul unei revizii (`main`, `v1.0~2`, ...) sau al unui interval `a..b`
+    pub fn log_revisions(&self, spec: &str) -> Result<Result<LogIter, RevisionError>, ErrorData> {
+        let revisions = match resolve_revisions(self.root(), spec).map_err(|e| {
+            error_data!(
+                "Repository::log_revisions",
+                e.to_string(),
+                "Failed to resolve revision"
+            )
+        })? {
+            Ok(r) => r,
+            Err(e) => return Ok(Err(e)),
+        };
+
+        let iter = match revisions {
+            Revisions::Single(hash) => LogIter::new(self.root(), &hash),
+            Revisions::Range { from, to } => LogIter::range(self.root(), &from, &to),
+        };
+
+        iter.map(Ok).map_err(|e| {
+            error_data!(
+                "Repository::log_revisions",
+                e.to_string(),
+                "Failed to start log"
+            )
+        })
+    }
+
+    // Valorile anterioare ale lui HEAD, ale unei ramuri sau ale unui tag, de la
+    // cea mai noua; `None` daca referinta nu are reflog
+    pub fn reflog(&self, name: &str) -> Result<Option<Vec<ReflogEntry>>, ErrorData> {
+        let ref_name = reflog_ref(self.root(), name).map_err(|e| {
+            error_data!("Repository::reflog", e.to_string(), "Failed to find reflog")
+        })?;
+
+        if !reflog_exists(self.root(), &ref_name) {
+            return Ok(None);
+        }
+
+        read_reflog(self.root(), &ref_name)
+            .map(Some)
+            .map_err(|e| error_data!("Repository::reflog", e.to_string(), "Failed to read reflog"))
+    }
+
+    // Fara `target` se compara ultimul commit cu parintele sau
+    pub fn diff(&self, target: Option<&str>) -> Result<DiffOutcome, ErrorData> {
+        diff_against(self.root(), target)
+            .map_err(|e| error_data!("Repository::diff", e.to_string(), "Failed to compute diff"))
+    }
+
+    pub fn merge(&self, branch: &str) -> Result<MergeOutcome, ErrorData> {
+        merge_branch(self.root(), branch)
+            .map_err(|e| error_data!("Repository::merge", e.to_string(), "Failed to merge branch"))
+    }
+}
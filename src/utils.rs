@@ -1,6 +1,17 @@
+pub mod config;
+pub mod delta;
 pub mod error;
 pub mod hash;
+pub mod hooks;
+pub mod identity;
 pub mod index;
 pub mod json;
+pub mod lock;
+pub mod object;
+pub mod pack;
 pub mod parser;
 pub mod path;
+pub mod reflog;
+pub mod revision;
+#[cfg(test)]
+pub mod testing;
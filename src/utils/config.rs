@@ -0,0 +1,310 @@
+use std::{env, fs, path::Path};
+
+use crate::{
+    error_data,
+    utils::{
+        error::ErrorData,
+        lock::{LockFile, write_locked},
+        path::format_path,
+    },
+};
+
+// Versiunea formatului pe disc scrisa de `init`. Depozitele fara config
+// (create inainte de versionare) sunt considerate versiunea 0.
+pub const REPOSITORY_FORMAT_VERSION: u32 = 1;
+
+// Fisier de configurare in stil INI:
+//
+// [core]
+//     repositoryformatversion = 1
+//     objectformat = sha1
+//
+// Cheile sunt adresate ca "sectiune.nume" si nu tin cont de majuscule.
+#[derive(Default)]
+pub struct Config {
+    sections: Vec<(String, Vec<(String, String)>)>,
+}
+
+pub fn repo_config_path(root: &str) -> String {
+    format_path(&vec![root, ".my_svn", "config"])
+}
+
+pub fn user_config_path() -> Option<String> {
+    let home = env::var("HOME").or_else(|_| env::var("USERPROFILE")).ok()?;
+
+    Some(format_path(&vec![&home, ".my_svnconfig"]))
+}
+
+// Config-ul utilizatorului, peste care se aplica cel al depozitului
+// (valorile din depozit au prioritate)
+pub fn load_config(root: &str) -> Result<Config, ErrorData> {
+    let mut config = match user_config_path() {
+        Some(path) => Config::load(&path)
+            .map_err(|e| error_data!("load_config", e.to_string(), "Failed to load user config"))?,
+        None => Config::default(),
+    };
+
+    let repo_config = Config::load(&repo_config_path(root)).map_err(|e| {
+        error_data!(
+            "load_config",
+            e.to_string(),
+            "Failed to load repository config"
+        )
+    })?;
+
+    for (key, value) in repo_config.entries() {
+        config.set(&key, &value);
+    }
+
+    Ok(config)
+}
+
+pub fn check_repository_format(root: &str) -> Result<(), ErrorData> {
+    let config = Config::load(&repo_config_path(root)).map_err(|e| {
+        error_data!(
+            "check_repository_format",
+            e.to_string(),
+            "Failed to load repository config"
+        )
+    })?;
+
+    let version = match config.get("core.repositoryformatversion") {
+        None => 0,
+        Some(v) => match v.parse::<u32>() {
+            Ok(n) => n,
+            Err(e) => {
+                return Err(error_data!(
+                    "check_repository_format",
+                    format!("core.repositoryformatversion = {} ({})", v, e),
+                    "Invalid repository format version"
+                ));
+            }
+        },
+    };
+
+    if version > REPOSITORY_FORMAT_VERSION {
+        return Err(error_data!(
+            "check_repository_format",
+            format!(
+                "Repository at {} uses format version {}, but this my_svn only supports up to version {}",
+                root, version, REPOSITORY_FORMAT_VERSION
+            ),
+            "Unknown repository format version"
+        ));
+    }
+
+    Ok(())
+}
+
+fn split_key(key: &str) -> Option<(String, String)> {
+    let (section, name) = key.rsplit_once('.')?;
+
+    if section.is_empty() || name.is_empty() {
+        return None;
+    }
+
+    Some((section.to_lowercase(), name.to_lowercase()))
+}
+
+impl Config {
+    pub fn parse(content: &str) -> Result<Self, ErrorData> {
+        let mut config = Config::default();
+        let mut current: Option<String> = None;
+
+        for (number, line) in content.lines().enumerate() {
+            let line = line.trim();
+
+            if line.is_empty() || line.starts_with('#') || line.starts_with(';') {
+                continue;
+            }
+
+            if line.starts_with('[') && line.ends_with(']') {
+                let section = line[1..line.len() - 1].trim().to_lowercase();
+                if !config.sections.iter().any(|(s, _)| *s == section) {
+                    config.sections.push((section.clone(), Vec::new()));
+                }
+                current = Some(section);
+                continue;
+            }
+
+            let (section, (name, value)) = match (&current, line.split_once('=')) {
+                (Some(s), Some(pair)) => (s.clone(), pair),
+                _ => {
+                    return Err(error_data!(
+                        "Config::parse",
+                        format!("line {}: {}", number + 1, line),
+                        "Invalid config line"
+                    ));
+                }
+            };
+
+            config.set(&format!("{}.{}", section, name.trim()), value.trim());
+        }
+
+        Ok(config)
+    }
+
+    pub fn load(path: &str) -> Result<Self, ErrorData> {
+        if !Path::new(path).exists() {
+            return Ok(Config::default());
+        }
+
+        let content = fs::read_to_string(path).map_err(|e| {
+            error_data!("Config::load", e.to_string(), "Failed to read config file")
+        })?;
+
+        Config::parse(&content)
+            .map_err(|e| error_data!("Config::load", e.to_string(), "Failed to parse config file"))
+    }
+
+    pub fn get(&self, key: &str) -> Option<&str> {
+        let (section, name) = split_key(key)?;
+
+        self.sections
+            .iter()
+            .find(|(s, _)| *s == section)?
+            .1
+            .iter()
+            .find(|(n, _)| *n == name)
+            .map(|(_, v)| v.as_str())
+    }
+
+    pub fn set(&mut self, key: &str, value: &str) -> bool {
+        let (section, name) = match split_key(key) {
+            Some(k) => k,
+            None => return false,
+        };
+
+        let entries = match self.sections.iter_mut().position(|(s, _)| *s == section) {
+            Some(i) => &mut self.sections[i].1,
+            None => {
+                self.sections.push((section, Vec::new()));
+                match self.sections.last_mut() {
+                    Some(s) => &mut s.1,
+                    None => return false,
+                }
+            }
+        };
+
+        match entries.iter_mut().find(|(n, _)| *n == name) {
+            Some(entry) => entry.1 = value.to_string(),
+            None => entries.push((name, value.to_string())),
+        }
+
+        true
+    }
+
+    pub fn unset(&mut self, key: &str) -> bool {
+        let (section, name) = match split_key(key) {
+            Some(k) => k,
+            None => return false,
+        };
+
+        match self.sections.iter_mut().find(|(s, _)| *s == section) {
+            Some((_, entries)) => {
+                let before = entries.len();
+                entries.retain(|(n, _)| *n != name);
+                entries.len() != before
+            }
+            None => false,
+        }
+    }
+
+    pub fn entries(&self) -> Vec<(String, String)> {
+        self.sections
+            .iter()
+            .flat_map(|(section, entries)| {
+                entries
+                    .iter()
+                    .map(move |(name, value)| (format!("{}.{}", section, name), value.clone()))
+            })
+            .collect()
+    }
+
+    pub fn to_ini(&self) -> String {
+        let mut content = String::new();
+
+        for (section, entries) in &self.sections {
+            if entries.is_empty() {
+                continue;
+            }
+
+            content.push_str(&format!("[{}]\n", section));
+            for (name, value) in entries {
+                content.push_str(&format!("\t{} = {}\n", name, value));
+            }
+        }
+
+        content
+    }
+
+    // Config-ul depozitului contine formatul lui, deci nu poate ramane scris pe jumatate
+    pub fn save(&self, path: &str) -> Result<(), ErrorData> {
+        write_locked(path, self.to_ini().as_bytes())
+            .map_err(|e| error_data!("Config::save", e.to_string(), "Failed to write config file"))
+    }
+
+    // Citeste, modifica si scrie fisierul sub acelasi lock, ca doua comenzi
+    // `config` simultane sa nu isi piarda modificarile; `change` intoarce
+    // `false` daca nu este nimic de scris
+    pub fn update(path: &str, change: impl FnOnce(&mut Config) -> bool) -> Result<bool, ErrorData> {
+        let lock = LockFile::acquire(path).map_err(|e| {
+            error_data!(
+                "Config::update",
+                e.to_string(),
+                "Failed to lock config file"
+            )
+        })?;
+
+        let mut config = Config::load(path).map_err(|e| {
+            error_data!(
+                "Config::update",
+                e.to_string(),
+                "Failed to load config file"
+            )
+        })?;
+
+        if !change(&mut config) {
+            return Ok(false);
+        }
+
+        lock.commit(config.to_ini().as_bytes()).map_err(|e| {
+            error_data!(
+                "Config::update",
+                e.to_string(),
+                "Failed to write config file"
+            )
+        })?;
+
+        Ok(true)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::testing::TempRepo;
+
+    #[test]
+    fn update_rewrites_config_under_lock() {
+        let repo = TempRepo::new();
+        let path = repo_config_path(&repo.root);
+
+        assert!(Config::update(&path, |c| c.set("user.name", "tester")).unwrap());
+        assert!(!Config::update(&path, |c| c.unset("user.email")).unwrap());
+
+        let config = Config::load(&path).unwrap();
+        assert_eq!(config.get("user.name"), Some("tester"));
+        assert_eq!(config.get("core.repositoryformatversion"), Some("1"));
+        assert!(!Path::new(&format!("{}.lock", path)).exists());
+
+        // Cu fisierul blocat de alta comanda nu se scrie nimic
+        let lock = LockFile::acquire(&path).unwrap();
+        assert!(Config::update(&path, |c| c.set("user.name", "other")).is_err());
+        drop(lock);
+        assert_eq!(
+            Config::load(&path).unwrap().get("user.name"),
+            Some("tester")
+        );
+    }
+}
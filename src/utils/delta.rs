@@ -0,0 +1,220 @@
+use std::collections::HashMap;
+
+const BLOCK_SIZE: usize = 16;
+
+const OP_COPY: u8 = 1;
+const OP_INSERT: u8 = 2;
+
+fn push_u64(out: &mut Vec<u8>, value: u64) {
+    out.extend_from_slice(&value.to_le_bytes());
+}
+
+fn read_u64(data: &[u8], pos: &mut usize) -> Option<u64> {
+    let bytes = data.get(*pos..*pos + 8)?;
+    *pos += 8;
+
+    Some(u64::from_le_bytes(bytes.try_into().ok()?))
+}
+
+fn flush_insert(out: &mut Vec<u8>, pending: &mut Vec<u8>) {
+    if pending.is_empty() {
+        return;
+    }
+
+    out.push(OP_INSERT);
+    push_u64(out, pending.len() as u64);
+    out.extend_from_slice(pending);
+    pending.clear();
+}
+
+// Delta = lista de operatii care reconstruiesc `target` din `base`:
+// COPY (offset, lungime) din base sau INSERT (octeti noi).
+// Base-ul este impartit in blocuri de BLOCK_SIZE octeti; fiecare potrivire
+// gasita in target este extinsa cat de mult se poate.
+pub fn create_delta(base: &[u8], target: &[u8]) -> Vec<u8> {
+    let mut blocks: HashMap<&[u8], Vec<usize>> = HashMap::new();
+
+    let mut pos = 0;
+    while pos + BLOCK_SIZE <= base.len() {
+        blocks
+            .entry(&base[pos..pos + BLOCK_SIZE])
+            .or_default()
+            .push(pos);
+        pos += BLOCK_SIZE;
+    }
+
+    let mut out: Vec<u8> = Vec::new();
+    push_u64(&mut out, base.len() as u64);
+    push_u64(&mut out, target.len() as u64);
+
+    let mut pending: Vec<u8> = Vec::new();
+    let mut i = 0;
+
+    while i < target.len() {
+        let mut best: Option<(usize, usize)> = None;
+
+        if i + BLOCK_SIZE <= target.len()
+            && let Some(candidates) = blocks.get(&target[i..i + BLOCK_SIZE])
+        {
+            for &start in candidates {
+                let mut len = BLOCK_SIZE;
+                while start + len < base.len()
+                    && i + len < target.len()
+                    && base[start + len] == target[i + len]
+                {
+                    len += 1;
+                }
+
+                if best.is_none_or(|(_, best_len)| len > best_len) {
+                    best = Some((start, len));
+                }
+            }
+        }
+
+        match best {
+            Some((mut start, mut len)) => {
+                i += len;
+
+                // Octetii deja pusi in INSERT care se potrivesc si ei sunt mutati in COPY
+                while start > 0 && !pending.is_empty() && pending.last() == Some(&base[start - 1]) {
+                    pending.pop();
+                    start -= 1;
+                    len += 1;
+                }
+
+                flush_insert(&mut out, &mut pending);
+
+                out.push(OP_COPY);
+                push_u64(&mut out, start as u64);
+                push_u64(&mut out, len as u64);
+            }
+            None => {
+                pending.push(target[i]);
+                i += 1;
+            }
+        }
+    }
+
+    flush_insert(&mut out, &mut pending);
+
+    out
+}
+
+pub fn apply_delta(base: &[u8], delta: &[u8]) -> Option<Vec<u8>> {
+    let mut pos = 0;
+
+    let base_size = read_u64(delta, &mut pos)? as usize;
+    let result_size = read_u64(delta, &mut pos)? as usize;
+
+    if base_size != base.len() {
+        return None;
+    }
+
+    // Dimensiunea vine din date de pe disc: memoria este rezervata doar cat pot
+    // acoperi base-ul si delta-ul, iar fiecare operatie este verificata fata de ea
+    let mut result: Vec<u8> = Vec::with_capacity(result_size.min(base.len() + delta.len()));
+
+    while pos < delta.len() {
+        let op = delta[pos];
+        pos += 1;
+
+        let chunk = match op {
+            OP_COPY => {
+                let start = read_u64(delta, &mut pos)? as usize;
+                let len = read_u64(delta, &mut pos)? as usize;
+                base.get(start..start.checked_add(len)?)?
+            }
+            OP_INSERT => {
+                let len = read_u64(delta, &mut pos)? as usize;
+                let chunk = delta.get(pos..pos.checked_add(len)?)?;
+                pos += len;
+                chunk
+            }
+            _ => return None,
+        };
+
+        if chunk.len() > result_size - result.len() {
+            return None;
+        }
+        result.extend_from_slice(chunk);
+    }
+
+    if result.len() != result_size {
+        return None;
+    }
+
+    Some(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn round_trip(base: &[u8], target: &[u8]) -> Vec<u8> {
+        let delta = create_delta(base, target);
+        assert_eq!(apply_delta(base, &delta).as_deref(), Some(target));
+        delta
+    }
+
+    #[test]
+    fn round_trip_edited_text() {
+        let base: Vec<u8> = (0..200)
+            .flat_map(|i| format!("line {}\n", i).into_bytes())
+            .collect();
+        let mut target = base.clone();
+        target.splice(500..510, b"inserted in the middle".iter().copied());
+        target.extend_from_slice(b"appended at the end\n");
+
+        let delta = round_trip(&base, &target);
+        assert!(delta.len() < target.len() / 4);
+    }
+
+    #[test]
+    fn round_trip_edge_cases() {
+        round_trip(b"", b"");
+        round_trip(b"", b"only new bytes");
+        round_trip(b"some base content that is long enough", b"");
+        round_trip(
+            b"0123456789abcdef0123456789abcdef",
+            b"0123456789abcdef0123456789abcdef",
+        );
+        round_trip(&[7u8; 100], &[7u8; 1000]);
+    }
+
+    #[test]
+    fn rejects_wrong_base() {
+        let delta = create_delta(b"the original base, 32 bytes long", b"the target");
+        assert_eq!(apply_delta(b"another base", &delta), None);
+    }
+
+    #[test]
+    fn rejects_corrupt_sizes() {
+        let base = b"base bytes";
+
+        // Dimensiunea rezultatului este enorma, dar nu este alocata
+        let mut delta = Vec::new();
+        push_u64(&mut delta, base.len() as u64);
+        push_u64(&mut delta, u64::MAX);
+        delta.push(OP_COPY);
+        push_u64(&mut delta, 0);
+        push_u64(&mut delta, base.len() as u64);
+        assert_eq!(apply_delta(base, &delta), None);
+
+        // Operatiile produc mai mult decat dimensiunea declarata
+        let mut delta = Vec::new();
+        push_u64(&mut delta, base.len() as u64);
+        push_u64(&mut delta, 2);
+        delta.push(OP_COPY);
+        push_u64(&mut delta, 0);
+        push_u64(&mut delta, base.len() as u64);
+        assert_eq!(apply_delta(base, &delta), None);
+
+        // INSERT care depaseste sfarsitul delta-ului
+        let mut delta = Vec::new();
+        push_u64(&mut delta, base.len() as u64);
+        push_u64(&mut delta, 4);
+        delta.push(OP_INSERT);
+        push_u64(&mut delta, u64::MAX);
+        assert_eq!(apply_delta(base, &delta), None);
+    }
+}
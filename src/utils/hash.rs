@@ -1,9 +1,189 @@
 use sha1::{Digest, Sha1};
-use std::{fs, io::Read};
+use sha2::Sha256;
+use std::{fs, io::Read, sync::Mutex};
 
-use crate::{error_data, utils::error::ErrorData};
+use crate::{
+    error_data,
+    utils::config::{Config, repo_config_path},
+    utils::error::ErrorData,
+    utils::path::symlink_target,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum HashAlgorithm {
+    Sha1,
+    Sha256,
+}
+
+impl HashAlgorithm {
+    pub fn name(&self) -> &'static str {
+        match self {
+            HashAlgorithm::Sha1 => "sha1",
+            HashAlgorithm::Sha256 => "sha256",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_lowercase().as_str() {
+            "sha1" => Some(HashAlgorithm::Sha1),
+            "sha256" => Some(HashAlgorithm::Sha256),
+            _ => None,
+        }
+    }
+
+    pub fn hex_len(&self) -> usize {
+        match self {
+            HashAlgorithm::Sha1 => 40,
+            HashAlgorithm::Sha256 => 64,
+        }
+    }
+
+    fn from_hex_len(len: usize) -> Option<Self> {
+        match len {
+            40 => Some(HashAlgorithm::Sha1),
+            64 => Some(HashAlgorithm::Sha256),
+            _ => None,
+        }
+    }
+}
+
+pub enum ObjectHasher {
+    Sha1(Sha1),
+    Sha256(Sha256),
+}
+
+impl ObjectHasher {
+    pub fn new(algorithm: HashAlgorithm) -> Self {
+        match algorithm {
+            HashAlgorithm::Sha1 => ObjectHasher::Sha1(Sha1::new()),
+            HashAlgorithm::Sha256 => ObjectHasher::Sha256(Sha256::new()),
+        }
+    }
+
+    pub fn update(&mut self, data: &[u8]) {
+        match self {
+            ObjectHasher::Sha1(h) => h.update(data),
+            ObjectHasher::Sha256(h) => h.update(data),
+        }
+    }
+
+    pub fn finalize_hex(self) -> String {
+        match self {
+            ObjectHasher::Sha1(h) => hex::encode(h.finalize()),
+            ObjectHasher::Sha256(h) => hex::encode(h.finalize()),
+        }
+    }
+}
+
+// Formatul se citeste din config o singura data per depozit
+static OBJECT_FORMATS: Mutex<Vec<(String, HashAlgorithm)>> = Mutex::new(Vec::new());
+
+// Depozitele fara `core.objectformat` in config folosesc SHA-1
+pub fn object_format(root: &str) -> Result<HashAlgorithm, ErrorData> {
+    let mut formats = match OBJECT_FORMATS.lock() {
+        Ok(f) => f,
+        Err(e) => {
+            return Err(error_data!(
+                "object_format",
+                e.to_string(),
+                "Failed to lock object format cache"
+            ));
+        }
+    };
+
+    if let Some((_, algorithm)) = formats.iter().find(|(r, _)| r == root) {
+        return Ok(*algorithm);
+    }
+
+    let config = Config::load(&repo_config_path(root)).map_err(|e| {
+        error_data!(
+            "object_format",
+            e.to_string(),
+            "Failed to load repository config"
+        )
+    })?;
+
+    let algorithm = match config.get("core.objectformat") {
+        None => HashAlgorithm::Sha1,
+        Some(name) => match HashAlgorithm::from_name(name) {
+            Some(a) => a,
+            None => {
+                return Err(error_data!(
+                    "object_format",
+                    format!("core.objectformat = {}", name),
+                    "Unknown object format in repository config"
+                ));
+            }
+        },
+    };
+
+    formats.push((root.to_string(), algorithm));
+
+    Ok(algorithm)
+}
+
+// Refuza numele de obiecte produse de alt algoritm decat cel al depozitului
+pub fn check_object_name(root: &str, hash: &str) -> Result<(), ErrorData> {
+    let algorithm = object_format(root).map_err(|e| {
+        error_data!(
+            "check_object_name",
+            e.to_string(),
+            "Failed to get repository object format"
+        )
+    })?;
+
+    if hash.len() == algorithm.hex_len() {
+        return Ok(());
+    }
+
+    let source = match HashAlgorithm::from_hex_len(hash.len()) {
+        Some(other) => format!(
+            "Object {} is a {} name, but this repository uses {}",
+            hash,
+            other.name(),
+            algorithm.name()
+        ),
+        None => format!("Object {} is not a valid {} name", hash, algorithm.name()),
+    };
+
+    Err(error_data!(
+        "check_object_name",
+        source,
+        "Object name does not match repository object format"
+    ))
+}
+
+pub fn hash_bytes(root: &str, data: &[u8]) -> Result<String, ErrorData> {
+    let algorithm = object_format(root).map_err(|e| {
+        error_data!(
+            "hash_bytes",
+            e.to_string(),
+            "Failed to get repository object format"
+        )
+    })?;
+
+    let mut hasher = ObjectHasher::new(algorithm);
+    hasher.update(data);
+
+    Ok(hasher.finalize_hex())
+}
+
+pub fn calculate_hash(root: &str, path: &String) -> Result<String, ErrorData> {
+    let algorithm = object_format(root).map_err(|e| {
+        error_data!(
+            "calculate_hash",
+            e.to_string(),
+            "Failed to get repository object format"
+        )
+    })?;
+
+    let mut hasher = ObjectHasher::new(algorithm);
+
+    if let Some(target) = symlink_target(path) {
+        hasher.update(&target);
+        return Ok(hasher.finalize_hex());
+    }
 
-pub fn calculate_hash(path: &String) -> Result<String, ErrorData> {
     let mut file = match fs::File::open(path) {
         Ok(f) => f,
         Err(e) => {
@@ -15,7 +195,6 @@ pub fn calculate_hash(path: &String) -> Result<String, ErrorData> {
         }
     };
 
-    let mut hasher = Sha1::new();
     let mut buffer = [0u8; 4096];
 
     loop {
@@ -37,5 +216,5 @@ pub fn calculate_hash(path: &String) -> Result<String, ErrorData> {
         hasher.update(&buffer[..read_bytes]);
     }
 
-    Ok(hex::encode(hasher.finalize()))
+    Ok(hasher.finalize_hex())
 }
@@ -0,0 +1,96 @@
+use std::{path::Path, process};
+
+use crate::{
+    error_data,
+    utils::{error::ErrorData, path::format_path},
+};
+
+fn hook_path(root: &str, name: &str) -> String {
+    format_path(&vec![root, ".my_svn", "hooks", name])
+}
+
+#[cfg(unix)]
+fn is_executable(path: &str) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+
+    std::fs::metadata(path)
+        .map(|m| m.permissions().mode() & 0o111 != 0)
+        .unwrap_or(false)
+}
+
+// Pe Windows nu exista bit de executie: orice hook existent este rulat
+#[cfg(not(unix))]
+fn is_executable(_path: &str) -> bool {
+    true
+}
+
+// Ce s-a intamplat cu un hook; iesirea lui este capturata, iar afisarea
+// ramane in seama comenzii
+#[derive(Debug, Clone, PartialEq)]
+pub enum HookOutcome {
+    NotFound,
+    NotExecutable,
+    Finished {
+        success: bool,
+        stdout: String,
+        stderr: String,
+    },
+}
+
+impl HookOutcome {
+    // Un hook lipsa sau neexecutabil este ignorat, deci nu opreste comanda
+    pub fn passed(&self) -> bool {
+        match self {
+            HookOutcome::Finished { success, .. } => *success,
+            _ => true,
+        }
+    }
+}
+
+// Ruleaza `.my_svn/hooks/<name>` din radacina depozitului
+pub fn run_hook(root: &str, name: &str, args: &[&str]) -> Result<HookOutcome, ErrorData> {
+    let path = hook_path(root, name);
+
+    if !Path::new(&path).is_file() {
+        return Ok(HookOutcome::NotFound);
+    }
+
+    if !is_executable(&path) {
+        return Ok(HookOutcome::NotExecutable);
+    }
+
+    // Pe Windows hook-urile sunt scripturi shell (ca in Git for Windows)
+    let mut command = if cfg!(windows) {
+        let mut c = process::Command::new("sh");
+        c.arg(&path);
+        c
+    } else {
+        process::Command::new(&path)
+    };
+
+    let output = command
+        .args(args)
+        .current_dir(root)
+        .output()
+        .map_err(|e| error_data!("run_hook", e.to_string(), "Failed to run hook"))?;
+
+    Ok(HookOutcome::Finished {
+        success: output.status.success(),
+        stdout: String::from_utf8_lossy(&output.stdout).to_string(),
+        stderr: String::from_utf8_lossy(&output.stderr).to_string(),
+    })
+}
+
+pub fn print_hook_outcome(name: &str, outcome: &HookOutcome) {
+    match outcome {
+        HookOutcome::NotFound => {}
+        HookOutcome::NotExecutable => println!(
+            "hint: The '.my_svn/hooks/{}' hook was ignored because it's not set as executable.",
+            name
+        ),
+        HookOutcome::Finished { stdout, stderr, .. } => {
+            print!("{}", stdout);
+            eprint!("{}", stderr);
+        }
+    }
+}
@@ -0,0 +1,98 @@
+use chrono::{Local, NaiveDateTime, TimeZone};
+use serde::{Deserialize, Serialize};
+use std::env;
+
+use crate::{error_data, utils::config::load_config, utils::error::ErrorData};
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+pub struct Signature {
+    pub name: String,
+    pub email: String,
+    pub timestamp: u64,
+}
+
+#[derive(Clone, Copy)]
+pub enum IdentityRole {
+    Author,
+    Committer,
+}
+
+impl IdentityRole {
+    fn env_prefix(&self) -> &'static str {
+        match self {
+            IdentityRole::Author => "MY_SVN_AUTHOR",
+            IdentityRole::Committer => "MY_SVN_COMMITTER",
+        }
+    }
+}
+
+// Accepta fie secunde de la UNIX epoch (optional cu `@` in fata), fie
+// "YYYY-MM-DD HH:MM:SS" in ora locala
+fn parse_identity_date(value: &str) -> Option<u64> {
+    let value = value.trim();
+
+    if let Ok(seconds) = value.trim_start_matches('@').parse::<u64>() {
+        return Some(seconds);
+    }
+
+    let datetime = NaiveDateTime::parse_from_str(value, "%Y-%m-%d %H:%M:%S").ok()?;
+    let timestamp = Local.from_local_datetime(&datetime).earliest()?.timestamp();
+
+    u64::try_from(timestamp).ok()
+}
+
+// Ordinea: variabilele de mediu MY_SVN_<ROL>_{NAME,EMAIL,DATE}, apoi
+// user.name / user.email din config, apoi numele utilizatorului din sistem
+pub fn resolve_identity(root: &str, role: IdentityRole, now: u64) -> Result<Signature, ErrorData> {
+    let config = load_config(root)
+        .map_err(|e| error_data!("resolve_identity", e.to_string(), "Failed to load config"))?;
+
+    let prefix = role.env_prefix();
+    let from_env = |name: &str| {
+        env::var(format!("{}_{}", prefix, name))
+            .ok()
+            .filter(|v| !v.is_empty())
+    };
+
+    let name = match from_env("NAME")
+        .or_else(|| config.get("user.name").map(|v| v.to_string()))
+        .or_else(|| env::var("USER").or_else(|_| env::var("USERNAME")).ok())
+    {
+        Some(n) if !n.trim().is_empty() => n.trim().to_string(),
+        _ => {
+            return Err(error_data!(
+                "resolve_identity",
+                String::from(
+                    "Please tell me who you are: run `my_svn config user.name \"Your Name\"` and `my_svn config user.email you@example.com`"
+                ),
+                "Identity unknown"
+            ));
+        }
+    };
+
+    let email = from_env("EMAIL")
+        .or_else(|| config.get("user.email").map(|v| v.to_string()))
+        .unwrap_or_default()
+        .trim()
+        .to_string();
+
+    let timestamp = match from_env("DATE") {
+        Some(date) => match parse_identity_date(&date) {
+            Some(t) => t,
+            None => {
+                return Err(error_data!(
+                    "resolve_identity",
+                    format!("{}_DATE = {}", prefix, date),
+                    "Invalid date (expected seconds since epoch or YYYY-MM-DD HH:MM:SS)"
+                ));
+            }
+        },
+        None => now,
+    };
+
+    Ok(Signature {
+        name,
+        email,
+        timestamp,
+    })
+}
@@ -1,57 +1,135 @@
-use std::{collections::HashMap, fs};
+use std::{collections::HashMap, fs, path::Path};
 
 use crate::{
     error_data,
     utils::error::ErrorData,
+    utils::hash::calculate_hash,
+    utils::lock::{LockFile, write_locked},
     utils::path::{FileInfo, format_path},
 };
 
 use crate::utils::json::load_json;
-use crate::utils::path::RepoLocationError::*;
+
+// Hash-ul arborelui fiecarui director, dupa cale ("" este radacina)
+pub(crate) type TreeHashes = HashMap<String, String>;
 
 #[derive(serde::Serialize, serde::Deserialize)]
 pub struct IndexData {
     absolute_path: String,
     pub entries: HashMap<String, FileInfo>,
+    // Arborii directoarelor ale caror intrari nu s-au schimbat de la ultimul
+    // commit; `commit` scrie doar arborii care lipsesc
+    pub(crate) trees: TreeHashes,
+    // Intrarile de la citire, ca `save_index` sa stie ce directoare s-au schimbat
+    #[serde(skip)]
+    loaded: Option<HashMap<String, FileInfo>>,
+    #[serde(skip)]
+    lock: Option<LockFile>,
+}
+
+// Formatul fisierului `index`; indexurile scrise de versiunile vechi contin doar intrarile
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(untagged)]
+enum IndexFile {
+    Current {
+        entries: HashMap<String, FileInfo>,
+        #[serde(default)]
+        trees: TreeHashes,
+    },
+    Legacy(HashMap<String, FileInfo>),
+}
+
+// Directoarele care contin `path`, pana la radacina ("")
+pub(crate) fn parent_dirs(path: &str) -> impl Iterator<Item = &str> {
+    Path::new(path)
+        .ancestors()
+        .skip(1)
+        .filter_map(|p| p.to_str())
 }
 
 impl IndexData {
-    pub fn new() -> Result<Self, ErrorData> {
-        let repo_location = match crate::utils::path::find_repo_root(&String::from("./")) {
-            Ok(loc) => loc,
-            Err(e) => {
-                return Err(error_data!(
-                    "IndexData::new",
-                    match e {
-                        ErrorData(ed) => ed.to_string(),
-                        RepositoryNotFoundError => String::new(),
-                    },
-                    "Failed to find repository root"
-                ));
-            }
-        };
+    pub fn new(root: &str) -> Result<Self, ErrorData> {
+        Self::load(root, false)
+    }
+
+    // Indexul este blocat inainte de citire, ca doua comenzi care il modifica
+    // in paralel sa nu isi suprascrie una alteia schimbarile
+    pub fn lock(root: &str) -> Result<Self, ErrorData> {
+        Self::load(root, true)
+    }
 
-        let absolute_path = format_path(&vec![&repo_location.root, ".my_svn", "index"]);
+    fn load(root: &str, locked: bool) -> Result<Self, ErrorData> {
+        let absolute_path = format_path(&vec![root, ".my_svn", "index"]);
 
-        let entries: HashMap<String, FileInfo> = match load_json(&absolute_path) {
-            Ok(ent) => ent,
+        let lock = match locked {
+            true => Some(LockFile::acquire(&absolute_path).map_err(|e| {
+                error_data!("IndexData::load", e.to_string(), "Failed to lock index")
+            })?),
+            false => None,
+        };
+
+        let (entries, trees) = match load_json(&absolute_path) {
+            Ok(IndexFile::Current { entries, trees }) => (entries, trees),
+            Ok(IndexFile::Legacy(entries)) => (entries, HashMap::new()),
             Err(e) => {
                 return Err(error_data!(
-                    "IndexData::new",
+                    "IndexData::load",
                     e.to_string(),
                     "Failed to load index JSON data"
                 ));
             }
         };
 
+        // Doar un index blocat poate fi salvat, deci doar atunci conteaza ce s-a schimbat
+        let loaded = lock.as_ref().map(|_| entries.clone());
+
         Ok(Self {
             absolute_path,
             entries,
+            trees,
+            loaded,
+            lock,
         })
     }
 
-    pub fn save_index(self) -> Result<(), ErrorData> {
-        let json: String = match serde_json::to_string_pretty(&self.entries) {
+    // Arborii directoarelor in care o intrare a aparut, a disparut sau si-a
+    // schimbat continutul ori modul nu mai sunt valabili; data modificarii nu conteaza
+    fn invalidate_trees(&mut self) {
+        let loaded = match self.loaded.take() {
+            Some(l) => l,
+            None => {
+                self.trees.clear();
+                return;
+            }
+        };
+
+        let changed = self
+            .entries
+            .iter()
+            .filter(|(path, info)| !loaded.get(*path).is_some_and(|l| l.same_version(info)))
+            .map(|(path, _)| path)
+            .chain(
+                loaded
+                    .keys()
+                    .filter(|path| !self.entries.contains_key(*path)),
+            );
+
+        for path in changed {
+            for dir in parent_dirs(path) {
+                self.trees.remove(dir);
+            }
+        }
+    }
+
+    pub fn save_index(mut self) -> Result<(), ErrorData> {
+        self.invalidate_trees();
+
+        let file = IndexFile::Current {
+            entries: self.entries,
+            trees: self.trees,
+        };
+
+        let json: String = match serde_json::to_string_pretty(&file) {
             Ok(j) => j,
             Err(e) => {
                 return Err(error_data!(
@@ -62,7 +140,11 @@ impl IndexData {
             }
         };
 
-        std::fs::write(self.absolute_path, json).map_err(|e| {
+        match self.lock {
+            Some(lock) => lock.commit(json.as_bytes()),
+            None => write_locked(&self.absolute_path, json.as_bytes()),
+        }
+        .map_err(|e| {
             error_data!(
                 "IndexData::save_index",
                 e.to_string(),
@@ -72,6 +154,29 @@ impl IndexData {
 
         Ok(())
     }
+
+    // Hash-ul unui fisier din directorul de lucru; daca data modificarii este
+    // cea din index, hash-ul din index este refolosit fara citirea fisierului
+    pub fn working_hash(
+        &self,
+        root: &str,
+        path: &str,
+        file: &FileInfo,
+    ) -> Result<String, ErrorData> {
+        if let Some(staged) = self.entries.get(path)
+            && staged.timestamp == file.timestamp
+        {
+            return Ok(staged.hash.clone());
+        }
+
+        calculate_hash(root, &format_path(&vec![root, path])).map_err(|e| {
+            error_data!(
+                "IndexData::working_hash",
+                e.to_string(),
+                "Failed to calculate file hash"
+            )
+        })
+    }
 }
 
 pub fn get_svn_ignore(address: &str) -> Vec<String> {
@@ -104,3 +209,67 @@ pub fn ignore_file(path: &String, rules: &Vec<String>) -> bool {
 
     false
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::testing::TempRepo;
+
+    fn file(hash: &str, timestamp: u64) -> FileInfo {
+        FileInfo {
+            hash: hash.to_string(),
+            timestamp,
+            mode: Default::default(),
+        }
+    }
+
+    #[test]
+    fn changed_entries_drop_their_directory_trees() {
+        let repo = TempRepo::new();
+
+        let mut index = IndexData::lock(&repo.root).unwrap();
+        index.entries.insert(String::from("a/b/x"), file("1", 1));
+        index.entries.insert(String::from("a/y"), file("2", 1));
+        index.entries.insert(String::from("c/z"), file("3", 1));
+        index.save_index().unwrap();
+
+        // Ca dupa un commit: arborii sunt scrisi, intrarile raman aceleasi
+        let mut index = IndexData::lock(&repo.root).unwrap();
+        for dir in ["", "a", "a/b", "c"] {
+            index.trees.insert(dir.to_string(), format!("tree {}", dir));
+        }
+        index.save_index().unwrap();
+
+        let mut index = IndexData::lock(&repo.root).unwrap();
+        assert_eq!(index.trees.len(), 4);
+
+        // Doar data modificarii s-a schimbat: arborele ramane valabil
+        index.entries.insert(String::from("c/z"), file("3", 2));
+        index.entries.insert(String::from("a/b/x"), file("4", 1));
+        index.save_index().unwrap();
+
+        let index = IndexData::new(&repo.root).unwrap();
+        let mut dirs: Vec<&String> = index.trees.keys().collect();
+        dirs.sort();
+        assert_eq!(dirs, vec!["c"]);
+
+        let mut index = IndexData::lock(&repo.root).unwrap();
+        index.entries.remove("c/z");
+        index.save_index().unwrap();
+        assert!(IndexData::new(&repo.root).unwrap().trees.is_empty());
+    }
+
+    #[test]
+    fn reads_index_without_trees() {
+        let repo = TempRepo::new();
+        fs::write(
+            format_path(&vec![&repo.root, ".my_svn", "index"]),
+            r#"{"a": {"hash": "1", "timestamp": 5}}"#,
+        )
+        .unwrap();
+
+        let index = IndexData::new(&repo.root).unwrap();
+        assert_eq!(index.entries["a"].hash, "1");
+        assert!(index.trees.is_empty());
+    }
+}
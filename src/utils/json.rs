@@ -1,7 +1,7 @@
 use serde::de::DeserializeOwned;
 use std::{fs::File, io::Read};
 
-use crate::{error_data, utils::error::ErrorData};
+use crate::{error_data, utils::error::ErrorData, utils::object::read_object};
 
 pub fn load_json<T: DeserializeOwned>(path: &String) -> Result<T, ErrorData> {
     let mut file = match File::open(path) {
@@ -32,3 +32,26 @@ pub fn load_json<T: DeserializeOwned>(path: &String) -> Result<T, ErrorData> {
 
     Ok(value)
 }
+
+pub fn load_object_json<T: DeserializeOwned>(root: &str, hash: &str) -> Result<T, ErrorData> {
+    let data = read_object(root, hash).map_err(|e| {
+        error_data!(
+            "load_object_json",
+            e.to_string(),
+            "Failed to read JSON object"
+        )
+    })?;
+
+    let value = match serde_json::from_slice::<T>(&data) {
+        Ok(v) => v,
+        Err(e) => {
+            return Err(error_data!(
+                "load_object_json",
+                e.to_string(),
+                "Failed to parse JSON object"
+            ));
+        }
+    };
+
+    Ok(value)
+}
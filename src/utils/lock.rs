@@ -0,0 +1,290 @@
+use std::{
+    fs::{self, OpenOptions},
+    io::{ErrorKind, Write},
+    path::{Path, PathBuf},
+    sync::atomic::{AtomicUsize, Ordering},
+    time::{Duration, SystemTime},
+};
+
+use walkdir::WalkDir;
+
+use crate::{error_data, utils::error::ErrorData, utils::path::format_path};
+
+// Fisierul `<path>.lock` este creat exclusiv, continutul nou este scris in el
+// si apoi redenumit peste `path`, asa ca cititorii vad mereu fie versiunea
+// veche, fie pe cea noua. Daca lock-ul nu este confirmat, este sters la drop.
+pub struct LockFile {
+    path: String,
+    lock_path: String,
+    committed: bool,
+}
+
+impl LockFile {
+    pub fn acquire(path: &str) -> Result<Self, ErrorData> {
+        let lock_path = format!("{}.lock", path);
+
+        match OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .open(&lock_path)
+        {
+            Ok(_) => Ok(Self {
+                path: path.to_string(),
+                lock_path,
+                committed: false,
+            }),
+            Err(e) if e.kind() == ErrorKind::AlreadyExists => Err(error_data!(
+                "LockFile::acquire",
+                format!(
+                    "Unable to create '{}': File exists.\nAnother my_svn process seems to be running in this repository. \
+                     If no other process is running, a previous command crashed: run `my_svn unlock` to remove stale locks.",
+                    lock_path
+                ),
+                "Lock is held by another process"
+            )),
+            Err(e) => Err(error_data!(
+                "LockFile::acquire",
+                e.to_string(),
+                "Failed to create lock file"
+            )),
+        }
+    }
+
+    pub fn commit(mut self, content: &[u8]) -> Result<(), ErrorData> {
+        let mut file = OpenOptions::new()
+            .write(true)
+            .truncate(true)
+            .open(&self.lock_path)
+            .map_err(|e| {
+                error_data!(
+                    "LockFile::commit",
+                    e.to_string(),
+                    "Failed to open lock file"
+                )
+            })?;
+
+        file.write_all(content).map_err(|e| {
+            error_data!(
+                "LockFile::commit",
+                e.to_string(),
+                "Failed to write lock file"
+            )
+        })?;
+
+        file.sync_all().map_err(|e| {
+            error_data!(
+                "LockFile::commit",
+                e.to_string(),
+                "Failed to flush lock file"
+            )
+        })?;
+
+        drop(file);
+
+        fs::rename(&self.lock_path, &self.path).map_err(|e| {
+            error_data!(
+                "LockFile::commit",
+                e.to_string(),
+                "Failed to move lock file into place"
+            )
+        })?;
+
+        self.committed = true;
+
+        Ok(())
+    }
+}
+
+impl Drop for LockFile {
+    fn drop(&mut self) {
+        if !self.committed {
+            let _ = fs::remove_file(&self.lock_path);
+        }
+    }
+}
+
+pub fn write_locked(path: &str, content: &[u8]) -> Result<(), ErrorData> {
+    let lock = LockFile::acquire(path)
+        .map_err(|e| error_data!("write_locked", e.to_string(), "Failed to acquire lock"))?;
+
+    lock.commit(content).map_err(|e| {
+        error_data!(
+            "write_locked",
+            e.to_string(),
+            "Failed to commit locked write"
+        )
+    })
+}
+
+static NEXT_TEMP_ID: AtomicUsize = AtomicUsize::new(0);
+
+// Un nume unic in `dir` pentru un fisier care va fi redenumit la final
+pub(crate) fn temp_path(dir: &Path) -> PathBuf {
+    dir.join(format!(
+        "tmp_{}_{}",
+        std::process::id(),
+        NEXT_TEMP_ID.fetch_add(1, Ordering::SeqCst)
+    ))
+}
+
+// Pentru fisierele scrise fara lock (obiecte, pack-uri): continutul ajunge
+// intr-un fisier temporar din acelasi director, apoi este redenumit, asa ca un
+// fisier pe jumatate scris nu apare niciodata sub numele final. Numele
+// temporar nu este hexazecimal si nu se termina in `.idx`, deci nu este luat
+// drept obiect sau pack.
+pub fn write_atomic(path: &str, content: &[u8]) -> Result<(), ErrorData> {
+    let temp_path = temp_path(Path::new(path).parent().unwrap_or(Path::new(".")));
+
+    let result = (|| {
+        let mut file = OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .open(&temp_path)?;
+        file.write_all(content)?;
+        file.sync_all()?;
+        drop(file);
+        fs::rename(&temp_path, path)
+    })();
+
+    result.map_err(|e| {
+        let _ = fs::remove_file(&temp_path);
+        error_data!(
+            "write_atomic",
+            format!("{}: {}", path, e),
+            "Failed to write file through a temporary file"
+        )
+    })
+}
+
+// Un lock mai nou de atat poate apartine unei comenzi care inca ruleaza
+pub const STALE_LOCK_AGE: Duration = Duration::from_secs(10 * 60);
+
+#[derive(Default)]
+pub struct LockCleanup {
+    pub removed: Vec<String>,
+    // Lock-uri recente, lasate pe loc, cu varsta lor
+    pub kept: Vec<(String, Duration)>,
+}
+
+// Sterge lock-urile ramase de la comenzi intrerupte (index, HEAD, refs). Fara
+// `force` sunt sterse doar cele mai vechi de STALE_LOCK_AGE.
+pub fn remove_stale_locks(root: &str, force: bool) -> Result<LockCleanup, ErrorData> {
+    let mut cleanup = LockCleanup::default();
+    let svn_dir = format_path(&vec![root, ".my_svn"]);
+
+    for entry in WalkDir::new(&svn_dir)
+        .into_iter()
+        .filter_entry(|e| e.file_name() != "objects")
+        .filter_map(|e| e.ok())
+    {
+        let path = entry.path();
+
+        if !path.is_file() || path.extension().is_none_or(|ext| ext != "lock") {
+            continue;
+        }
+
+        let name = match Path::new(path).strip_prefix(&svn_dir) {
+            Ok(p) => p,
+            Err(_) => path,
+        }
+        .to_string_lossy()
+        .to_string();
+
+        // Un lock a carui varsta nu poate fi citita este tratat ca fiind in uz
+        let age = fs::metadata(path)
+            .and_then(|m| m.modified())
+            .ok()
+            .and_then(|t| SystemTime::now().duration_since(t).ok())
+            .unwrap_or_default();
+
+        if !force && age < STALE_LOCK_AGE {
+            cleanup.kept.push((name, age));
+            continue;
+        }
+
+        fs::remove_file(path).map_err(|e| {
+            error_data!(
+                "remove_stale_locks",
+                e.to_string(),
+                "Failed to remove lock file"
+            )
+        })?;
+
+        cleanup.removed.push(name);
+    }
+
+    cleanup.removed.sort();
+    cleanup.kept.sort();
+    Ok(cleanup)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::testing::TempRepo;
+
+    fn age_file(path: &str, age: Duration) {
+        fs::File::options()
+            .write(true)
+            .open(path)
+            .unwrap()
+            .set_modified(SystemTime::now() - age)
+            .unwrap();
+    }
+
+    #[test]
+    fn unlock_keeps_recent_locks() {
+        let repo = TempRepo::new();
+        let index_lock = repo.path(".my_svn/index.lock");
+        let head_lock = repo.path(".my_svn/HEAD.lock");
+        fs::write(&index_lock, "").unwrap();
+        fs::write(&head_lock, "").unwrap();
+        age_file(&head_lock, STALE_LOCK_AGE + Duration::from_secs(60));
+
+        let cleanup = remove_stale_locks(&repo.root, false).unwrap();
+        assert_eq!(cleanup.removed, vec![String::from("HEAD.lock")]);
+        assert_eq!(cleanup.kept.len(), 1);
+        assert_eq!(cleanup.kept[0].0, "index.lock");
+        assert!(Path::new(&index_lock).exists());
+        assert!(!Path::new(&head_lock).exists());
+
+        let cleanup = remove_stale_locks(&repo.root, true).unwrap();
+        assert_eq!(cleanup.removed, vec![String::from("index.lock")]);
+        assert!(!Path::new(&index_lock).exists());
+    }
+
+    #[test]
+    fn held_lock_blocks_other_writers() {
+        let repo = TempRepo::new();
+        let path = repo.path(".my_svn/HEAD");
+
+        let lock = LockFile::acquire(&path).unwrap();
+        assert!(LockFile::acquire(&path).is_err());
+        assert!(
+            remove_stale_locks(&repo.root, false)
+                .unwrap()
+                .removed
+                .is_empty()
+        );
+
+        lock.commit(b"other").unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), "other");
+    }
+
+    #[test]
+    fn write_atomic_replaces_file_without_leftovers() {
+        let repo = TempRepo::new();
+        let path = repo.path("data");
+
+        write_atomic(&path, b"first").unwrap();
+        write_atomic(&path, b"second").unwrap();
+
+        assert_eq!(fs::read_to_string(&path).unwrap(), "second");
+        let leftovers: Vec<_> = fs::read_dir(&repo.root)
+            .unwrap()
+            .filter_map(|e| e.ok())
+            .filter(|e| e.file_name().to_string_lossy().starts_with("tmp_"))
+            .collect();
+        assert!(leftovers.is_empty());
+    }
+}
@@ -0,0 +1,740 @@
+use flate2::{Compression, read::ZlibDecoder, write::ZlibEncoder};
+use std::{
+    fs::{self, OpenOptions},
+    io::{Read, Write},
+    path::{Path, PathBuf},
+};
+
+use crate::{
+    error_data,
+    utils::{
+        error::ErrorData,
+        hash::{ObjectHasher, check_object_name, hash_bytes, object_format},
+        lock::{temp_path, write_atomic},
+        pack::{packed_object_exists, read_packed_object},
+        path::{FileMode, format_path, symlink_target},
+    },
+};
+
+pub fn objects_dir(root: &str) -> String {
+    format_path(&vec![root, ".my_svn", "objects"])
+}
+
+// objects/ab/cdef... - primele doua caractere ale hash-ului dau subdirectorul
+pub fn object_path(root: &str, hash: &str) -> String {
+    if hash.len() <= 2 {
+        return flat_object_path(root, hash);
+    }
+
+    format_path(&vec![root, ".my_svn", "objects", &hash[..2], &hash[2..]])
+}
+
+fn flat_object_path(root: &str, hash: &str) -> String {
+    format_path(&vec![root, ".my_svn", "objects", hash])
+}
+
+// Depozitele vechi tin obiectele direct in objects/, asa ca se cauta si acolo
+fn existing_object_path(root: &str, hash: &str) -> Option<String> {
+    let path = object_path(root, hash);
+    if Path::new(&path).is_file() {
+        return Some(path);
+    }
+
+    let flat_path = flat_object_path(root, hash);
+    if Path::new(&flat_path).is_file() {
+        return Some(flat_path);
+    }
+
+    None
+}
+
+pub fn object_exists(root: &str, hash: &str) -> bool {
+    packed_object_exists(root, hash) || existing_object_path(root, hash).is_some()
+}
+
+fn create_object_dir(root: &str, hash: &str) -> Result<(), ErrorData> {
+    if let Some(parent) = Path::new(&object_path(root, hash)).parent() {
+        fs::create_dir_all(parent).map_err(|e| {
+            error_data!(
+                "create_object_dir",
+                e.to_string(),
+                "Failed to create object directory"
+            )
+        })?;
+    }
+
+    Ok(())
+}
+
+pub(crate) fn compress(data: &[u8]) -> Result<Vec<u8>, ErrorData> {
+    let mut encoder = ZlibEncoder::new(Vec::new(), Compression::default());
+
+    encoder
+        .write_all(data)
+        .map_err(|e| error_data!("compress", e.to_string(), "Failed to compress object data"))?;
+
+    encoder
+        .finish()
+        .map_err(|e| error_data!("compress", e.to_string(), "Failed to finish compression"))
+}
+
+pub(crate) fn decompress(data: &[u8]) -> Option<Vec<u8>> {
+    let mut decoded = Vec::new();
+
+    match ZlibDecoder::new(data).read_to_end(&mut decoded) {
+        Ok(_) => Some(decoded),
+        Err(_) => None,
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ObjectType {
+    Blob,
+    Tree,
+    Commit,
+    Tag,
+}
+
+impl ObjectType {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ObjectType::Blob => "blob",
+            ObjectType::Tree => "tree",
+            ObjectType::Commit => "commit",
+            ObjectType::Tag => "tag",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "blob" => Some(ObjectType::Blob),
+            "tree" => Some(ObjectType::Tree),
+            "commit" => Some(ObjectType::Commit),
+            "tag" => Some(ObjectType::Tag),
+            _ => None,
+        }
+    }
+}
+
+fn object_header(object_type: ObjectType, size: u64) -> Vec<u8> {
+    format!("{} {}\0", object_type.as_str(), size).into_bytes()
+}
+
+// Antetul are forma "<tip> <dimensiune>\0"; se accepta doar daca dimensiunea
+// coincide cu restul datelor, altfel obiectul este considerat fara antet
+fn split_header(data: &[u8]) -> Option<(ObjectType, usize)> {
+    let nul = data.iter().take(32).position(|&b| b == 0)?;
+    let header = std::str::from_utf8(&data[..nul]).ok()?;
+    let (type_name, size) = header.split_once(' ')?;
+
+    let object_type = ObjectType::from_name(type_name)?;
+    let size: usize = size.parse().ok()?;
+
+    if size != data.len() - nul - 1 {
+        return None;
+    }
+
+    Some((object_type, nul + 1))
+}
+
+// Obiectele scrise inainte de antete nu isi cunosc tipul, asa ca il deducem din continut
+fn infer_object_type(data: &[u8]) -> ObjectType {
+    match serde_json::from_slice::<serde_json::Value>(data) {
+        Ok(serde_json::Value::Object(map)) if map.contains_key("tree") => ObjectType::Commit,
+        Ok(serde_json::Value::Array(entries))
+            if entries.iter().all(|e| e.get("data_type").is_some()) =>
+        {
+            ObjectType::Tree
+        }
+        _ => ObjectType::Blob,
+    }
+}
+
+fn parse_object(data: Vec<u8>) -> (ObjectType, Vec<u8>) {
+    match split_header(&data) {
+        Some((object_type, start)) => (object_type, data[start..].to_vec()),
+        None => (infer_object_type(&data), data),
+    }
+}
+
+// Obiectele sunt scrise mereu comprimate (zlib), cu antetul de tip in fata.
+// Hash-ul (numele obiectului) se calculeaza doar pe continut, fara antet.
+pub fn write_object(
+    root: &str,
+    hash: &str,
+    object_type: ObjectType,
+    data: &[u8],
+) -> Result<(), ErrorData> {
+    if check_existing_type(root, hash, object_type)
+        .map_err(|e| error_data!("write_object", e.to_string(), "Refusing to write object"))?
+    {
+        return Ok(());
+    }
+
+    store_object(root, hash, object_type, data)
+}
+
+// Scrie obiectul fara verificari, inlocuind un fisier existent (folosit si de migrare)
+fn store_object(
+    root: &str,
+    hash: &str,
+    object_type: ObjectType,
+    data: &[u8],
+) -> Result<(), ErrorData> {
+    let mut content = object_header(object_type, data.len() as u64);
+    content.extend_from_slice(data);
+
+    let compressed = compress(&content)
+        .map_err(|e| error_data!("store_object", e.to_string(), "Failed to compress object"))?;
+
+    create_object_dir(root, hash).map_err(|e| {
+        error_data!(
+            "store_object",
+            e.to_string(),
+            "Failed to prepare object path"
+        )
+    })?;
+
+    write_atomic(&object_path(root, hash), &compressed).map_err(|e| {
+        error_data!(
+            "store_object",
+            e.to_string(),
+            "Failed to write object to file"
+        )
+    })?;
+
+    Ok(())
+}
+
+// Numele nu include tipul, asa ca un blob poate avea acelasi continut (si
+// acelasi nume) ca un arbore sau un commit. Obiectul existent nu este inlocuit
+// cu unul de alt tip; `true` inseamna ca exista deja cu tipul cerut.
+fn check_existing_type(root: &str, hash: &str, object_type: ObjectType) -> Result<bool, ErrorData> {
+    if !object_exists(root, hash) {
+        return Ok(false);
+    }
+
+    let (existing, _) = read_typed_object(root, hash).map_err(|e| {
+        error_data!(
+            "check_existing_type",
+            e.to_string(),
+            "Failed to read existing object"
+        )
+    })?;
+
+    if existing != object_type {
+        return Err(error_data!(
+            "check_existing_type",
+            format!(
+                "{} is already stored as a {}, not a {}",
+                hash,
+                existing.as_str(),
+                object_type.as_str()
+            ),
+            "Object name is used by an object of another type"
+        ));
+    }
+
+    Ok(true)
+}
+
+// Fisierul este citit o singura data: aceiasi octeti sunt hash-uiti si
+// comprimati intr-un fisier temporar, redenumit apoi dupa hash-ul obtinut
+pub fn write_object_from_file(root: &str, src: &str) -> Result<String, ErrorData> {
+    if let Some(target) = symlink_target(src) {
+        let hash = hash_bytes(root, &target).map_err(|e| {
+            error_data!(
+                "write_object_from_file",
+                e.to_string(),
+                "Failed to hash symlink target"
+            )
+        })?;
+
+        write_object(root, &hash, ObjectType::Blob, &target).map_err(|e| {
+            error_data!(
+                "write_object_from_file",
+                e.to_string(),
+                "Failed to write symlink object"
+            )
+        })?;
+
+        return Ok(hash);
+    }
+
+    let temp = temp_path(Path::new(&objects_dir(root)));
+
+    let hash = compress_file(root, src, &temp).inspect_err(|_| {
+        let _ = fs::remove_file(&temp);
+    })?;
+
+    // Continutul exista deja (scris de alta comanda sau din alt fisier)
+    match check_existing_type(root, &hash, ObjectType::Blob) {
+        Ok(false) => {}
+        result => {
+            let _ = fs::remove_file(&temp);
+            return result.map(|_| hash).map_err(|e| {
+                error_data!(
+                    "write_object_from_file",
+                    e.to_string(),
+                    "Refusing to write object"
+                )
+            });
+        }
+    }
+
+    create_object_dir(root, &hash)
+        .and_then(|_| {
+            fs::rename(&temp, object_path(root, &hash)).map_err(|e| {
+                error_data!(
+                    "write_object_from_file",
+                    e.to_string(),
+                    "Failed to move object into place"
+                )
+            })
+        })
+        .inspect_err(|_| {
+            let _ = fs::remove_file(&temp);
+        })?;
+
+    Ok(hash)
+}
+
+fn compress_file(root: &str, src: &str, temp: &Path) -> Result<String, ErrorData> {
+    let algorithm = object_format(root).map_err(|e| {
+        error_data!(
+            "compress_file",
+            e.to_string(),
+            "Failed to get repository object format"
+        )
+    })?;
+
+    let file = fs::File::open(src)
+        .map_err(|e| error_data!("compress_file", e.to_string(), "Failed to open source file"))?;
+
+    let size = file
+        .metadata()
+        .map_err(|e| {
+            error_data!(
+                "compress_file",
+                e.to_string(),
+                "Failed to get source file metadata"
+            )
+        })?
+        .len();
+
+    let dest = OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .open(temp)
+        .map_err(|e| {
+            error_data!(
+                "compress_file",
+                e.to_string(),
+                "Failed to create temporary object file"
+            )
+        })?;
+
+    let mut encoder = ZlibEncoder::new(dest, Compression::default());
+
+    encoder
+        .write_all(&object_header(ObjectType::Blob, size))
+        .map_err(|e| {
+            error_data!(
+                "compress_file",
+                e.to_string(),
+                "Failed to write object header"
+            )
+        })?;
+
+    // Antetul a fost scris cu dimensiunea de la deschidere; se citesc cel mult
+    // atatia octeti, iar un fisier micsorat intre timp este refuzat
+    let mut reader = file.take(size);
+    let mut hasher = ObjectHasher::new(algorithm);
+    let mut buffer = vec![0u8; 64 * 1024];
+    let mut read_total: u64 = 0;
+
+    loop {
+        let read_bytes = reader.read(&mut buffer).map_err(|e| {
+            error_data!("compress_file", e.to_string(), "Failed to read source file")
+        })?;
+
+        if read_bytes == 0 {
+            break;
+        }
+
+        hasher.update(&buffer[..read_bytes]);
+        encoder.write_all(&buffer[..read_bytes]).map_err(|e| {
+            error_data!(
+                "compress_file",
+                e.to_string(),
+                "Failed to compress file into object"
+            )
+        })?;
+        read_total += read_bytes as u64;
+    }
+
+    if read_total != size {
+        return Err(error_data!(
+            "compress_file",
+            format!("{}: expected {} bytes, read {}", src, size, read_total),
+            "File changed while it was being stored"
+        ));
+    }
+
+    encoder
+        .finish()
+        .and_then(|dest| dest.sync_all())
+        .map_err(|e| {
+            error_data!(
+                "compress_file",
+                e.to_string(),
+                "Failed to finish object compression"
+            )
+        })?;
+
+    Ok(hasher.finalize_hex())
+}
+
+// Depozitele vechi pot contine inca obiecte necomprimate; daca datele nu sunt
+// un flux zlib valid, sunt returnate asa cum sunt
+pub(crate) fn read_loose_object_file(path: &Path) -> Result<(ObjectType, Vec<u8>), ErrorData> {
+    let raw = fs::read(path).map_err(|e| {
+        error_data!(
+            "read_loose_object_file",
+            e.to_string(),
+            "Failed to read object file"
+        )
+    })?;
+
+    Ok(parse_object(decompress(&raw).unwrap_or(raw)))
+}
+
+// Pack-urile sunt consultate inaintea obiectelor libere
+pub fn read_typed_object(root: &str, hash: &str) -> Result<(ObjectType, Vec<u8>), ErrorData> {
+    check_object_name(root, hash).map_err(|e| {
+        error_data!(
+            "read_typed_object",
+            e.to_string(),
+            "Refusing to read object"
+        )
+    })?;
+
+    if let Some(object) = read_packed_object(root, hash).map_err(|e| {
+        error_data!(
+            "read_typed_object",
+            e.to_string(),
+            "Failed to read packed object"
+        )
+    })? {
+        return Ok(object);
+    }
+
+    let path = match existing_object_path(root, hash) {
+        Some(p) => p,
+        None => {
+            return Err(error_data!(
+                "read_typed_object",
+                format!("Object {} not found", hash),
+                "Failed to find object file"
+            ));
+        }
+    };
+
+    read_loose_object_file(Path::new(&path)).map_err(|e| {
+        error_data!(
+            "read_typed_object",
+            e.to_string(),
+            "Failed to read loose object"
+        )
+    })
+}
+
+pub fn read_object(root: &str, hash: &str) -> Result<Vec<u8>, ErrorData> {
+    let (_, data) = read_typed_object(root, hash)
+        .map_err(|e| error_data!("read_object", e.to_string(), "Failed to read object"))?;
+
+    Ok(data)
+}
+
+pub fn restore_object(root: &str, hash: &str, dest: &str) -> Result<(), ErrorData> {
+    let data = read_object(root, hash)
+        .map_err(|e| error_data!("restore_object", e.to_string(), "Failed to read object"))?;
+
+    fs::write(dest, data).map_err(|e| {
+        error_data!(
+            "restore_object",
+            e.to_string(),
+            "Failed to write object content to file"
+        )
+    })?;
+
+    Ok(())
+}
+
+// Refac fisierul cu modul din arbore; un symlink existent la `dest` este
+// sters inainte, altfel scrierea ar ajunge in fisierul spre care trimite
+pub fn restore_file(root: &str, hash: &str, dest: &str, mode: FileMode) -> Result<(), ErrorData> {
+    let replaces_link = fs::symlink_metadata(dest)
+        .map(|m| m.file_type().is_symlink())
+        .unwrap_or(false);
+
+    if replaces_link || (mode == FileMode::Symlink && Path::new(dest).exists()) {
+        fs::remove_file(dest).map_err(|e| {
+            error_data!(
+                "restore_file",
+                e.to_string(),
+                "Failed to remove existing file"
+            )
+        })?;
+    }
+
+    if mode == FileMode::Symlink {
+        let data = read_object(root, hash)
+            .map_err(|e| error_data!("restore_file", e.to_string(), "Failed to read object"))?;
+
+        return create_symlink(&String::from_utf8_lossy(&data), dest)
+            .map_err(|e| error_data!("restore_file", e.to_string(), "Failed to create symlink"));
+    }
+
+    restore_object(root, hash, dest)
+        .map_err(|e| error_data!("restore_file", e.to_string(), "Failed to restore file"))?;
+
+    apply_mode(dest, mode)
+        .map_err(|e| error_data!("restore_file", e.to_string(), "Failed to set file mode"))
+}
+
+#[cfg(unix)]
+fn create_symlink(target: &str, dest: &str) -> Result<(), ErrorData> {
+    std::os::unix::fs::symlink(target, dest)
+        .map_err(|e| error_data!("create_symlink", e.to_string(), "Failed to create symlink"))
+}
+
+// Pe Windows crearea de symlink-uri poate cere drepturi speciale; in lipsa
+// lor legatura este scrisa ca fisier obisnuit ce contine calea tinta
+#[cfg(not(unix))]
+fn create_symlink(target: &str, dest: &str) -> Result<(), ErrorData> {
+    if std::os::windows::fs::symlink_file(target, dest).is_ok() {
+        return Ok(());
+    }
+
+    fs::write(dest, target).map_err(|e| {
+        error_data!(
+            "create_symlink",
+            e.to_string(),
+            "Failed to write symlink placeholder"
+        )
+    })
+}
+
+// Pastreaza bitii de citire/scriere si seteaza doar bitii de executie
+pub fn apply_mode(path: &str, mode: FileMode) -> Result<(), ErrorData> {
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+
+        if mode == FileMode::Symlink {
+            return Ok(());
+        }
+
+        let mut permissions = fs::metadata(path)
+            .map_err(|e| error_data!("apply_mode", e.to_string(), "Failed to get file metadata"))?
+            .permissions();
+
+        let current = permissions.mode();
+        let updated = match mode {
+            // Executia se acorda acolo unde exista deja drept de citire
+            FileMode::Executable => current | ((current & 0o444) >> 2),
+            _ => current & !0o111,
+        };
+
+        if updated != current {
+            permissions.set_mode(updated);
+            fs::set_permissions(path, permissions).map_err(|e| {
+                error_data!(
+                    "apply_mode",
+                    e.to_string(),
+                    "Failed to set file permissions"
+                )
+            })?;
+        }
+    }
+
+    #[cfg(not(unix))]
+    let _ = (path, mode);
+
+    Ok(())
+}
+
+// Lista tuturor obiectelor libere, din ambele organizari ale directorului objects/
+pub fn list_loose_objects(root: &str) -> Result<Vec<(String, PathBuf)>, ErrorData> {
+    let mut objects: Vec<(String, PathBuf)> = Vec::new();
+
+    let entries = fs::read_dir(objects_dir(root)).map_err(|e| {
+        error_data!(
+            "list_loose_objects",
+            e.to_string(),
+            "Failed to read objects directory"
+        )
+    })?;
+
+    for entry in entries {
+        let entry = entry.map_err(|e| {
+            error_data!(
+                "list_loose_objects",
+                e.to_string(),
+                "Failed to read objects directory entry"
+            )
+        })?;
+
+        let name = entry.file_name().to_string_lossy().to_string();
+        if !name.chars().all(|c| c.is_ascii_hexdigit()) {
+            continue;
+        }
+
+        let path = entry.path();
+
+        if path.is_file() {
+            objects.push((name, path));
+        } else if path.is_dir() && name.len() == 2 {
+            let sub_entries = fs::read_dir(&path).map_err(|e| {
+                error_data!(
+                    "list_loose_objects",
+                    e.to_string(),
+                    "Failed to read objects subdirectory"
+                )
+            })?;
+
+            for sub_entry in sub_entries {
+                let sub_entry = sub_entry.map_err(|e| {
+                    error_data!(
+                        "list_loose_objects",
+                        e.to_string(),
+                        "Failed to read objects subdirectory entry"
+                    )
+                })?;
+
+                let sub_name = sub_entry.file_name().to_string_lossy().to_string();
+                if sub_entry.path().is_file() && sub_name.chars().all(|c| c.is_ascii_hexdigit()) {
+                    objects.push((format!("{}{}", name, sub_name), sub_entry.path()));
+                }
+            }
+        }
+    }
+
+    Ok(objects)
+}
+
+// Comprima toate obiectele vechi, le adauga antetul de tip si le muta din objects/
+// in subdirectoarele lor. Un obiect este considerat deja migrat doar daca se
+// decomprima la un continut cu antet al carui hash este chiar numele lui,
+// deci comanda poate fi rulata de mai multe ori.
+pub fn migrate_objects(root: &str) -> Result<usize, ErrorData> {
+    let mut migrated = 0;
+
+    for (hash, path) in list_loose_objects(root)
+        .map_err(|e| error_data!("migrate_objects", e.to_string(), "Failed to list objects"))?
+    {
+        let raw = fs::read(&path).map_err(|e| {
+            error_data!(
+                "migrate_objects",
+                e.to_string(),
+                "Failed to read object file"
+            )
+        })?;
+
+        let is_fan_out = path.to_string_lossy() == object_path(root, &hash);
+
+        let payload = match decompress(&raw) {
+            Some(decoded) => {
+                let has_header = split_header(&decoded).is_some();
+                let (object_type, data) = parse_object(decoded);
+
+                let data_hash = hash_bytes(root, &data).map_err(|e| {
+                    error_data!("migrate_objects", e.to_string(), "Failed to hash object")
+                })?;
+
+                if data_hash == hash {
+                    if is_fan_out && has_header {
+                        continue;
+                    }
+
+                    (object_type, data)
+                } else {
+                    (infer_object_type(&raw), raw)
+                }
+            }
+            None => (infer_object_type(&raw), raw),
+        };
+
+        store_object(root, &hash, payload.0, &payload.1).map_err(|e| {
+            error_data!("migrate_objects", e.to_string(), "Failed to rewrite object")
+        })?;
+
+        if !is_fan_out {
+            fs::remove_file(&path).map_err(|e| {
+                error_data!(
+                    "migrate_objects",
+                    e.to_string(),
+                    "Failed to remove old object file"
+                )
+            })?;
+        }
+
+        migrated += 1;
+    }
+
+    Ok(migrated)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::testing::TempRepo;
+
+    #[test]
+    fn file_object_is_named_after_the_bytes_it_holds() {
+        let repo = TempRepo::new();
+        repo.write("big.txt", &"line of text\n".repeat(20_000));
+
+        let hash = write_object_from_file(&repo.root, &repo.path("big.txt")).unwrap();
+        let content = fs::read(repo.path("big.txt")).unwrap();
+        assert_eq!(hash, hash_bytes(&repo.root, &content).unwrap());
+        assert_eq!(
+            read_typed_object(&repo.root, &hash).unwrap(),
+            (ObjectType::Blob, content)
+        );
+
+        // A doua scriere a aceluiasi continut nu lasa fisiere temporare
+        assert_eq!(
+            write_object_from_file(&repo.root, &repo.path("big.txt")).unwrap(),
+            hash
+        );
+        let leftovers: Vec<_> = fs::read_dir(objects_dir(&repo.root))
+            .unwrap()
+            .filter_map(|e| e.ok())
+            .filter(|e| e.file_name().to_string_lossy().starts_with("tmp_"))
+            .collect();
+        assert!(leftovers.is_empty());
+    }
+
+    #[test]
+    fn blob_cannot_take_over_a_tree_name() {
+        let repo = TempRepo::new();
+        let tree = br#"[{"name":"a.txt","hash":"x","data_type":"File"}]"#;
+        let hash = hash_bytes(&repo.root, tree).unwrap();
+
+        write_object(&repo.root, &hash, ObjectType::Tree, tree).unwrap();
+        // Acelasi obiect scris din nou nu este o eroare
+        write_object(&repo.root, &hash, ObjectType::Tree, tree).unwrap();
+
+        assert!(write_object(&repo.root, &hash, ObjectType::Blob, tree).is_err());
+
+        repo.write("tree.json", std::str::from_utf8(tree).unwrap());
+        assert!(write_object_from_file(&repo.root, &repo.path("tree.json")).is_err());
+
+        assert_eq!(
+            read_typed_object(&repo.root, &hash).unwrap().0,
+            ObjectType::Tree
+        );
+    }
+}
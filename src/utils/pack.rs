@@ -0,0 +1,853 @@
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::{HashMap, HashSet},
+    fs,
+    io::{Read, Seek, SeekFrom},
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex},
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use crate::{
+    error_data,
+    utils::{
+        delta::{apply_delta, create_delta},
+        error::ErrorData,
+        hash::hash_bytes,
+        json::load_json,
+        lock::write_atomic,
+        object::{ObjectType, compress, decompress, list_loose_objects, read_loose_object_file},
+        path::format_path,
+    },
+};
+
+const PACK_SIGNATURE: &[u8; 8] = b"MSVNPACK";
+const PACK_VERSION: u32 = 1;
+
+const ENTRY_FULL: u8 = 0;
+const ENTRY_DELTA: u8 = 1;
+
+// Cate obiecte anterioare (de acelasi tip) sunt incercate ca baza pentru delta
+const DELTA_WINDOW: usize = 10;
+const MAX_DELTA_DEPTH: usize = 10;
+
+#[derive(Serialize, Deserialize)]
+struct PackIndexFile {
+    version: u32,
+    objects: HashMap<String, u64>,
+    // Data la care a fost scris fiecare obiect (secunde de la UNIX epoch); se
+    // pastreaza la fiecare repack, ca `gc --prune` sa nu vada obiectele intinerite.
+    // Indexurile vechi nu o au si folosesc data fisierului pack.
+    #[serde(default)]
+    mtimes: HashMap<String, u64>,
+}
+
+struct PackIndex {
+    pack_path: String,
+    objects: HashMap<String, u64>,
+    mtimes: HashMap<String, u64>,
+}
+
+pub struct RepackStats {
+    pub objects: usize,
+    pub deltas: usize,
+}
+
+struct CachedPacks {
+    root: String,
+    // Data modificarii directorului `pack` la citire; un pack scris sau sters
+    // (si de alt proces) o schimba, iar indexurile sunt citite din nou
+    dir_mtime: Option<SystemTime>,
+    packs: Arc<Vec<PackIndex>>,
+}
+
+// Indexurile pack-urilor sunt tinute in memorie cat timp directorul `pack` nu se schimba
+static PACK_CACHE: Mutex<Vec<CachedPacks>> = Mutex::new(Vec::new());
+
+pub fn pack_dir(root: &str) -> String {
+    format_path(&vec![root, ".my_svn", "objects", "pack"])
+}
+
+fn type_to_byte(object_type: ObjectType) -> u8 {
+    match object_type {
+        ObjectType::Blob => 1,
+        ObjectType::Tree => 2,
+        ObjectType::Commit => 3,
+        ObjectType::Tag => 4,
+    }
+}
+
+fn byte_to_type(byte: u8) -> Option<ObjectType> {
+    match byte {
+        1 => Some(ObjectType::Blob),
+        2 => Some(ObjectType::Tree),
+        3 => Some(ObjectType::Commit),
+        4 => Some(ObjectType::Tag),
+        _ => None,
+    }
+}
+
+fn load_packs_uncached(root: &str) -> Result<Vec<PackIndex>, ErrorData> {
+    let mut packs: Vec<PackIndex> = Vec::new();
+
+    let dir = pack_dir(root);
+    if !Path::new(&dir).is_dir() {
+        return Ok(packs);
+    }
+
+    let entries = fs::read_dir(&dir).map_err(|e| {
+        error_data!(
+            "load_packs_uncached",
+            e.to_string(),
+            "Failed to read pack directory"
+        )
+    })?;
+
+    for entry in entries {
+        let entry = entry.map_err(|e| {
+            error_data!(
+                "load_packs_uncached",
+                e.to_string(),
+                "Failed to read pack directory entry"
+            )
+        })?;
+
+        let idx_path = entry.path().to_string_lossy().to_string();
+        let pack_path = match idx_path.strip_suffix(".idx") {
+            Some(p) => format!("{}.pack", p),
+            None => continue,
+        };
+
+        if !Path::new(&pack_path).is_file() {
+            continue;
+        }
+
+        let index: PackIndexFile = load_json(&idx_path).map_err(|e| {
+            error_data!(
+                "load_packs_uncached",
+                e.to_string(),
+                "Failed to load pack index"
+            )
+        })?;
+
+        packs.push(PackIndex {
+            pack_path,
+            objects: index.objects,
+            mtimes: index.mtimes,
+        });
+    }
+
+    Ok(packs)
+}
+
+fn load_packs(root: &str) -> Result<Arc<Vec<PackIndex>>, ErrorData> {
+    let mut cache = match PACK_CACHE.lock() {
+        Ok(c) => c,
+        Err(e) => {
+            return Err(error_data!(
+                "load_packs",
+                e.to_string(),
+                "Failed to lock pack cache"
+            ));
+        }
+    };
+
+    let dir_mtime = fs::metadata(pack_dir(root)).and_then(|m| m.modified()).ok();
+
+    if let Some(cached) = cache.iter().find(|c| c.root == root)
+        && cached.dir_mtime == dir_mtime
+    {
+        return Ok(cached.packs.clone());
+    }
+
+    let packs =
+        Arc::new(load_packs_uncached(root).map_err(|e| {
+            error_data!("load_packs", e.to_string(), "Failed to load pack indexes")
+        })?);
+
+    cache.retain(|c| c.root != root);
+    cache.push(CachedPacks {
+        root: root.to_string(),
+        dir_mtime,
+        packs: packs.clone(),
+    });
+
+    Ok(packs)
+}
+
+pub fn invalidate_pack_cache(root: &str) {
+    if let Ok(mut cache) = PACK_CACHE.lock() {
+        cache.retain(|c| c.root != root);
+    }
+}
+
+pub fn packed_object_exists(root: &str, hash: &str) -> bool {
+    match load_packs(root) {
+        Ok(packs) => packs.iter().any(|p| p.objects.contains_key(hash)),
+        Err(_) => false,
+    }
+}
+
+fn to_unix_secs(time: SystemTime) -> u64 {
+    time.duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default()
+}
+
+// Data obiectului din index, nu cea a fisierului pack (care este rescris de
+// fiecare `repack` si `gc`)
+pub fn packed_object_mtime(root: &str, hash: &str) -> Option<SystemTime> {
+    let packs = load_packs(root).ok()?;
+    let pack = packs.iter().find(|p| p.objects.contains_key(hash))?;
+
+    match pack.mtimes.get(hash) {
+        Some(secs) => Some(UNIX_EPOCH + Duration::from_secs(*secs)),
+        None => fs::metadata(&pack.pack_path).ok()?.modified().ok(),
+    }
+}
+
+pub fn list_packed_objects(root: &str) -> Result<Vec<String>, ErrorData> {
+    let packs = load_packs(root).map_err(|e| {
+        error_data!(
+            "list_packed_objects",
+            e.to_string(),
+            "Failed to load pack indexes"
+        )
+    })?;
+
+    Ok(packs
+        .iter()
+        .flat_map(|p| p.objects.keys().cloned())
+        .collect())
+}
+
+// `depth` este numarul de delta-uri deja urmate pana la aceasta intrare
+fn read_pack_entry(
+    root: &str,
+    pack_path: &str,
+    offset: u64,
+    depth: usize,
+) -> Result<(ObjectType, Vec<u8>), ErrorData> {
+    let mut file = fs::File::open(pack_path)
+        .map_err(|e| error_data!("read_pack_entry", e.to_string(), "Failed to open pack file"))?;
+
+    file.seek(SeekFrom::Start(offset)).map_err(|e| {
+        error_data!(
+            "read_pack_entry",
+            e.to_string(),
+            "Failed to seek in pack file"
+        )
+    })?;
+
+    let mut head = [0u8; 2];
+    file.read_exact(&mut head).map_err(|e| {
+        error_data!(
+            "read_pack_entry",
+            e.to_string(),
+            "Failed to read pack entry header"
+        )
+    })?;
+
+    let object_type = match byte_to_type(head[0]) {
+        Some(t) => t,
+        None => {
+            return Err(error_data!(
+                "read_pack_entry",
+                format!("Unknown object type {} in {}", head[0], pack_path),
+                "Corrupt pack entry"
+            ));
+        }
+    };
+
+    let mut base_hash = String::new();
+    if head[1] == ENTRY_DELTA {
+        let mut len = [0u8; 1];
+        file.read_exact(&mut len).map_err(|e| {
+            error_data!(
+                "read_pack_entry",
+                e.to_string(),
+                "Failed to read delta base length"
+            )
+        })?;
+
+        let mut name = vec![0u8; len[0] as usize];
+        file.read_exact(&mut name).map_err(|e| {
+            error_data!(
+                "read_pack_entry",
+                e.to_string(),
+                "Failed to read delta base name"
+            )
+        })?;
+
+        base_hash = String::from_utf8_lossy(&name).to_string();
+    }
+
+    let mut size = [0u8; 8];
+    file.read_exact(&mut size).map_err(|e| {
+        error_data!(
+            "read_pack_entry",
+            e.to_string(),
+            "Failed to read pack entry size"
+        )
+    })?;
+
+    // Dimensiunea vine din pack: nu poate depasi ce a mai ramas din fisier
+    let size = u64::from_le_bytes(size);
+    let remaining = file
+        .metadata()
+        .and_then(|m| Ok(m.len().saturating_sub(file.stream_position()?)))
+        .map_err(|e| {
+            error_data!(
+                "read_pack_entry",
+                e.to_string(),
+                "Failed to get pack file size"
+            )
+        })?;
+
+    if size > remaining {
+        return Err(error_data!(
+            "read_pack_entry",
+            format!(
+                "Entry at offset {} in {} claims {} bytes, but only {} remain",
+                offset, pack_path, size, remaining
+            ),
+            "Corrupt pack entry"
+        ));
+    }
+
+    let mut compressed = vec![0u8; size as usize];
+    file.read_exact(&mut compressed).map_err(|e| {
+        error_data!(
+            "read_pack_entry",
+            e.to_string(),
+            "Failed to read pack entry data"
+        )
+    })?;
+
+    let data = match decompress(&compressed) {
+        Some(d) => d,
+        None => {
+            return Err(error_data!(
+                "read_pack_entry",
+                format!(
+                    "Invalid compressed data at offset {} in {}",
+                    offset, pack_path
+                ),
+                "Corrupt pack entry"
+            ));
+        }
+    };
+
+    if head[1] != ENTRY_DELTA {
+        return Ok((object_type, data));
+    }
+
+    // Pack-urile scrise aici nu depasesc MAX_DELTA_DEPTH; un lant mai lung
+    // (sau unul care se intoarce la el insusi) vine dintr-un pack stricat
+    if depth >= MAX_DELTA_DEPTH {
+        return Err(error_data!(
+            "read_pack_entry",
+            format!(
+                "Delta chain at offset {} in {} is longer than {}",
+                offset, pack_path, MAX_DELTA_DEPTH
+            ),
+            "Corrupt pack entry"
+        ));
+    }
+
+    let base = match read_packed_object_at(root, &base_hash, depth + 1)? {
+        Some((_, b)) => b,
+        None => {
+            return Err(error_data!(
+                "read_pack_entry",
+                format!("Delta base {} not found", base_hash),
+                "Corrupt pack entry"
+            ));
+        }
+    };
+
+    match apply_delta(&base, &data) {
+        Some(result) => Ok((object_type, result)),
+        None => Err(error_data!(
+            "read_pack_entry",
+            format!("Invalid delta at offset {} in {}", offset, pack_path),
+            "Corrupt pack entry"
+        )),
+    }
+}
+
+pub fn read_packed_object(
+    root: &str,
+    hash: &str,
+) -> Result<Option<(ObjectType, Vec<u8>)>, ErrorData> {
+    read_packed_object_at(root, hash, 0)
+}
+
+fn read_packed_object_at(
+    root: &str,
+    hash: &str,
+    depth: usize,
+) -> Result<Option<(ObjectType, Vec<u8>)>, ErrorData> {
+    let packs = load_packs(root).map_err(|e| {
+        error_data!(
+            "read_packed_object",
+            e.to_string(),
+            "Failed to load pack indexes"
+        )
+    })?;
+
+    for pack in packs.iter() {
+        if let Some(offset) = pack.objects.get(hash) {
+            let object = read_pack_entry(root, &pack.pack_path, *offset, depth).map_err(|e| {
+                error_data!(
+                    "read_packed_object",
+                    e.to_string(),
+                    "Failed to read pack entry"
+                )
+            })?;
+
+            return Ok(Some(object));
+        }
+    }
+
+    Ok(None)
+}
+
+struct PackCandidate {
+    hash: String,
+    object_type: ObjectType,
+    data: Vec<u8>,
+    depth: usize,
+    mtime: u64,
+}
+
+// Impacheteaza toate obiectele libere intr-un singur pack nou, apoi le sterge.
+pub fn repack(root: &str) -> Result<RepackStats, ErrorData> {
+    repack_objects(root, false, &HashSet::new())
+}
+
+// Scrie un pack nou cu obiectele libere si, daca `include_packed` este setat,
+// cu cele din pack-urile existente, care sunt apoi inlocuite. Obiectele din
+// `exclude` nu sunt incluse: cele libere raman neatinse, iar cele din pack-urile
+// vechi dispar odata cu acestea.
+// Obiectele similare sunt salvate ca delta fata de unul dintre vecinii lor
+// (sortati dupa tip si dimensiune).
+pub fn repack_objects(
+    root: &str,
+    include_packed: bool,
+    exclude: &HashSet<String>,
+) -> Result<RepackStats, ErrorData> {
+    let loose: Vec<(String, PathBuf)> = list_loose_objects(root)
+        .map_err(|e| {
+            error_data!(
+                "repack_objects",
+                e.to_string(),
+                "Failed to list loose objects"
+            )
+        })?
+        .into_iter()
+        .filter(|(hash, _)| !exclude.contains(hash))
+        .collect();
+
+    let old_packs: Vec<String> = if include_packed {
+        load_packs(root)
+            .map_err(|e| {
+                error_data!(
+                    "repack_objects",
+                    e.to_string(),
+                    "Failed to load pack indexes"
+                )
+            })?
+            .iter()
+            .map(|p| p.pack_path.clone())
+            .collect()
+    } else {
+        Vec::new()
+    };
+
+    let mut stats = RepackStats {
+        objects: 0,
+        deltas: 0,
+    };
+
+    if loose.is_empty() && old_packs.is_empty() {
+        return Ok(stats);
+    }
+
+    let mut candidates: Vec<PackCandidate> = Vec::new();
+    let mut seen: HashSet<String> = HashSet::new();
+
+    for (hash, path) in &loose {
+        let (object_type, data) = read_loose_object_file(path).map_err(|e| {
+            error_data!(
+                "repack_objects",
+                e.to_string(),
+                "Failed to read loose object"
+            )
+        })?;
+
+        let mtime = fs::metadata(path)
+            .and_then(|m| m.modified())
+            .unwrap_or_else(|_| SystemTime::now());
+
+        seen.insert(hash.clone());
+        candidates.push(PackCandidate {
+            hash: hash.clone(),
+            object_type,
+            data,
+            depth: 0,
+            mtime: to_unix_secs(mtime),
+        });
+    }
+
+    if include_packed {
+        for hash in list_packed_objects(root).map_err(|e| {
+            error_data!(
+                "repack_objects",
+                e.to_string(),
+                "Failed to list packed objects"
+            )
+        })? {
+            if exclude.contains(&hash) || seen.contains(&hash) {
+                continue;
+            }
+
+            if let Some((object_type, data)) = read_packed_object(root, &hash).map_err(|e| {
+                error_data!(
+                    "repack_objects",
+                    e.to_string(),
+                    "Failed to read packed object"
+                )
+            })? {
+                let mtime = packed_object_mtime(root, &hash).unwrap_or_else(SystemTime::now);
+
+                seen.insert(hash.clone());
+                candidates.push(PackCandidate {
+                    hash,
+                    object_type,
+                    data,
+                    depth: 0,
+                    mtime: to_unix_secs(mtime),
+                });
+            }
+        }
+    }
+
+    let mut new_pack_path = String::new();
+    if !candidates.is_empty() {
+        new_pack_path = write_pack(root, &mut candidates, &mut stats)
+            .map_err(|e| error_data!("repack_objects", e.to_string(), "Failed to write pack"))?;
+    }
+
+    invalidate_pack_cache(root);
+
+    for pack_path in old_packs {
+        if pack_path == new_pack_path {
+            continue;
+        }
+
+        let idx_path = format!("{}.idx", pack_path.trim_end_matches(".pack"));
+
+        // Indexul este sters primul, ca un pack pe jumatate sters sa fie ignorat
+        for path in [idx_path, pack_path] {
+            fs::remove_file(&path).map_err(|e| {
+                error_data!("repack_objects", e.to_string(), "Failed to remove old pack")
+            })?;
+        }
+    }
+
+    for (_, path) in loose {
+        fs::remove_file(&path).map_err(|e| {
+            error_data!(
+                "repack_objects",
+                e.to_string(),
+                "Failed to remove packed loose object"
+            )
+        })?;
+
+        if let Some(parent) = path.parent()
+            && parent.to_string_lossy() != format_path(&vec![root, ".my_svn", "objects"])
+        {
+            let _ = fs::remove_dir(parent);
+        }
+    }
+
+    Ok(stats)
+}
+
+fn write_pack(
+    root: &str,
+    candidates: &mut [PackCandidate],
+    stats: &mut RepackStats,
+) -> Result<String, ErrorData> {
+    candidates.sort_by(|a, b| {
+        type_to_byte(a.object_type)
+            .cmp(&type_to_byte(b.object_type))
+            .then_with(|| b.data.len().cmp(&a.data.len()))
+    });
+
+    let mut pack: Vec<u8> = Vec::new();
+    pack.extend_from_slice(PACK_SIGNATURE);
+    pack.extend_from_slice(&PACK_VERSION.to_le_bytes());
+    pack.extend_from_slice(&(candidates.len() as u32).to_le_bytes());
+
+    let mut offsets: HashMap<String, u64> = HashMap::new();
+
+    for i in 0..candidates.len() {
+        let mut best: Option<(usize, Vec<u8>)> = None;
+
+        for j in i.saturating_sub(DELTA_WINDOW)..i {
+            if candidates[j].object_type != candidates[i].object_type
+                || candidates[j].depth >= MAX_DELTA_DEPTH
+            {
+                continue;
+            }
+
+            let delta = create_delta(&candidates[j].data, &candidates[i].data);
+
+            if delta.len() < candidates[i].data.len() / 2
+                && best.as_ref().is_none_or(|(_, d)| delta.len() < d.len())
+            {
+                best = Some((j, delta));
+            }
+        }
+
+        offsets.insert(candidates[i].hash.clone(), pack.len() as u64);
+        pack.push(type_to_byte(candidates[i].object_type));
+
+        let content = match best {
+            Some((j, delta)) => {
+                candidates[i].depth = candidates[j].depth + 1;
+                stats.deltas += 1;
+
+                pack.push(ENTRY_DELTA);
+                pack.push(candidates[j].hash.len() as u8);
+                pack.extend_from_slice(candidates[j].hash.as_bytes());
+
+                delta
+            }
+            None => {
+                pack.push(ENTRY_FULL);
+                candidates[i].data.clone()
+            }
+        };
+
+        let compressed = compress(&content).map_err(|e| {
+            error_data!("write_pack", e.to_string(), "Failed to compress pack entry")
+        })?;
+
+        pack.extend_from_slice(&(compressed.len() as u64).to_le_bytes());
+        pack.extend_from_slice(&compressed);
+
+        stats.objects += 1;
+    }
+
+    let pack_hash = hash_bytes(root, &pack)
+        .map_err(|e| error_data!("write_pack", e.to_string(), "Failed to hash pack file"))?;
+    let pack_name = format!("pack-{}", pack_hash);
+
+    fs::create_dir_all(pack_dir(root)).map_err(|e| {
+        error_data!(
+            "write_pack",
+            e.to_string(),
+            "Failed to create pack directory"
+        )
+    })?;
+
+    write_atomic(
+        &format_path(&vec![&pack_dir(root), &format!("{}.pack", pack_name)]),
+        &pack,
+    )
+    .map_err(|e| error_data!("write_pack", e.to_string(), "Failed to write pack file"))?;
+
+    let index_json = serde_json::to_string(&PackIndexFile {
+        version: PACK_VERSION,
+        objects: offsets,
+        mtimes: candidates
+            .iter()
+            .map(|c| (c.hash.clone(), c.mtime))
+            .collect(),
+    })
+    .map_err(|e| {
+        error_data!(
+            "write_pack",
+            e.to_string(),
+            "Failed to serialize pack index"
+        )
+    })?;
+
+    // Indexul este scris ultimul: un pack fara index este ignorat la citire
+    write_atomic(
+        &format_path(&vec![&pack_dir(root), &format!("{}.idx", pack_name)]),
+        index_json.as_bytes(),
+    )
+    .map_err(|e| error_data!("write_pack", e.to_string(), "Failed to write pack index"))?;
+
+    Ok(format_path(&vec![
+        &pack_dir(root),
+        &format!("{}.pack", pack_name),
+    ]))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::{
+        hash::HashAlgorithm,
+        object::{read_typed_object, write_object},
+        testing::TempRepo,
+    };
+
+    fn write_blob(repo: &TempRepo, data: &[u8]) -> String {
+        let hash = hash_bytes(&repo.root, data).unwrap();
+        write_object(&repo.root, &hash, ObjectType::Blob, data).unwrap();
+        hash
+    }
+
+    fn sample_blobs() -> Vec<Vec<u8>> {
+        let base: String = (0..100).map(|i| format!("line {}\n", i)).collect();
+
+        vec![
+            base.clone().into_bytes(),
+            format!("{}one more line\n", base).into_bytes(),
+            base.replace("line 50\n", "changed\n").into_bytes(),
+            b"unrelated".to_vec(),
+        ]
+    }
+
+    fn round_trip(repo: &TempRepo) {
+        let blobs = sample_blobs();
+        let hashes: Vec<String> = blobs.iter().map(|b| write_blob(repo, b)).collect();
+
+        let stats = repack(&repo.root).unwrap();
+        assert_eq!(stats.objects, blobs.len());
+        assert!(stats.deltas >= 2);
+        assert!(list_loose_objects(&repo.root).unwrap().is_empty());
+
+        for (hash, data) in hashes.iter().zip(&blobs) {
+            let (object_type, packed) = read_packed_object(&repo.root, hash).unwrap().unwrap();
+            assert_eq!(object_type, ObjectType::Blob);
+            assert_eq!(&packed, data);
+            assert_eq!(read_typed_object(&repo.root, hash).unwrap().1, *data);
+        }
+    }
+
+    #[test]
+    fn write_then_read_pack() {
+        round_trip(&TempRepo::new());
+    }
+
+    #[test]
+    fn write_then_read_pack_sha256() {
+        round_trip(&TempRepo::with_format(HashAlgorithm::Sha256));
+    }
+
+    #[test]
+    fn repack_twice_keeps_objects() {
+        let repo = TempRepo::new();
+        let first = write_blob(&repo, b"first object");
+        repack(&repo.root).unwrap();
+
+        let second = write_blob(&repo, b"second object");
+        repack_objects(&repo.root, true, &HashSet::new()).unwrap();
+
+        let mut packed = list_packed_objects(&repo.root).unwrap();
+        packed.sort();
+        let mut expected = vec![first, second];
+        expected.sort();
+        assert_eq!(packed, expected);
+    }
+
+    #[test]
+    fn repack_keeps_object_times() {
+        let repo = TempRepo::new();
+        let hash = write_blob(&repo, b"an old object");
+
+        let old = UNIX_EPOCH + Duration::from_secs(1_577_836_800);
+        fs::File::options()
+            .write(true)
+            .open(crate::utils::object::object_path(&repo.root, &hash))
+            .unwrap()
+            .set_modified(old)
+            .unwrap();
+
+        repack(&repo.root).unwrap();
+        assert_eq!(packed_object_mtime(&repo.root, &hash), Some(old));
+
+        // Un al doilea repack scrie un pack nou, dar data obiectului ramane
+        write_blob(&repo, b"a new object");
+        repack_objects(&repo.root, true, &HashSet::new()).unwrap();
+        assert_eq!(packed_object_mtime(&repo.root, &hash), Some(old));
+    }
+
+    #[test]
+    fn corrupt_entry_size_is_an_error() {
+        let repo = TempRepo::new();
+        let hash = write_blob(&repo, b"some content");
+        repack(&repo.root).unwrap();
+
+        let packs = load_packs(&repo.root).unwrap();
+        let pack_path = packs[0].pack_path.clone();
+        let offset = packs[0].objects[&hash] as usize;
+
+        // Dupa tip si felul intrarii urmeaza dimensiunea datelor comprimate
+        let mut bytes = fs::read(&pack_path).unwrap();
+        bytes[offset + 2..offset + 10].copy_from_slice(&u64::MAX.to_le_bytes());
+        fs::write(&pack_path, bytes).unwrap();
+
+        assert!(read_packed_object(&repo.root, &hash).is_err());
+    }
+
+    #[test]
+    fn looping_delta_chain_is_an_error() {
+        let repo = TempRepo::new();
+        let (a, b) = ("a".repeat(40), "b".repeat(40));
+
+        // `a` este delta fata de `b`, iar `b` fata de `a`
+        let mut pack = PACK_SIGNATURE.to_vec();
+        pack.extend_from_slice(&PACK_VERSION.to_le_bytes());
+        let mut offsets = HashMap::new();
+
+        for (hash, base) in [(&a, &b), (&b, &a)] {
+            offsets.insert(hash.clone(), pack.len() as u64);
+
+            let compressed = compress(b"delta").unwrap();
+            pack.extend_from_slice(&[type_to_byte(ObjectType::Blob), ENTRY_DELTA]);
+            pack.push(base.len() as u8);
+            pack.extend_from_slice(base.as_bytes());
+            pack.extend_from_slice(&(compressed.len() as u64).to_le_bytes());
+            pack.extend_from_slice(&compressed);
+        }
+
+        fs::create_dir_all(pack_dir(&repo.root)).unwrap();
+        fs::write(repo.path(".my_svn/objects/pack/pack-loop.pack"), pack).unwrap();
+        let index = PackIndexFile {
+            version: PACK_VERSION,
+            objects: offsets,
+            mtimes: HashMap::new(),
+        };
+        fs::write(
+            repo.path(".my_svn/objects/pack/pack-loop.idx"),
+            serde_json::to_string(&index).unwrap(),
+        )
+        .unwrap();
+
+        let error = read_packed_object(&repo.root, &a).unwrap_err();
+        assert!(error.to_string().contains("Corrupt pack entry"));
+    }
+
+    #[test]
+    fn cache_sees_packs_changed_outside() {
+        let repo = TempRepo::new();
+        let hash = write_blob(&repo, b"packed elsewhere");
+        repack(&repo.root).unwrap();
+        assert!(packed_object_exists(&repo.root, &hash));
+
+        // Alt proces sterge pack-ul; cache-ul nu este invalidat explicit
+        for entry in fs::read_dir(pack_dir(&repo.root)).unwrap() {
+            fs::remove_file(entry.unwrap().path()).unwrap();
+        }
+
+        assert!(!packed_object_exists(&repo.root, &hash));
+    }
+}
@@ -1,16 +1,32 @@
 use std::env;
 
+use crate::commands::branch::BranchAction;
+use crate::commands::cat_file::CatFileMode;
+use crate::commands::commit::{CommitOptions, MessageSource};
+use crate::commands::config::ConfigAction;
+use crate::commands::tag::TagAction;
+use crate::utils::hash::HashAlgorithm;
+
 #[derive(PartialEq)]
 pub enum Command {
-    Init,
+    Init(Option<HashAlgorithm>),
     Add(Vec<String>),
-    Commit(String),
-    Checkout(String),
-    Branch(String),
+    Commit(MessageSource, CommitOptions),
+    Checkout(String, bool),
+    Branch(BranchAction),
+    Tag(TagAction),
     Merge(String),
     Diff(Option<String>),
     Status,
-    Log,
+    Log(Option<String>),
+    Reflog(Option<String>),
+    Migrate,
+    Repack,
+    Fsck,
+    Gc(Option<String>, bool),
+    CatFile(CatFileMode, String),
+    Config(ConfigAction, bool),
+    Unlock(bool),
     Help,
 }
 
@@ -22,9 +38,54 @@ pub fn parse_args() -> Result<Command, String> {
     }
 
     match args[1].as_str() {
-        "init" => Ok(Command::Init),
-        "log" => Ok(Command::Log),
+        "init" => {
+            if args.len() >= 3 {
+                match args[2].strip_prefix("--object-format=") {
+                    Some(name) => match HashAlgorithm::from_name(name) {
+                        Some(algorithm) => Ok(Command::Init(Some(algorithm))),
+                        None => Err(format!(
+                            "Unknown object format '{}' (expected sha1 or sha256)",
+                            name
+                        )),
+                    },
+                    None => Err(String::from(
+                        "The init command only accepts --object-format=<sha1|sha256>",
+                    )),
+                }
+            } else {
+                Ok(Command::Init(None))
+            }
+        }
+        "log" => Ok(Command::Log(args.get(2).cloned())),
+        "reflog" => Ok(Command::Reflog(args.get(2).cloned())),
         "status" => Ok(Command::Status),
+        "migrate" => Ok(Command::Migrate),
+        "repack" => Ok(Command::Repack),
+        "fsck" => Ok(Command::Fsck),
+        "unlock" => match args.get(2).map(|a| a.as_str()) {
+            None => Ok(Command::Unlock(false)),
+            Some("-f" | "--force") => Ok(Command::Unlock(true)),
+            Some(other) => Err(format!("Unknown unlock option '{}'", other)),
+        },
+        "gc" => {
+            let mut prune: Option<String> = None;
+            let mut dry_run = false;
+
+            for arg in &args[2..] {
+                if arg == "--dry-run" || arg == "-n" {
+                    dry_run = true;
+                } else if let Some(date) = arg.strip_prefix("--prune=") {
+                    prune = Some(date.to_string());
+                } else {
+                    return Err(format!(
+                        "Unknown gc option '{}' (expected --dry-run or --prune=<date>)",
+                        arg
+                    ));
+                }
+            }
+
+            Ok(Command::Gc(prune, dry_run))
+        }
         "add" => {
             if args.len() >= 3 {
                 Ok(Command::Add(args[2..].to_vec()))
@@ -35,30 +96,147 @@ pub fn parse_args() -> Result<Command, String> {
             }
         }
         "commit" => {
-            if args.len() >= 4 && args[2] == "-m" {
-                Ok(Command::Commit(args[3].clone()))
-            } else {
-                Err(String::from(
-                    "The commit command requires a message! (-m \"Message\")",
-                ))
+            let mut paragraphs: Vec<String> = Vec::new();
+            let mut file: Option<String> = None;
+            let mut options = CommitOptions::default();
+
+            let mut i = 2;
+            while i < args.len() {
+                match args[i].as_str() {
+                    "-m" | "--message" | "-F" | "--file" => {
+                        let value = match args.get(i + 1) {
+                            Some(v) => v.clone(),
+                            None => return Err(format!("Option '{}' requires a value", args[i])),
+                        };
+
+                        if args[i] == "-m" || args[i] == "--message" {
+                            paragraphs.push(value);
+                        } else {
+                            file = Some(value);
+                        }
+                        i += 1;
+                    }
+                    "--amend" => options.amend = true,
+                    "-a" | "--all" => options.all = true,
+                    "--allow-empty" => options.allow_empty = true,
+                    "-n" | "--no-verify" => options.no_verify = true,
+                    "--" => {
+                        options.paths.extend(args[i + 1..].iter().cloned());
+                        break;
+                    }
+                    "-am" => {
+                        let value = match args.get(i + 1) {
+                            Some(v) => v.clone(),
+                            None => return Err(String::from("Option '-am' requires a value")),
+                        };
+
+                        options.all = true;
+                        paragraphs.push(value);
+                        i += 1;
+                    }
+                    other if other.starts_with('-') => {
+                        return Err(format!("Unknown commit option '{}'", other));
+                    }
+                    path => options.paths.push(path.to_string()),
+                }
+                i += 1;
             }
+
+            if options.all && !options.paths.is_empty() {
+                return Err(String::from("Paths with -a does not make sense"));
+            }
+
+            let source = match (paragraphs.is_empty(), file) {
+                (true, None) => MessageSource::Editor,
+                (false, None) => MessageSource::Paragraphs(paragraphs),
+                (true, Some(f)) => MessageSource::File(f),
+                (false, Some(_)) => {
+                    return Err(String::from("Options -m and -F cannot be used together"));
+                }
+            };
+
+            Ok(Command::Commit(source, options))
         }
         "checkout" => {
-            if args.len() >= 3 {
-                Ok(Command::Checkout(args[2].clone()))
+            if args.len() >= 4 && args[2] == "--orphan" {
+                Ok(Command::Checkout(args[3].clone(), true))
+            } else if args.len() >= 3 && args[2] != "--orphan" {
+                Ok(Command::Checkout(args[2].clone(), false))
             } else {
                 Err(String::from(
-                    "The checkout command requires a branch name as an argument",
+                    "The checkout command requires a branch, tag or commit as an argument",
                 ))
             }
         }
-        "branch" => {
-            if args.len() >= 3 {
-                Ok(Command::Branch(args[2].clone()))
-            } else {
-                Err(String::from(
-                    "The branch command requires a branch name as an argument",
-                ))
+        "branch" => match args.get(2).map(|a| a.as_str()) {
+            None => Ok(Command::Branch(BranchAction::List(false))),
+            Some("-v") | Some("--verbose") => Ok(Command::Branch(BranchAction::List(true))),
+            Some(flag @ ("-d" | "-D" | "--delete")) => match args.get(3) {
+                Some(name) => Ok(Command::Branch(BranchAction::Delete(
+                    name.clone(),
+                    flag == "-D",
+                ))),
+                None => Err(String::from("Branch name required for deletion")),
+            },
+            Some("-m" | "--move") => match (args.get(3), args.get(4)) {
+                (Some(old), Some(new)) => Ok(Command::Branch(BranchAction::Rename(
+                    Some(old.clone()),
+                    new.clone(),
+                ))),
+                (Some(new), None) => Ok(Command::Branch(BranchAction::Rename(None, new.clone()))),
+                _ => Err(String::from("Branch name required for renaming")),
+            },
+            Some(other) if other.starts_with('-') => {
+                Err(format!("Unknown branch option '{}'", other))
+            }
+            Some(name) => Ok(Command::Branch(BranchAction::Create(name.to_string()))),
+        },
+        "tag" => {
+            let mut annotate = false;
+            let mut paragraphs: Vec<String> = Vec::new();
+            let mut names: Vec<String> = Vec::new();
+
+            let mut i = 2;
+            while i < args.len() {
+                match args[i].as_str() {
+                    "-l" | "--list" if args.len() == 3 => return Ok(Command::Tag(TagAction::List)),
+                    "-d" | "--delete" => {
+                        return match args.get(i + 1) {
+                            Some(name) if args.len() == i + 2 => {
+                                Ok(Command::Tag(TagAction::Delete(name.clone())))
+                            }
+                            Some(_) => Err(String::from("Only one tag can be deleted at a time")),
+                            None => Err(String::from("Tag name required for deletion")),
+                        };
+                    }
+                    "-a" | "--annotate" => annotate = true,
+                    "-m" | "--message" => match args.get(i + 1) {
+                        Some(value) => {
+                            paragraphs.push(value.clone());
+                            i += 1;
+                        }
+                        None => return Err(format!("Option '{}' requires a value", args[i])),
+                    },
+                    other if other.starts_with('-') => {
+                        return Err(format!("Unknown tag option '{}'", other));
+                    }
+                    name => names.push(name.to_string()),
+                }
+                i += 1;
+            }
+
+            match names.as_slice() {
+                [] if !annotate && paragraphs.is_empty() => Ok(Command::Tag(TagAction::List)),
+                [name] | [name, _] => Ok(Command::Tag(TagAction::Create {
+                    name: name.clone(),
+                    target: names.get(1).cloned(),
+                    annotate: annotate || !paragraphs.is_empty(),
+                    paragraphs,
+                })),
+                [] => Err(String::from("Tag name required")),
+                _ => Err(String::from(
+                    "Too many arguments for tag (expected <name> [<commit>])",
+                )),
             }
         }
         "merge" => {
@@ -66,7 +244,7 @@ pub fn parse_args() -> Result<Command, String> {
                 Ok(Command::Merge(args[2].clone()))
             } else {
                 Err(String::from(
-                    "The merge command requires a branch name as an argument",
+                    "The merge command requires a branch or commit as an argument",
                 ))
             }
         }
@@ -77,6 +255,49 @@ pub fn parse_args() -> Result<Command, String> {
                 Ok(Command::Diff(None))
             }
         }
+        "cat-file" => {
+            if args.len() >= 4 {
+                let mode = match args[2].as_str() {
+                    "-t" => CatFileMode::Type,
+                    "-s" => CatFileMode::Size,
+                    "-p" => CatFileMode::Pretty,
+                    _ => {
+                        return Err(String::from(
+                            "The cat-file command requires one of the -t, -s or -p options",
+                        ));
+                    }
+                };
+
+                Ok(Command::CatFile(mode, args[3].clone()))
+            } else {
+                Err(String::from(
+                    "The cat-file command requires an option and an object hash (-t|-s|-p <object>)",
+                ))
+            }
+        }
+        "config" => {
+            let global = args[2..].iter().any(|a| a == "--global");
+            let rest: Vec<&String> = args[2..].iter().filter(|a| *a != "--global").collect();
+
+            match rest.as_slice() {
+                [flag] if *flag == "--list" || *flag == "-l" => {
+                    Ok(Command::Config(ConfigAction::List, global))
+                }
+                [flag, key] if *flag == "--unset" => {
+                    Ok(Command::Config(ConfigAction::Unset((*key).clone()), global))
+                }
+                [key] if !key.starts_with('-') => {
+                    Ok(Command::Config(ConfigAction::Get((*key).clone()), global))
+                }
+                [key, value] if !key.starts_with('-') => Ok(Command::Config(
+                    ConfigAction::Set((*key).clone(), (*value).clone()),
+                    global,
+                )),
+                _ => Err(String::from(
+                    "Usage: config [--global] (<key> [<value>] | --unset <key> | --list)",
+                )),
+            }
+        }
         "help" => Ok(Command::Help),
         _ => Err(String::from("Unknown command")),
     }
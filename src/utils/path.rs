@@ -1,17 +1,84 @@
 use std::{
     collections::HashMap,
+    fs::Metadata,
     path::{Path, PathBuf},
 };
 
 use pathdiff::diff_paths;
 use walkdir::WalkDir;
 
-use crate::{error_data, utils::error::ErrorData};
+use crate::{
+    error_data,
+    utils::{config::check_repository_format, error::ErrorData},
+};
+
+// Pentru symlink-uri blob-ul contine calea catre care trimite legatura
+#[derive(Debug, serde::Serialize, serde::Deserialize, Clone, Copy, PartialEq, Default)]
+pub enum FileMode {
+    #[default]
+    Regular,
+    Executable,
+    Symlink,
+}
+
+impl FileMode {
+    pub fn is_regular(&self) -> bool {
+        *self == FileMode::Regular
+    }
+}
 
 #[derive(Debug, serde::Serialize, serde::Deserialize, Clone)]
 pub struct FileInfo {
     pub hash: String,
     pub timestamp: u64,
+    // Indexurile mai vechi nu au modul fisierelor
+    #[serde(default)]
+    pub mode: FileMode,
+}
+
+impl FileInfo {
+    // Doua versiuni ale unui fisier sunt egale daca au acelasi continut si mod
+    pub fn same_version(&self, other: &FileInfo) -> bool {
+        self.hash == other.hash && self.mode == other.mode
+    }
+}
+
+// `metadata` trebuie obtinut cu `symlink_metadata`, ca legaturile sa nu fie urmate
+pub fn file_mode(metadata: &Metadata) -> FileMode {
+    if metadata.file_type().is_symlink() {
+        return FileMode::Symlink;
+    }
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+
+        if metadata.permissions().mode() & 0o111 != 0 {
+            return FileMode::Executable;
+        }
+    }
+
+    FileMode::Regular
+}
+
+// Pe sistemele fara bit de executie (Windows) un fisier executabil din index
+// nu este considerat modificat doar pentru ca pe disc apare ca obisnuit
+pub fn same_mode(recorded: FileMode, detected: FileMode) -> bool {
+    recorded == detected
+        || (!cfg!(unix) && recorded == FileMode::Executable && detected == FileMode::Regular)
+}
+
+// Continutul unui symlink este calea tinta, nu fisierul spre care trimite
+pub fn symlink_target(path: &str) -> Option<Vec<u8>> {
+    let metadata = std::fs::symlink_metadata(path).ok()?;
+
+    if !metadata.file_type().is_symlink() {
+        return None;
+    }
+
+    let target = std::fs::read_link(path).ok()?;
+
+    Some(target.to_string_lossy().as_bytes().to_vec())
 }
 
 pub struct WorkingDirectoryFiles {
@@ -77,6 +144,14 @@ pub fn find_repo_root(start_path: &String) -> Result<RepoLocation, RepoLocationE
 
     loop {
         if dir.join(".my_svn").exists() {
+            check_repository_format(&dir.to_string_lossy()).map_err(|e| {
+                RepoLocationError::ErrorData(error_data!(
+                    "find_repo_root",
+                    e.to_string(),
+                    "Refusing to open repository"
+                ))
+            })?;
+
             let relative = match start.strip_prefix(&dir) {
                 Ok(r) => r.to_path_buf(),
                 Err(e) => {
@@ -192,16 +267,17 @@ pub fn get_working_directory_optimized(
             continue;
         }
 
-        if entry.path().is_file() {
-            let timestamp = entry
-                .metadata()
-                .map_err(|e| {
-                    error_data!(
-                        "get_working_directory",
-                        e.to_string(),
-                        "Failed to get file metadata"
-                    )
-                })?
+        // Legaturile simbolice nu sunt urmate: sunt inregistrate ca atare
+        if entry.file_type().is_file() || entry.file_type().is_symlink() {
+            let metadata = entry.metadata().map_err(|e| {
+                error_data!(
+                    "get_working_directory",
+                    e.to_string(),
+                    "Failed to get file metadata"
+                )
+            })?;
+
+            let timestamp = metadata
                 .modified()
                 .map_err(|e| {
                     error_data!(
@@ -225,6 +301,7 @@ pub fn get_working_directory_optimized(
                 FileInfo {
                     hash: String::new(),
                     timestamp,
+                    mode: file_mode(&metadata),
                 },
             );
         }
@@ -239,15 +316,3 @@ pub fn is_path_within(base: &str, target: &str) -> bool {
 
     target_path.starts_with(base_path)
 }
-
-pub fn copy_to_repo_objects(src: &str, dest: &PathBuf) -> Result<(), ErrorData> {
-    std::fs::copy(src, dest).map_err(|e| {
-        error_data!(
-            "copy_to_repo_objects",
-            e.to_string(),
-            "Failed to copy file to repository objects"
-        )
-    })?;
-
-    Ok(())
-}
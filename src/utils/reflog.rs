@@ -0,0 +1,423 @@
+use std::{
+    fs::{self, OpenOptions},
+    io::Write,
+    path::Path,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use walkdir::WalkDir;
+
+use crate::{
+    commands::branch::{Head, read_head},
+    error_data,
+    utils::{error::ErrorData, hash::object_format, lock::LockFile, path::format_path},
+};
+
+// O linie din `.my_svn/logs/<ref>`: "<hash vechi> <hash nou> <timestamp>\t<mesaj>".
+// O referinta fara commit (ramura noua, ramura stearsa) este scrisa ca zerouri
+// si citita inapoi ca hash gol.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ReflogEntry {
+    pub old: String,
+    pub new: String,
+    pub timestamp: u64,
+    pub message: String,
+}
+
+fn reflog_path(root: &str, ref_name: &str) -> String {
+    let mut parts = vec![root, ".my_svn", "logs"];
+    parts.extend(ref_name.split('/'));
+
+    format_path(&parts)
+}
+
+fn parse_line(line: &str) -> Option<ReflogEntry> {
+    let (fields, message) = line.split_once('\t')?;
+    let mut fields = fields.split(' ');
+
+    let mut hash = || {
+        fields.next().map(|h| match h.chars().all(|c| c == '0') {
+            true => String::new(),
+            false => h.to_string(),
+        })
+    };
+    let old = hash()?;
+    let new = hash()?;
+    let timestamp = fields.next()?.parse().ok()?;
+
+    Some(ReflogEntry {
+        old,
+        new,
+        timestamp,
+        message: message.to_string(),
+    })
+}
+
+// Numele jurnalului pentru un nume dat de utilizator: `HEAD`, `refs/...`,
+// o ramura sau un tag; numele gol inseamna ramura curenta (sau HEAD detasat)
+pub fn reflog_ref(root: &str, name: &str) -> Result<String, ErrorData> {
+    if name.is_empty() {
+        return match read_head(root)
+            .map_err(|e| error_data!("reflog_ref", e.to_string(), "Failed to read HEAD"))?
+        {
+            Head::Branch(branch) => Ok(format!("refs/heads/{}", branch)),
+            Head::Detached(_) => Ok(String::from("HEAD")),
+        };
+    }
+
+    if name == "HEAD" || name.starts_with("refs/") {
+        return Ok(name.to_string());
+    }
+
+    let tag_ref = format!("refs/tags/{}", name);
+    let is_tag = Path::new(&format_path(&vec![root, ".my_svn", "refs", "tags", name])).is_file()
+        || Path::new(&reflog_path(root, &tag_ref)).is_file();
+    let is_branch =
+        Path::new(&format_path(&vec![root, ".my_svn", "refs", "heads", name])).is_file();
+
+    // Ca la rezolvarea reviziilor, ramura are prioritate in fata tag-ului
+    match is_tag && !is_branch {
+        true => Ok(tag_ref),
+        false => Ok(format!("refs/heads/{}", name)),
+    }
+}
+
+pub fn reflog_exists(root: &str, ref_name: &str) -> bool {
+    Path::new(&reflog_path(root, ref_name)).is_file()
+}
+
+pub fn append_reflog(
+    root: &str,
+    ref_name: &str,
+    old: &str,
+    new: &str,
+    message: &str,
+) -> Result<(), ErrorData> {
+    if old.is_empty() && new.is_empty() {
+        return Ok(());
+    }
+
+    let zero = "0".repeat(
+        object_format(root)
+            .map_err(|e| {
+                error_data!(
+                    "append_reflog",
+                    e.to_string(),
+                    "Failed to get repository object format"
+                )
+            })?
+            .hex_len(),
+    );
+
+    let timestamp = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_err(|e| error_data!("append_reflog", e.to_string(), "Failed to get current time"))?
+        .as_secs();
+
+    let path = reflog_path(root, ref_name);
+    if let Some(parent) = Path::new(&path).parent() {
+        fs::create_dir_all(parent).map_err(|e| {
+            error_data!(
+                "append_reflog",
+                e.to_string(),
+                "Failed to create logs directory"
+            )
+        })?;
+    }
+
+    let line = format!(
+        "{} {} {}\t{}\n",
+        if old.is_empty() { &zero } else { old },
+        if new.is_empty() { &zero } else { new },
+        timestamp,
+        message.replace(['\n', '\r', '\t'], " ")
+    );
+
+    // Scrierile cu O_APPEND ale unei singure linii nu se amesteca
+    let mut file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&path)
+        .map_err(|e| error_data!("append_reflog", e.to_string(), "Failed to open reflog"))?;
+
+    file.write_all(line.as_bytes())
+        .map_err(|e| error_data!("append_reflog", e.to_string(), "Failed to write reflog"))
+}
+
+// Miscarea varfului ramurii curente apare atat in jurnalul ramurii, cat si in
+// cel al lui HEAD; cu HEAD detasat doar in cel al lui HEAD
+pub fn append_head_reflog(
+    root: &str,
+    head: &Head,
+    old: &str,
+    new: &str,
+    message: &str,
+) -> Result<(), ErrorData> {
+    if let Head::Branch(branch) = head {
+        append_reflog(root, &format!("refs/heads/{}", branch), old, new, message).map_err(|e| {
+            error_data!(
+                "append_head_reflog",
+                e.to_string(),
+                "Failed to update branch reflog"
+            )
+        })?;
+    }
+
+    append_reflog(root, "HEAD", old, new, message).map_err(|e| {
+        error_data!(
+            "append_head_reflog",
+            e.to_string(),
+            "Failed to update HEAD reflog"
+        )
+    })
+}
+
+// Intrarile unei referinte, de la cea mai noua la cea mai veche (`ref@{0}` este prima)
+pub fn read_reflog(root: &str, ref_name: &str) -> Result<Vec<ReflogEntry>, ErrorData> {
+    let path = reflog_path(root, ref_name);
+
+    if !Path::new(&path).is_file() {
+        return Ok(Vec::new());
+    }
+
+    let content = fs::read_to_string(&path)
+        .map_err(|e| error_data!("read_reflog", e.to_string(), "Failed to read reflog"))?;
+
+    Ok(content.lines().rev().filter_map(parse_line).collect())
+}
+
+pub fn delete_reflog(root: &str, ref_name: &str) -> Result<(), ErrorData> {
+    let path = reflog_path(root, ref_name);
+
+    if !Path::new(&path).is_file() {
+        return Ok(());
+    }
+
+    fs::remove_file(&path)
+        .map_err(|e| error_data!("delete_reflog", e.to_string(), "Failed to remove reflog"))
+}
+
+pub fn rename_reflog(root: &str, old_ref: &str, new_ref: &str) -> Result<(), ErrorData> {
+    let old_path = reflog_path(root, old_ref);
+
+    if !Path::new(&old_path).is_file() {
+        return Ok(());
+    }
+
+    let new_path = reflog_path(root, new_ref);
+    if let Some(parent) = Path::new(&new_path).parent() {
+        fs::create_dir_all(parent).map_err(|e| {
+            error_data!(
+                "rename_reflog",
+                e.to_string(),
+                "Failed to create logs directory"
+            )
+        })?;
+    }
+
+    fs::rename(&old_path, &new_path)
+        .map_err(|e| error_data!("rename_reflog", e.to_string(), "Failed to move reflog"))
+}
+
+// Hash-urile din intrarile scrise dupa `since`; `gc` le pastreaza, ca munca
+// pierduta sa poata fi recuperata cu `ref@{N}` pana expira intrarea
+pub fn reflog_hashes(root: &str, since: u64) -> Result<Vec<String>, ErrorData> {
+    let mut hashes: Vec<String> = Vec::new();
+
+    for path in reflog_files(root) {
+        let content = fs::read_to_string(&path)
+            .map_err(|e| error_data!("reflog_hashes", e.to_string(), "Failed to read reflog"))?;
+
+        for line in content.lines().filter_map(parse_line) {
+            if line.timestamp > since {
+                hashes.extend([line.old, line.new].into_iter().filter(|h| !h.is_empty()));
+            }
+        }
+    }
+
+    hashes.sort();
+    hashes.dedup();
+    Ok(hashes)
+}
+
+// Sterge intrarile scrise pana la `cutoff` inclusiv; un jurnal ramas gol este
+// sters cu totul. Intoarce numarul de intrari sterse
+pub fn expire_reflogs(root: &str, cutoff: u64) -> Result<usize, ErrorData> {
+    let mut expired = 0;
+
+    for path in reflog_files(root) {
+        let lock = LockFile::acquire(&path)
+            .map_err(|e| error_data!("expire_reflogs", e.to_string(), "Failed to lock reflog"))?;
+
+        // Citit sub lock, ca sa nu pierdem o intrare adaugata intre timp
+        let content = fs::read_to_string(&path)
+            .map_err(|e| error_data!("expire_reflogs", e.to_string(), "Failed to read reflog"))?;
+
+        let mut kept = String::new();
+        let mut removed = 0;
+        for line in content.lines() {
+            match parse_line(line) {
+                Some(entry) if entry.timestamp <= cutoff => removed += 1,
+                _ => {
+                    kept.push_str(line);
+                    kept.push('\n');
+                }
+            }
+        }
+
+        if removed == 0 {
+            continue;
+        }
+        expired += removed;
+
+        if kept.trim().is_empty() {
+            fs::remove_file(&path).map_err(|e| {
+                error_data!("expire_reflogs", e.to_string(), "Failed to remove reflog")
+            })?;
+        } else {
+            lock.commit(kept.as_bytes()).map_err(|e| {
+                error_data!("expire_reflogs", e.to_string(), "Failed to write reflog")
+            })?;
+        }
+    }
+
+    Ok(expired)
+}
+
+fn reflog_files(root: &str) -> Vec<String> {
+    let logs_dir = format_path(&vec![root, ".my_svn", "logs"]);
+
+    WalkDir::new(&logs_dir)
+        .into_iter()
+        .filter_map(|e| e.ok())
+        .filter(|e| e.path().is_file() && e.path().extension().is_none_or(|ext| ext != "lock"))
+        .map(|e| e.path().to_string_lossy().to_string())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::testing::TempRepo;
+
+    const A: &str = "1111111111111111111111111111111111111111";
+    const B: &str = "2222222222222222222222222222222222222222";
+    const C: &str = "3333333333333333333333333333333333333333";
+
+    #[test]
+    fn appended_entries_are_read_newest_first() {
+        let repo = TempRepo::new();
+        let main = "refs/heads/main";
+
+        assert!(!reflog_exists(&repo.root, main));
+        assert!(read_reflog(&repo.root, main).unwrap().is_empty());
+
+        append_reflog(&repo.root, main, "", A, "commit (initial): one").unwrap();
+        append_reflog(&repo.root, main, A, B, "commit: two").unwrap();
+        append_reflog(&repo.root, main, B, C, "merge side:\tthree\nlines").unwrap();
+
+        assert!(reflog_exists(&repo.root, main));
+
+        let entries = read_reflog(&repo.root, main).unwrap();
+        let moves: Vec<(&str, &str)> = entries
+            .iter()
+            .map(|e| (e.old.as_str(), e.new.as_str()))
+            .collect();
+        assert_eq!(moves, vec![(B, C), (A, B), ("", A)]);
+
+        // Mesajul ramane pe o singura linie
+        assert_eq!(entries[0].message, "merge side: three lines");
+        assert_eq!(entries[2].message, "commit (initial): one");
+        assert!(entries[0].timestamp >= entries[2].timestamp);
+    }
+
+    #[test]
+    fn head_moves_are_logged_for_the_branch_too() {
+        let repo = TempRepo::new();
+
+        append_head_reflog(
+            &repo.root,
+            &Head::Branch(String::from("main")),
+            "",
+            A,
+            "commit",
+        )
+        .unwrap();
+        append_head_reflog(&repo.root, &Head::Detached(A.to_string()), A, B, "commit").unwrap();
+        // O referinta care nu avea si nu are commit nu este notata
+        append_reflog(&repo.root, "refs/heads/empty", "", "", "branch: Created").unwrap();
+
+        assert_eq!(read_reflog(&repo.root, "HEAD").unwrap().len(), 2);
+        assert_eq!(read_reflog(&repo.root, "refs/heads/main").unwrap().len(), 1);
+        assert!(!reflog_exists(&repo.root, "refs/heads/empty"));
+    }
+
+    #[test]
+    fn malformed_lines_are_skipped() {
+        let repo = TempRepo::new();
+        let main = "refs/heads/main";
+
+        append_reflog(&repo.root, main, "", A, "first").unwrap();
+
+        let path = reflog_path(&repo.root, main);
+        let mut content = fs::read_to_string(&path).unwrap();
+        content.push_str("garbage without a tab\n");
+        content.push_str(&format!("{} {} not-a-time\tbad time\n", A, B));
+        content.push_str(&format!("{}\tmissing new hash\n", A));
+        content.push('\n');
+        fs::write(&path, content).unwrap();
+
+        append_reflog(&repo.root, main, A, C, "last").unwrap();
+
+        let entries = read_reflog(&repo.root, main).unwrap();
+        let messages: Vec<&str> = entries.iter().map(|e| e.message.as_str()).collect();
+        assert_eq!(messages, vec!["last", "first"]);
+
+        // `gc` pastreaza doar hash-urile din liniile valide
+        assert_eq!(
+            reflog_hashes(&repo.root, 0).unwrap(),
+            vec![A.to_string(), C.to_string()]
+        );
+    }
+
+    #[test]
+    fn expired_entries_are_dropped_and_no_longer_kept() {
+        let repo = TempRepo::new();
+        let main = "refs/heads/main";
+        let path = reflog_path(&repo.root, main);
+
+        fs::create_dir_all(Path::new(&path).parent().unwrap()).unwrap();
+        fs::write(
+            &path,
+            format!(
+                "{} {} 100\told\n{} {} 200\tnewer\n",
+                "0".repeat(40),
+                A,
+                A,
+                B
+            ),
+        )
+        .unwrap();
+        fs::write(
+            reflog_path(&repo.root, "HEAD"),
+            format!("{} {} 100\told\n", C, A),
+        )
+        .unwrap();
+
+        assert_eq!(
+            reflog_hashes(&repo.root, 150).unwrap(),
+            vec![A.to_string(), B.to_string()]
+        );
+
+        assert_eq!(expire_reflogs(&repo.root, 150).unwrap(), 2);
+        let messages: Vec<String> = read_reflog(&repo.root, main)
+            .unwrap()
+            .into_iter()
+            .map(|e| e.message)
+            .collect();
+        assert_eq!(messages, vec!["newer"]);
+        assert!(!reflog_exists(&repo.root, "HEAD"));
+
+        assert_eq!(expire_reflogs(&repo.root, 150).unwrap(), 0);
+    }
+}
@@ -0,0 +1,600 @@
+use std::{collections::BTreeSet, fmt, path::Path};
+
+use crate::{
+    commands::{
+        branch::{get_branch_hash, head_commit},
+        commit::CommitObject,
+        tag::{get_tag_hash, peel_to_commit},
+    },
+    error_data,
+    utils::{
+        error::ErrorData,
+        hash::object_format,
+        json::load_object_json,
+        object::{ObjectType, list_loose_objects, object_exists, read_typed_object},
+        pack::list_packed_objects,
+        path::format_path,
+        reflog::{read_reflog, reflog_ref},
+    },
+};
+
+// Prefixele mai scurte ar fi aproape mereu ambigue
+const MIN_ABBREV_LEN: usize = 4;
+
+// Numele de ramuri si tag-uri: un singur fisier din `refs/heads` sau `refs/tags`,
+// fara caractere care ar fi citite ca sintaxa de revizie (`~`, `^`, `@{`, `..`, ...)
+pub fn valid_ref_name(name: &str) -> bool {
+    !name.is_empty()
+        && name != "HEAD"
+        && name != "@"
+        && !name.starts_with('-')
+        && !name.starts_with('.')
+        && !name.ends_with('.')
+        && !name.ends_with(".lock")
+        && !name.contains("..")
+        && !name.contains("@{")
+        && !name.chars().any(|c| {
+            matches!(c, '/' | '\\' | '~' | '^' | ':' | '?' | '*' | '[')
+                || c.is_whitespace()
+                || c.is_control()
+        })
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum RevisionError {
+    // Nu exista nicio ramura, tag sau obiect cu acest nume
+    Unknown(String),
+    // Prefixul se potriveste cu mai multe obiecte
+    Ambiguous(String, Vec<String>),
+    // Numele indica spre un tree sau un blob
+    NotACommit(String),
+    // `rev~N` / `rev^N` cer un parinte care nu exista
+    NoSuchParent(String),
+    // `ref@{N}` cere o intrare mai veche decat are reflog-ul
+    ReflogTooShort(String, usize),
+}
+
+impl fmt::Display for RevisionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RevisionError::Unknown(spec) => {
+                write!(f, "'{}' is not a branch, a tag or a commit.", spec)
+            }
+            RevisionError::Ambiguous(spec, candidates) => {
+                write!(f, "short object ID {} is ambiguous", spec)?;
+                write!(f, "\nhint: The candidates are:")?;
+                for candidate in candidates {
+                    write!(f, "\nhint:   {}", candidate)?;
+                }
+                Ok(())
+            }
+            RevisionError::NotACommit(spec) => write!(f, "'{}' is not a commit.", spec),
+            RevisionError::NoSuchParent(spec) => {
+                write!(f, "'{}' refers to a parent that does not exist.", spec)
+            }
+            RevisionError::ReflogTooShort(name, 0) => write!(f, "log for '{}' is empty.", name),
+            RevisionError::ReflogTooShort(name, count) => {
+                write!(f, "log for '{}' only has {} entries.", name, count)
+            }
+        }
+    }
+}
+
+pub type Resolution = Result<String, RevisionError>;
+
+// `rev1..rev2`: commit-urile accesibile din `to`, dar nu si din `from`
+#[derive(Debug, Clone, PartialEq)]
+pub enum Revisions {
+    Single(String),
+    Range { from: String, to: String },
+}
+
+// Obiectele (din pack-uri si individuale) al caror nume incepe cu `prefix`
+fn objects_with_prefix(root: &str, prefix: &str) -> Result<Vec<String>, ErrorData> {
+    let mut matches: BTreeSet<String> = list_packed_objects(root)
+        .map_err(|e| {
+            error_data!(
+                "objects_with_prefix",
+                e.to_string(),
+                "Failed to list packed objects"
+            )
+        })?
+        .into_iter()
+        .filter(|h| h.starts_with(prefix))
+        .collect();
+
+    for (hash, _) in list_loose_objects(root).map_err(|e| {
+        error_data!(
+            "objects_with_prefix",
+            e.to_string(),
+            "Failed to list loose objects"
+        )
+    })? {
+        if hash.starts_with(prefix) {
+            matches.insert(hash);
+        }
+    }
+
+    Ok(matches.into_iter().collect())
+}
+
+fn describe_object(root: &str, hash: &str) -> String {
+    match read_typed_object(root, hash) {
+        Ok((object_type, _)) => format!("{} {}", hash, object_type.as_str()),
+        Err(_) => hash.to_string(),
+    }
+}
+
+// `ref@{N}`: valoarea pe care o avea referinta acum N modificari (`@{0}` este
+// cea curenta); fara nume se foloseste ramura curenta
+fn resolve_reflog(
+    root: &str,
+    spec: &str,
+    base: &str,
+    index: &str,
+) -> Result<Resolution, ErrorData> {
+    let index: usize = match index.parse() {
+        Ok(n) => n,
+        Err(_) => return Ok(Err(RevisionError::Unknown(spec.to_string()))),
+    };
+
+    let ref_name = reflog_ref(root, base)
+        .map_err(|e| error_data!("resolve_reflog", e.to_string(), "Failed to find reflog"))?;
+    let entries = read_reflog(root, &ref_name)
+        .map_err(|e| error_data!("resolve_reflog", e.to_string(), "Failed to read reflog"))?;
+
+    match entries.get(index) {
+        Some(entry) if !entry.new.is_empty() => Ok(Ok(entry.new.clone())),
+        // Ramura nu avea inca niciun commit in acel moment
+        Some(_) => Ok(Err(RevisionError::Unknown(spec.to_string()))),
+        None => {
+            let name = ref_name
+                .strip_prefix("refs/heads/")
+                .or_else(|| ref_name.strip_prefix("refs/tags/"))
+                .unwrap_or(&ref_name);
+
+            Ok(Err(RevisionError::ReflogTooShort(
+                name.to_string(),
+                entries.len(),
+            )))
+        }
+    }
+}
+
+// Ordinea: reflog, HEAD, hash complet, ramura, tag, prefix de hash. Tag-urile
+// adnotate nu sunt urmate aici (`cat-file` trebuie sa poata afisa obiectul tag)
+fn resolve_name(root: &str, name: &str) -> Result<Resolution, ErrorData> {
+    let unknown = || Ok(Err(RevisionError::Unknown(name.to_string())));
+
+    if let Some(rest) = name.strip_suffix('}')
+        && let Some((base, index)) = rest.rsplit_once("@{")
+    {
+        return resolve_reflog(root, name, base, index);
+    }
+
+    if name == "HEAD" {
+        let hash = head_commit(root)
+            .map_err(|e| error_data!("resolve_name", e.to_string(), "Failed to read HEAD"))?;
+
+        return if hash.is_empty() {
+            unknown()
+        } else {
+            Ok(Ok(hash))
+        };
+    }
+
+    let hex_len = object_format(root)
+        .map_err(|e| {
+            error_data!(
+                "resolve_name",
+                e.to_string(),
+                "Failed to get repository object format"
+            )
+        })?
+        .hex_len();
+    let is_hex = !name.is_empty() && name.chars().all(|c| c.is_ascii_hexdigit());
+
+    if is_hex && name.len() == hex_len && object_exists(root, name) {
+        return Ok(Ok(name.to_string()));
+    }
+
+    if Path::new(&format_path(&vec![root, ".my_svn", "refs", "heads", name])).is_file() {
+        let hash = get_branch_hash(root, name)
+            .map_err(|e| error_data!("resolve_name", e.to_string(), "Failed to get branch hash"))?;
+
+        return if hash.is_empty() {
+            unknown()
+        } else {
+            Ok(Ok(hash))
+        };
+    }
+
+    if let Some(hash) = get_tag_hash(root, name)
+        .map_err(|e| error_data!("resolve_name", e.to_string(), "Failed to get tag hash"))?
+    {
+        return Ok(Ok(hash));
+    }
+
+    if !is_hex || name.len() < MIN_ABBREV_LEN || name.len() > hex_len {
+        return unknown();
+    }
+
+    let prefix = name.to_ascii_lowercase();
+    let matches = objects_with_prefix(root, &prefix).map_err(|e| {
+        error_data!(
+            "resolve_name",
+            e.to_string(),
+            "Failed to look up abbreviated hash"
+        )
+    })?;
+
+    match matches.as_slice() {
+        [] => unknown(),
+        [hash] => Ok(Ok(hash.clone())),
+        _ => Ok(Err(RevisionError::Ambiguous(
+            name.to_string(),
+            matches.iter().map(|h| describe_object(root, h)).collect(),
+        ))),
+    }
+}
+
+fn to_commit(root: &str, spec: &str, hash: &str) -> Result<Resolution, ErrorData> {
+    let (object_type, _) = read_typed_object(root, hash)
+        .map_err(|e| error_data!("to_commit", e.to_string(), "Failed to read object"))?;
+
+    match object_type {
+        ObjectType::Commit => Ok(Ok(hash.to_string())),
+        ObjectType::Tag => peel_to_commit(root, hash)
+            .map(Ok)
+            .map_err(|e| error_data!("to_commit", e.to_string(), "Failed to peel tag")),
+        _ => Ok(Err(RevisionError::NotACommit(spec.to_string()))),
+    }
+}
+
+fn parent_of(root: &str, hash: &str, index: usize) -> Result<Option<String>, ErrorData> {
+    let commit: CommitObject = load_object_json(root, hash)
+        .map_err(|e| error_data!("parent_of", e.to_string(), "Failed to load commit"))?;
+
+    Ok(commit.parents().get(index).cloned())
+}
+
+// Numarul de dupa `~` sau `^` (lipsa inseamna 1) si restul expresiei
+fn split_count(rest: &str) -> Option<(usize, &str)> {
+    let digits = rest.chars().take_while(|c| c.is_ascii_digit()).count();
+
+    if digits == 0 {
+        return Some((1, rest));
+    }
+
+    Some((rest[..digits].parse().ok()?, &rest[digits..]))
+}
+
+// Un singur obiect: `<nume>` urmat optional de `~N` (al N-lea stramos pe
+// linia primilor parinti) si `^N` (al N-lea parinte al unui merge, `^0` este
+// commit-ul insusi), in orice combinatie
+pub fn resolve_object(root: &str, spec: &str) -> Result<Resolution, ErrorData> {
+    let split = spec.find(['~', '^']).unwrap_or(spec.len());
+    let (name, mut suffix) = spec.split_at(split);
+
+    let mut hash = match resolve_name(root, name)
+        .map_err(|e| error_data!("resolve_object", e.to_string(), "Failed to resolve name"))?
+    {
+        Ok(hash) => hash,
+        Err(e) => return Ok(Err(e)),
+    };
+
+    while let Some(operator) = suffix.chars().next() {
+        if operator != '~' && operator != '^' {
+            return Ok(Err(RevisionError::Unknown(spec.to_string())));
+        }
+
+        let (count, rest) = match split_count(&suffix[1..]) {
+            Some(c) => c,
+            None => return Ok(Err(RevisionError::Unknown(spec.to_string()))),
+        };
+        suffix = rest;
+
+        hash = match to_commit(root, spec, &hash)? {
+            Ok(h) => h,
+            Err(e) => return Ok(Err(e)),
+        };
+
+        let steps = match operator {
+            '~' => count,
+            '^' if count == 0 => continue,
+            _ => 1,
+        };
+        let index = if operator == '^' { count - 1 } else { 0 };
+
+        for _ in 0..steps {
+            hash = match parent_of(root, &hash, index).map_err(|e| {
+                error_data!("resolve_object", e.to_string(), "Failed to read parents")
+            })? {
+                Some(parent) => parent,
+                None => return Ok(Err(RevisionError::NoSuchParent(spec.to_string()))),
+            };
+        }
+    }
+
+    Ok(Ok(hash))
+}
+
+// Ca `resolve_object`, dar tag-urile adnotate sunt urmate pana la commit, iar
+// tree-urile si blob-urile sunt refuzate
+pub fn resolve_commit(root: &str, spec: &str) -> Result<Resolution, ErrorData> {
+    match resolve_object(root, spec).map_err(|e| {
+        error_data!(
+            "resolve_commit",
+            e.to_string(),
+            "Failed to resolve revision"
+        )
+    })? {
+        Ok(hash) => to_commit(root, spec, &hash),
+        Err(e) => Ok(Err(e)),
+    }
+}
+
+// `rev` sau `rev1..rev2`; o parte lipsa a intervalului inseamna HEAD
+pub fn resolve_revisions(
+    root: &str,
+    spec: &str,
+) -> Result<Result<Revisions, RevisionError>, ErrorData> {
+    let (from, to) = match spec.split_once("..") {
+        None => {
+            return resolve_commit(root, spec).map(|r| r.map(Revisions::Single));
+        }
+        Some((from, to)) => (
+            if from.is_empty() { "HEAD" } else { from },
+            if to.is_empty() { "HEAD" } else { to },
+        ),
+    };
+
+    let from = match resolve_commit(root, from)? {
+        Ok(h) => h,
+        Err(e) => return Ok(Err(e)),
+    };
+    let to = match resolve_commit(root, to)? {
+        Ok(h) => h,
+        Err(e) => return Ok(Err(e)),
+    };
+
+    Ok(Ok(Revisions::Range { from, to }))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::*;
+    use crate::{
+        commands::commit::write_index_tree,
+        utils::{hash::hash_bytes, object::write_object, reflog::append_reflog, testing::TempRepo},
+    };
+
+    fn write_commit(repo: &TempRepo, message: &str, parents: &[&str]) -> String {
+        let tree = write_index_tree(&repo.root, &HashMap::new(), &mut HashMap::new()).unwrap();
+        let commit = CommitObject {
+            tree,
+            parent: Some(parents.iter().map(|p| p.to_string()).collect()),
+            message: message.to_string(),
+            timestamp: 0,
+            author: None,
+            committer: None,
+        };
+
+        let json = serde_json::to_string(&commit).unwrap();
+        let hash = hash_bytes(&repo.root, json.as_bytes()).unwrap();
+        write_object(&repo.root, &hash, ObjectType::Commit, json.as_bytes()).unwrap();
+        hash
+    }
+
+    fn set_branch(repo: &TempRepo, branch: &str, hash: &str) {
+        repo.write(&format!(".my_svn/refs/heads/{}", branch), hash);
+    }
+
+    fn resolve(repo: &TempRepo, spec: &str) -> Resolution {
+        resolve_commit(&repo.root, spec).unwrap()
+    }
+
+    // main: c0 <- c1 <- c2 <- merge, al doilea parinte al merge-ului fiind side (<- c0)
+    struct History {
+        c0: String,
+        c1: String,
+        c2: String,
+        side: String,
+        merge: String,
+    }
+
+    fn history(repo: &TempRepo) -> History {
+        let c0 = write_commit(repo, "c0", &[]);
+        let c1 = write_commit(repo, "c1", &[&c0]);
+        let c2 = write_commit(repo, "c2", &[&c1]);
+        let side = write_commit(repo, "side", &[&c0]);
+        let merge = write_commit(repo, "merge", &[&c2, &side]);
+
+        set_branch(repo, "main", &merge);
+        set_branch(repo, "side", &side);
+
+        History {
+            c0,
+            c1,
+            c2,
+            side,
+            merge,
+        }
+    }
+
+    #[test]
+    fn tilde_follows_first_parents() {
+        let repo = TempRepo::new();
+        let h = history(&repo);
+
+        assert_eq!(resolve(&repo, "main~0"), Ok(h.merge.clone()));
+        assert_eq!(resolve(&repo, "main~"), Ok(h.c2.clone()));
+        assert_eq!(resolve(&repo, "main~2"), Ok(h.c1));
+        assert_eq!(resolve(&repo, "HEAD~3"), Ok(h.c0.clone()));
+        assert_eq!(resolve(&repo, "main~1~2"), Ok(h.c0));
+    }
+
+    #[test]
+    fn caret_selects_merge_parents() {
+        let repo = TempRepo::new();
+        let h = history(&repo);
+
+        assert_eq!(resolve(&repo, "main^0"), Ok(h.merge));
+        assert_eq!(resolve(&repo, "main^"), Ok(h.c2.clone()));
+        assert_eq!(resolve(&repo, "main^1"), Ok(h.c2));
+        assert_eq!(resolve(&repo, "main^2"), Ok(h.side));
+        assert_eq!(resolve(&repo, "main^2~1"), Ok(h.c0.clone()));
+        assert_eq!(resolve(&repo, "main^2^"), Ok(h.c0));
+    }
+
+    #[test]
+    fn missing_parent_is_an_error() {
+        let repo = TempRepo::new();
+        history(&repo);
+
+        for spec in ["main~4", "main^3", "side~2", "main^2^2"] {
+            assert_eq!(
+                resolve(&repo, spec),
+                Err(RevisionError::NoSuchParent(spec.to_string()))
+            );
+        }
+    }
+
+    #[test]
+    fn reflog_entries_are_counted_from_newest() {
+        let repo = TempRepo::new();
+        let h = history(&repo);
+
+        append_reflog(&repo.root, "refs/heads/main", "", &h.c1, "commit").unwrap();
+        append_reflog(&repo.root, "refs/heads/main", &h.c1, &h.c2, "commit").unwrap();
+        append_reflog(&repo.root, "refs/heads/main", &h.c2, &h.merge, "merge").unwrap();
+
+        assert_eq!(resolve(&repo, "main@{0}"), Ok(h.merge));
+        assert_eq!(resolve(&repo, "main@{2}"), Ok(h.c1.clone()));
+        // Fara nume: ramura curenta
+        assert_eq!(resolve(&repo, "@{1}"), Ok(h.c2));
+        assert_eq!(resolve(&repo, "main@{1}^"), Ok(h.c1));
+
+        assert_eq!(
+            resolve(&repo, "main@{3}"),
+            Err(RevisionError::ReflogTooShort(String::from("main"), 3))
+        );
+        assert_eq!(
+            resolve(&repo, "side@{0}"),
+            Err(RevisionError::ReflogTooShort(String::from("side"), 0))
+        );
+    }
+
+    #[test]
+    fn abbreviated_hashes_resolve_when_unique() {
+        let repo = TempRepo::new();
+        let h = history(&repo);
+
+        assert_eq!(resolve(&repo, &h.side[..7]), Ok(h.side.clone()));
+        assert_eq!(
+            resolve(&repo, &h.side[..MIN_ABBREV_LEN].to_ascii_uppercase()),
+            Ok(h.side.clone())
+        );
+        assert_eq!(
+            resolve(&repo, &h.side[..MIN_ABBREV_LEN - 1]),
+            Err(RevisionError::Unknown(
+                h.side[..MIN_ABBREV_LEN - 1].to_string()
+            ))
+        );
+    }
+
+    #[test]
+    fn ambiguous_prefix_lists_candidates() {
+        let repo = TempRepo::new();
+
+        // Doua blob-uri cu acelasi prefix de MIN_ABBREV_LEN caractere
+        let mut seen: HashMap<String, (String, String)> = HashMap::new();
+        let (first, second) = (0..)
+            .find_map(|i| {
+                let content = format!("blob {}", i);
+                let hash = hash_bytes(&repo.root, content.as_bytes()).unwrap();
+                match seen.insert(hash[..MIN_ABBREV_LEN].to_string(), (hash.clone(), content)) {
+                    Some(previous) if previous.0 != hash => {
+                        Some((previous, (hash, format!("blob {}", i))))
+                    }
+                    _ => None,
+                }
+            })
+            .unwrap();
+
+        for (hash, content) in [&first, &second] {
+            write_object(&repo.root, hash, ObjectType::Blob, content.as_bytes()).unwrap();
+        }
+
+        let prefix = &first.0[..MIN_ABBREV_LEN];
+        let mut candidates = vec![format!("{} blob", first.0), format!("{} blob", second.0)];
+        candidates.sort();
+
+        assert_eq!(
+            resolve_object(&repo.root, prefix).unwrap(),
+            Err(RevisionError::Ambiguous(prefix.to_string(), candidates))
+        );
+
+        // Un prefix mai lung le deosebeste
+        let longer = first
+            .0
+            .char_indices()
+            .find(|(i, c)| second.0.as_bytes()[*i] as char != *c)
+            .map(|(i, _)| i + 1)
+            .unwrap();
+        assert_eq!(
+            resolve_object(&repo.root, &first.0[..longer]).unwrap(),
+            Ok(first.0.clone())
+        );
+    }
+
+    #[test]
+    fn ranges_default_to_head() {
+        let repo = TempRepo::new();
+        let h = history(&repo);
+
+        let range = |from: &String, to: &String| {
+            Ok(Revisions::Range {
+                from: from.clone(),
+                to: to.clone(),
+            })
+        };
+
+        assert_eq!(
+            resolve_revisions(&repo.root, "side..main").unwrap(),
+            range(&h.side, &h.merge)
+        );
+        assert_eq!(
+            resolve_revisions(&repo.root, "main~2..").unwrap(),
+            range(&h.c1, &h.merge)
+        );
+        assert_eq!(
+            resolve_revisions(&repo.root, "..side").unwrap(),
+            range(&h.merge, &h.side)
+        );
+        assert_eq!(
+            resolve_revisions(&repo.root, "main^2").unwrap(),
+            Ok(Revisions::Single(h.side))
+        );
+        assert_eq!(
+            resolve_revisions(&repo.root, "side..nope").unwrap(),
+            Err(RevisionError::Unknown(String::from("nope")))
+        );
+    }
+
+    #[test]
+    fn ref_names_reject_revision_syntax() {
+        for name in ["main", "feature-1", "v1.0", "fix_bug", "a@b"] {
+            assert!(valid_ref_name(name), "{}", name);
+        }
+
+        for name in [
+            "", "HEAD", "@", "-x", ".x", "x.", "x.lock", "a..b", "a@{1}", "a/b", "a~1", "a^",
+            "a:b", "a?", "a*", "a[", "a b", "a\tb",
+        ] {
+            assert!(!valid_ref_name(name), "{:?}", name);
+        }
+    }
+}
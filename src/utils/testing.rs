@@ -0,0 +1,66 @@
+use std::{
+    fs,
+    path::Path,
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+use crate::{
+    commands::init::create_repository,
+    utils::{hash::HashAlgorithm, path::format_path},
+};
+
+static NEXT_ID: AtomicUsize = AtomicUsize::new(0);
+
+// Un depozit gol intr-un director temporar, sters la drop. Fiecare test are
+// propriul director, deci cache-urile globale (indexate dupa radacina) nu se amesteca.
+pub struct TempRepo {
+    pub root: String,
+}
+
+impl Default for TempRepo {
+    fn default() -> Self {
+        Self::with_format(HashAlgorithm::Sha1)
+    }
+}
+
+impl TempRepo {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_format(object_format: HashAlgorithm) -> Self {
+        let dir = std::env::temp_dir().join(format!(
+            "my_svn-test-{}-{}",
+            std::process::id(),
+            NEXT_ID.fetch_add(1, Ordering::SeqCst)
+        ));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).expect("create temp dir");
+
+        let root = dunce::canonicalize(&dir)
+            .expect("canonicalize temp dir")
+            .to_string_lossy()
+            .to_string();
+        create_repository(&root, Some(object_format)).expect("create repository");
+
+        Self { root }
+    }
+
+    pub fn path(&self, relative: &str) -> String {
+        format_path(&vec![&self.root, relative])
+    }
+
+    pub fn write(&self, relative: &str, content: &str) {
+        let path = self.path(relative);
+        if let Some(parent) = Path::new(&path).parent() {
+            fs::create_dir_all(parent).expect("create parent dir");
+        }
+        fs::write(path, content).expect("write file");
+    }
+}
+
+impl Drop for TempRepo {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.root);
+    }
+}
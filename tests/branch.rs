@@ -0,0 +1,20 @@
+mod common;
+
+use common::TempDir;
+
+#[test]
+fn renaming_a_branch_to_its_own_name_changes_nothing() {
+    let repo = TempDir::new();
+
+    repo.write("a.txt", "a\n");
+    repo.ok(&["add", "a.txt"]);
+    repo.ok(&["commit", "-m", "one"]);
+    let tip = repo.rev("main");
+
+    repo.ok(&["branch", "-m", "main", "main"]);
+    repo.ok(&["branch", "-m", "main"]);
+
+    assert_eq!(repo.rev("main"), tip);
+    assert!(repo.ok(&["branch"]).contains("* main"));
+    assert!(!std::path::Path::new(&repo.path(".my_svn/refs/heads/main.lock")).exists());
+}
@@ -0,0 +1,71 @@
+mod common;
+
+use common::TempDir;
+
+#[cfg(unix)]
+#[test]
+fn executable_bit_and_symlink_survive_commit_and_checkout() {
+    use std::{fs, os::unix::fs::PermissionsExt};
+
+    let repo = TempDir::new();
+    repo.write("a.txt", "a\n");
+    repo.ok(&["add", "a.txt"]);
+    repo.ok(&["commit", "-m", "base"]);
+    repo.ok(&["branch", "plain"]);
+
+    repo.script("run.sh", "echo run");
+    std::os::unix::fs::symlink("a.txt", repo.path("link")).unwrap();
+    repo.ok(&["add", "."]);
+    repo.ok(&["commit", "-m", "modes"]);
+
+    let tree = repo.tree("HEAD");
+    assert_eq!(tree["run.sh"].0, "100755");
+    assert_eq!(tree["link"].0, "120000");
+    assert_eq!(tree["a.txt"].0, "100644");
+
+    // Fisierele dispar pe ramura fara ele si revin la loc cu modul lor
+    repo.ok(&["checkout", "plain"]);
+    assert!(!repo.path("run.sh").exists());
+    assert!(fs::symlink_metadata(repo.path("link")).is_err());
+
+    repo.ok(&["checkout", "main"]);
+    let mode = fs::metadata(repo.path("run.sh"))
+        .unwrap()
+        .permissions()
+        .mode();
+    assert_ne!(mode & 0o111, 0);
+    assert_eq!(repo.read("run.sh"), "#!/bin/sh\necho run\n");
+    assert!(
+        fs::symlink_metadata(repo.path("link"))
+            .unwrap()
+            .file_type()
+            .is_symlink()
+    );
+    assert_eq!(
+        fs::read_link(repo.path("link")).unwrap(),
+        std::path::PathBuf::from("a.txt")
+    );
+
+    assert!(!repo.ok(&["status"]).contains("mode change"));
+}
+
+#[test]
+fn commit_on_detached_head_moves_head_not_a_branch() {
+    let repo = TempDir::new();
+    repo.write("a.txt", "a\n");
+    repo.ok(&["add", "a.txt"]);
+    repo.ok(&["commit", "-m", "first"]);
+    let first = repo.rev("main");
+
+    repo.ok(&["checkout", &first]);
+    repo.write("a.txt", "detached\n");
+    repo.ok(&["add", "a.txt"]);
+    repo.ok(&["commit", "-m", "detached"]);
+
+    let detached = repo.rev("HEAD");
+    assert_ne!(detached, first);
+    assert_eq!(repo.rev("HEAD~1"), first);
+    assert_eq!(repo.rev("main"), first);
+    assert_eq!(repo.read(".my_svn/HEAD").trim(), detached);
+    assert!(repo.ok(&["status"]).contains("HEAD detached at"));
+}
@@ -0,0 +1,161 @@
+mod common;
+
+use std::{
+    fs,
+    time::{Duration, SystemTime},
+};
+
+use common::{TempDir, set_mtime};
+
+#[test]
+fn touched_file_is_nothing_to_commit() {
+    let repo = TempDir::new();
+    let file = repo.path("a.txt");
+
+    fs::write(&file, "content\n").unwrap();
+    set_mtime(&file, SystemTime::now() - Duration::from_secs(60));
+    assert!(repo.run(&["add", "a.txt"]).status.success());
+    assert!(repo.run(&["commit", "-m", "first"]).status.success());
+
+    // Doar data modificarii se schimba: arborele ramane acelasi
+    set_mtime(&file, SystemTime::now());
+    assert!(repo.run(&["add", "a.txt"]).status.success());
+
+    let output = repo.run(&["commit", "-m", "second"]);
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stdout).contains("nothing to commit"));
+
+    assert!(
+        repo.run(&["commit", "--allow-empty", "-m", "second"])
+            .status
+            .success()
+    );
+}
+
+#[cfg(unix)]
+#[test]
+fn editor_is_not_opened_when_there_is_nothing_to_commit() {
+    let repo = TempDir::new();
+    repo.write("a.txt", "a\n");
+    repo.ok(&["add", "a.txt"]);
+    repo.ok(&["commit", "-m", "first"]);
+
+    // Scripturile din `.my_svn` nu apar in status
+    let called = repo.path(".my_svn/editor-called");
+    repo.script(
+        ".my_svn/editor.sh",
+        &format!("touch '{}'\necho message > \"$1\"", called.display()),
+    );
+    let editor_path = repo.path(".my_svn/editor.sh");
+    let env = [("EDITOR", editor_path.to_str().unwrap())];
+
+    let output = repo.run_with_env(&["commit"], &env);
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stdout).contains("nothing to commit"));
+    assert!(!called.exists());
+
+    repo.write("a.txt", "changed\n");
+    repo.ok(&["add", "a.txt"]);
+    assert!(repo.run_with_env(&["commit"], &env).status.success());
+    assert!(called.exists());
+}
+
+// a.txt si b.txt sunt in primul commit, apoi amandoua sunt modificate si doar
+// b.txt este adaugat in index
+fn partly_staged() -> TempDir {
+    let repo = TempDir::new();
+    repo.write("a.txt", "a\n");
+    repo.write("b.txt", "b\n");
+    repo.ok(&["add", "."]);
+    repo.ok(&["commit", "-m", "first"]);
+
+    repo.write("a.txt", "a changed\n");
+    repo.write("b.txt", "b changed\n");
+    repo.ok(&["add", "b.txt"]);
+    repo
+}
+
+#[cfg(unix)]
+#[test]
+fn pathspec_is_staged_before_pre_commit() {
+    let repo = partly_staged();
+    repo.script(
+        ".my_svn/hooks/pre-commit",
+        "\"$MY_SVN\" status > .my_svn/status-seen",
+    );
+
+    let output = repo.run_with_env(
+        &["commit", "a.txt", "-m", "only a"],
+        &[("MY_SVN", env!("CARGO_BIN_EXE_My_SVN"))],
+    );
+    assert!(output.status.success());
+
+    // Hook-ul vede a.txt in index, nu doar in directorul de lucru
+    let seen = repo.read(".my_svn/status-seen");
+    let staged = seen.split("Changes not staged").next().unwrap();
+    assert!(staged.contains("a.txt"), "{}", seen);
+}
+
+#[test]
+fn pathspec_commit_keeps_other_staged_entries() {
+    let repo = partly_staged();
+
+    repo.ok(&["commit", "a.txt", "-m", "only a"]);
+
+    // b.txt nu a intrat in commit, dar ramane adaugat pentru urmatorul
+    let status = repo.ok(&["status"]);
+    assert!(status.contains("Changes to be committed"), "{}", status);
+    assert!(status.contains("b.txt"), "{}", status);
+    assert!(!status.contains("a.txt"), "{}", status);
+
+    let (first, only_a) = (repo.tree("HEAD~1"), repo.tree("HEAD"));
+    assert_ne!(only_a["a.txt"], first["a.txt"]);
+    assert_eq!(only_a["b.txt"], first["b.txt"]);
+
+    repo.ok(&["commit", "-m", "then b"]);
+    assert_ne!(repo.tree("HEAD")["b.txt"], first["b.txt"]);
+    assert!(repo.ok(&["status"]).contains("working tree clean"));
+}
+
+#[test]
+fn commit_all_stages_tracked_deletions() {
+    let repo = TempDir::new();
+    repo.write("kept.txt", "kept\n");
+    repo.write("gone.txt", "gone\n");
+    repo.ok(&["add", "."]);
+    repo.ok(&["commit", "-m", "first"]);
+
+    fs::remove_file(repo.path("gone.txt")).unwrap();
+    repo.write("kept.txt", "changed\n");
+    repo.write("new.txt", "untracked\n");
+    repo.ok(&["commit", "-a", "-m", "second"]);
+
+    // `-a` nu adauga fisierele neurmarite
+    let tree = repo.tree("HEAD");
+    let mut names: Vec<&String> = tree.keys().collect();
+    names.sort();
+    assert_eq!(names, vec!["kept.txt"]);
+    assert_ne!(tree["kept.txt"].1, repo.tree("HEAD~1")["kept.txt"].1);
+
+    let status = repo.ok(&["status"]);
+    assert!(!status.contains("gone.txt"));
+    assert!(status.contains("new.txt"));
+}
+
+#[test]
+fn unchanged_tree_is_refused_unless_allow_empty() {
+    let repo = TempDir::new();
+    repo.write("a.txt", "a\n");
+    repo.ok(&["add", "a.txt"]);
+    repo.ok(&["commit", "-m", "first"]);
+    let first = repo.rev("HEAD");
+
+    let output = repo.run(&["commit", "-m", "again"]);
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stdout).contains("nothing to commit"));
+    assert_eq!(repo.rev("HEAD"), first);
+
+    repo.ok(&["commit", "--allow-empty", "-m", "again"]);
+    assert_eq!(repo.rev("HEAD~1"), first);
+    assert_eq!(repo.tree("HEAD"), repo.tree("HEAD~1"));
+}
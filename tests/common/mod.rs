@@ -0,0 +1,143 @@
+#![allow(dead_code)]
+
+use std::{
+    collections::HashMap,
+    fs,
+    path::{Path, PathBuf},
+    process::{Command, Output},
+    sync::atomic::{AtomicUsize, Ordering},
+    time::{Duration, SystemTime},
+};
+
+static NEXT_ID: AtomicUsize = AtomicUsize::new(0);
+static NEXT_MTIME: AtomicUsize = AtomicUsize::new(0);
+
+// Un director temporar cu un depozit nou, sters la drop
+pub struct TempDir(PathBuf);
+
+impl TempDir {
+    pub fn new() -> Self {
+        let dir = std::env::temp_dir().join(format!(
+            "my_svn-cli-{}-{}",
+            std::process::id(),
+            NEXT_ID.fetch_add(1, Ordering::SeqCst)
+        ));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).expect("create temp dir");
+
+        let temp = Self(dir);
+        temp.ok(&["init"]);
+        temp.ok(&["config", "user.name", "tester"]);
+        temp
+    }
+
+    pub fn run(&self, args: &[&str]) -> Output {
+        self.run_with_env(args, &[])
+    }
+
+    pub fn run_with_env(&self, args: &[&str], vars: &[(&str, &str)]) -> Output {
+        Command::new(env!("CARGO_BIN_EXE_My_SVN"))
+            .args(args)
+            .envs(vars.iter().copied())
+            .current_dir(&self.0)
+            .output()
+            .expect("run my_svn")
+    }
+
+    // Ruleaza comanda si intoarce ce a afisat; comenzile care nu reusesc
+    // afiseaza "fatal: ..." sau "Error at ..." chiar daca iesirea este 0
+    pub fn ok(&self, args: &[&str]) -> String {
+        let output = self.run(args);
+        let stdout = String::from_utf8_lossy(&output.stdout).to_string();
+
+        assert!(
+            output.status.success() && !stdout.contains("fatal:") && !stdout.contains("Error at"),
+            "my_svn {:?} failed:\n{}{}",
+            args,
+            stdout,
+            String::from_utf8_lossy(&output.stderr)
+        );
+        stdout
+    }
+
+    pub fn path(&self, relative: &str) -> PathBuf {
+        self.0.join(relative)
+    }
+
+    // Fiecare fisier scris primeste o data de modificare diferita, ca `add`
+    // sa nu il considere neschimbat cand doua scrieri cad in aceeasi secunda
+    pub fn write(&self, relative: &str, content: &str) {
+        let path = self.path(relative);
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).expect("create parent dir");
+        }
+        fs::write(&path, content).expect("write file");
+
+        let step = NEXT_MTIME.fetch_add(1, Ordering::SeqCst) as u64;
+        set_mtime(
+            &path,
+            SystemTime::now() - Duration::from_secs(100_000) + Duration::from_secs(step),
+        );
+    }
+
+    // Un script executabil (editor, hook) scris in depozit
+    #[cfg(unix)]
+    pub fn script(&self, relative: &str, body: &str) {
+        use std::os::unix::fs::PermissionsExt;
+
+        self.write(relative, &format!("#!/bin/sh\n{}\n", body));
+        fs::set_permissions(self.path(relative), fs::Permissions::from_mode(0o755))
+            .expect("make script executable");
+    }
+
+    pub fn read(&self, relative: &str) -> String {
+        fs::read_to_string(self.path(relative)).expect("read file")
+    }
+
+    // Hash-ul complet al unei revizii
+    pub fn rev(&self, spec: &str) -> String {
+        self.ok(&["log", spec])
+            .lines()
+            .find_map(|l| l.strip_prefix("Commit: "))
+            .expect("log shows a commit")
+            .trim()
+            .to_string()
+    }
+
+    // Intrarile arborelui de la radacina unei revizii: nume -> (mod, hash)
+    pub fn tree(&self, spec: &str) -> HashMap<String, (String, String)> {
+        let commit = self.ok(&["cat-file", "-p", spec]);
+        let tree = commit
+            .lines()
+            .find_map(|l| l.strip_prefix("tree "))
+            .expect("commit has a tree")
+            .trim()
+            .to_string();
+
+        self.ok(&["cat-file", "-p", &tree])
+            .lines()
+            .filter_map(|line| {
+                let (fields, name) = line.split_once('\t')?;
+                let mut fields = fields.split(' ');
+                let mode = fields.next()?.to_string();
+                let hash = fields.nth(1)?.to_string();
+                Some((name.to_string(), (mode, hash)))
+            })
+            .collect()
+    }
+}
+
+impl Drop for TempDir {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.0);
+    }
+}
+
+pub fn set_mtime(path: &Path, time: SystemTime) {
+    fs::File::options()
+        .write(true)
+        .open(path)
+        .expect("open file")
+        .set_modified(time)
+        .expect("set mtime");
+}
@@ -0,0 +1,93 @@
+mod common;
+
+use std::fs;
+
+use common::TempDir;
+
+// main: base -> main; side: base -> side, cu fisiere diferite schimbate
+fn diverged() -> TempDir {
+    let repo = TempDir::new();
+
+    repo.write("a.txt", "a\n");
+    repo.write("b.txt", "b\n");
+    repo.ok(&["add", "."]);
+    repo.ok(&["commit", "-m", "base"]);
+
+    repo.ok(&["branch", "side"]);
+    repo.ok(&["checkout", "side"]);
+    repo.write("b.txt", "b from side\n");
+    repo.ok(&["add", "b.txt"]);
+    repo.ok(&["commit", "-m", "side"]);
+
+    repo.ok(&["checkout", "main"]);
+    repo.write("a.txt", "a from main\n");
+    repo.ok(&["add", "a.txt"]);
+    repo.ok(&["commit", "-m", "main"]);
+
+    repo
+}
+
+#[test]
+fn diverged_branches_get_a_merge_commit() {
+    let repo = diverged();
+    let main = repo.rev("main");
+    let side = repo.rev("side");
+
+    repo.ok(&["merge", "side"]);
+
+    assert_eq!(repo.rev("HEAD^1"), main);
+    assert_eq!(repo.rev("HEAD^2"), side);
+    assert_eq!(repo.read("a.txt"), "a from main\n");
+    assert_eq!(repo.read("b.txt"), "b from side\n");
+}
+
+#[test]
+fn ancestor_or_same_commit_is_already_up_to_date() {
+    let repo = diverged();
+    let main = repo.rev("main");
+
+    for target in ["HEAD", "main", "HEAD~1"] {
+        assert!(repo.ok(&["merge", target]).contains("Already up to date."));
+        assert_eq!(repo.rev("HEAD"), main);
+    }
+}
+
+#[test]
+fn descendant_is_fast_forwarded() {
+    let repo = diverged();
+    let side = repo.rev("side");
+
+    repo.ok(&["checkout", "side~1"]);
+    repo.ok(&["branch", "behind"]);
+    repo.ok(&["checkout", "behind"]);
+
+    assert!(repo.ok(&["merge", "side"]).contains("Fast-forward"));
+    assert_eq!(repo.rev("behind"), side);
+    assert_eq!(repo.read("b.txt"), "b from side\n");
+    assert!(
+        repo.ok(&["reflog", "behind"])
+            .contains("merge side: Fast-forward")
+    );
+}
+
+#[test]
+fn locked_branch_leaves_working_tree_and_index_untouched() {
+    let repo = diverged();
+    let main = repo.rev("main");
+
+    // Ca o alta comanda care muta ramura in acelasi timp
+    let lock = repo.path(".my_svn/refs/heads/main.lock");
+    fs::write(&lock, "").unwrap();
+    let index = fs::read(repo.path(".my_svn/index")).unwrap();
+
+    let output = repo.run(&["merge", "side"]);
+    assert!(String::from_utf8_lossy(&output.stdout).contains("Error at merge"));
+
+    assert_eq!(repo.read("b.txt"), "b\n");
+    assert_eq!(fs::read(repo.path(".my_svn/index")).unwrap(), index);
+
+    fs::remove_file(&lock).unwrap();
+    assert_eq!(repo.rev("HEAD"), main);
+    repo.ok(&["merge", "side"]);
+    assert_eq!(repo.read("b.txt"), "b from side\n");
+}
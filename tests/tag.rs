@@ -0,0 +1,32 @@
+mod common;
+
+use common::TempDir;
+
+#[test]
+fn annotated_tag_peels_to_its_commit() {
+    let repo = TempDir::new();
+    repo.write("a.txt", "a\n");
+    repo.ok(&["add", "a.txt"]);
+    repo.ok(&["commit", "-m", "release"]);
+    let commit = repo.rev("HEAD");
+
+    repo.ok(&["tag", "-a", "-m", "First release", "v1.0"]);
+    repo.write("a.txt", "after\n");
+    repo.ok(&["add", "a.txt"]);
+    repo.ok(&["commit", "-m", "after"]);
+
+    // Referinta indica spre obiectul tag, care indica spre commit
+    let tag_object = repo.read(".my_svn/refs/tags/v1.0").trim().to_string();
+    assert_ne!(tag_object, commit);
+    assert_eq!(repo.ok(&["cat-file", "-t", "v1.0"]).trim(), "tag");
+    let shown = repo.ok(&["cat-file", "-p", "v1.0"]);
+    assert!(shown.contains(&format!("object {}", commit)));
+    assert!(shown.contains("First release"));
+
+    assert_eq!(repo.rev("v1.0"), commit);
+    assert_eq!(repo.rev("v1.0~0"), commit);
+
+    repo.ok(&["checkout", "v1.0"]);
+    assert_eq!(repo.rev("HEAD"), commit);
+    assert_eq!(repo.read("a.txt"), "a\n");
+}